@@ -0,0 +1,195 @@
+//! the interpreter core: dispatch, calls, copies, signature checks and tokenizing
+//! run with `cargo bench --bench core [-- filter]`, see harness/mod.rs for the output
+
+mod harness;
+
+use harness::Bench;
+use pal_forth::buildins::*;
+use pal_forth::input::InputStream;
+use pal_forth::input::WordStream;
+use pal_forth::ir::CompEasyMemory;
+use pal_forth::lex::LexEasyMemory;
+use pal_forth::prelude::load_prelude;
+use pal_forth::types::READ_FLAG;
+use pal_forth::types::SigItem;
+use pal_forth::types::WRITE_FLAG;
+use pal_forth::vm::Code;
+use pal_forth::vm::Vm;
+use pal_forth::vm::VmEasyMemory;
+use std::hint::black_box;
+use std::mem::ManuallyDrop;
+
+///runs a word iters times on either dispatch loop
+fn run_word(vm: &mut Vm, word: &Code, fast: bool, iters: u64) {
+    for _ in 0..iters {
+        unsafe {
+            if fast {
+                vm.execute_code_fast(black_box(word));
+            } else {
+                vm.execute_code_plain(black_box(word));
+            }
+        }
+    }
+}
+
+const LOOPS: [(&str, bool); 2] = [("plain", false), ("fast", true)];
+
+fn dispatch(b: &mut Bench) {
+    let mut body: Vec<Code> = (0..1000).map(|_| Code::basic(op::no_op, 0)).collect();
+    body.push(Code::basic(op::ret, 0));
+    let word = Code::word(&body);
+
+    let mut mem = VmEasyMemory::<64>::new();
+    let mut vm = mem.make_vm();
+    for (name, fast) in LOOPS {
+        b.run(&format!("dispatch/no_op_x1000/{name}"), |n| {
+            run_word(&mut vm, &word, fast, n)
+        });
+    }
+}
+
+fn nested_calls(b: &mut Bench) {
+    //every level calls the one below twice so the leaf runs 2^DEPTH times
+    const DEPTH: usize = 10;
+    let mut levels: Vec<Box<[Code]>> = vec![Box::new([
+        Code::basic(op::no_op, 0),
+        Code::basic(op::ret, 0),
+    ])];
+    for _ in 0..DEPTH {
+        let below = levels.last().unwrap().as_ptr();
+        levels.push(Box::new([
+            Code::word_raw(below),
+            Code::word_raw(below),
+            Code::basic(op::ret, 0),
+        ]));
+    }
+    let word = Code::word(levels.last().unwrap());
+
+    let mut mem = VmEasyMemory::<64>::new();
+    let mut vm = mem.make_vm();
+    for (name, fast) in LOOPS {
+        b.run(&format!("calls/binary_tree_depth_{DEPTH}/{name}"), |n| {
+            run_word(&mut vm, &word, fast, n)
+        });
+    }
+}
+
+fn dynamic_calls(b: &mut Bench) {
+    let body = [Code::basic(op::no_op, 0), Code::basic(op::ret, 0)];
+    let target = Code::word(&body);
+    //call_dyn reads a code pointer out of the box it pops
+    let slot: *const Code = &target;
+    let slot_box = &slot as *const *const Code as isize;
+
+    for (name, f) in [
+        ("call_dyn", op::call_dyn),
+        ("call_dyn_threaded", op::call_dyn_threaded),
+    ] {
+        let mut caller: Vec<Code> = Vec::new();
+        for _ in 0..100 {
+            caller.push(Code::basic(op::push_var, slot_box));
+            caller.push(Code::basic(f, 0));
+        }
+        caller.push(Code::basic(op::ret, 0));
+        let word = Code::word(&caller);
+
+        let mut mem = VmEasyMemory::<64>::new();
+        let mut vm = mem.make_vm();
+        b.run(&format!("calls/{name}_x100"), |n| {
+            run_word(&mut vm, &word, false, n)
+        });
+    }
+}
+
+fn inject_sizes(b: &mut Bench) {
+    for size in [8usize, 64, 512, 4096] {
+        let mut src = vec![1u64; size / 8];
+        let mut dst = vec![0u64; size / 8];
+
+        // dst src -- dst src
+        let mut body: Vec<Code> = Vec::new();
+        for _ in 0..100 {
+            body.extend([
+                Code::basic(op::pick, 1),
+                Code::basic(op::pick, 1),
+                Code::basic(op::inject, size as isize),
+                Code::basic(op::param_drop, 1),
+            ]);
+        }
+        body.push(Code::basic(op::ret, 0));
+        let word = Code::word(&body);
+
+        let mut mem = VmEasyMemory::<64>::new();
+        let mut vm = mem.make_vm();
+        vm.param_stack.push(dst.as_mut_ptr() as *mut _).unwrap();
+        vm.param_stack.push(src.as_mut_ptr() as *mut _).unwrap();
+        b.run_bytes(
+            &format!("inject/{size}_bytes_x100"),
+            100 * size as u64,
+            |n| run_word(&mut vm, &word, false, n),
+        );
+    }
+}
+
+fn call_sig(b: &mut Bench) {
+    let mut lex_mem = LexEasyMemory::new();
+    let mut lex = ManuallyDrop::new(lex_mem.make_lex());
+    load_prelude(&mut lex);
+    let mut comp_mem = CompEasyMemory::<64>::new();
+    let mut comp = comp_mem.make_comp(&mut lex);
+
+    let int = comp.lex.basic_type("int").unwrap();
+    let out = [SigItem {
+        tp: int,
+        permissions: READ_FLAG | WRITE_FLAG,
+    }];
+    let inp = [SigItem {
+        tp: int,
+        permissions: READ_FLAG,
+    }];
+
+    //the shape of checking a single int_add
+    b.run("sig/call_sig_int_add", |n| {
+        for _ in 0..n {
+            let stack = &mut comp.stack;
+            stack.push_box(int, READ_FLAG | WRITE_FLAG, None);
+            stack.push_box(int, READ_FLAG | WRITE_FLAG, None);
+            stack.call_sig(black_box(&out), black_box(&inp)).unwrap();
+            stack.clear();
+        }
+    });
+}
+
+fn tokenize(b: &mut Bench) {
+    const SIZE: usize = 10 * 1024 * 1024;
+    let line = ": square pick 0 int_mul ; 12345 square -7 true bool_not\n";
+    let text = line.repeat(SIZE / line.len() + 1);
+
+    let path = std::env::temp_dir().join(format!("pal_bench_words_{}.txt", std::process::id()));
+    std::fs::write(&path, &text).unwrap();
+
+    b.run_bytes("input/word_stream_10mb_file", text.len() as u64, |n| {
+        for _ in 0..n {
+            let file = std::fs::File::open(&path).unwrap();
+            let mut stream: WordStream<_> = WordStream::new(file);
+            let mut count = 0usize;
+            while stream.next_word().unwrap().is_some() {
+                count += 1;
+            }
+            black_box(count);
+        }
+    });
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+fn main() {
+    let mut b = Bench::new("core");
+    dispatch(&mut b);
+    nested_calls(&mut b);
+    dynamic_calls(&mut b);
+    inject_sizes(&mut b);
+    call_sig(&mut b);
+    tokenize(&mut b);
+    b.finish();
+}
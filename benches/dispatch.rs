@@ -0,0 +1,351 @@
+//! compares the plain dispatch loop with the register cached one on recursive fib and a sieve
+//! run with `cargo bench --bench dispatch`, see harness/mod.rs for the output
+
+mod harness;
+
+use harness::Bench;
+use pal_forth::PalData;
+use pal_forth::buildins::*;
+use pal_forth::vm::Buildin;
+use pal_forth::vm::Code;
+use pal_forth::vm::FastCode;
+use pal_forth::vm::Vm;
+use pal_forth::vm::VmEasyMemory;
+use std::hint::black_box;
+
+static ONE: i64 = 1;
+static TWO: i64 = 2;
+
+static PICK: [FastCode; 4] = [
+    FastCode::new(pick_fast, std::ptr::without_provenance(0), op::pick),
+    FastCode::new(pick_fast, std::ptr::without_provenance(1), op::pick),
+    FastCode::new(pick_fast, std::ptr::without_provenance(2), op::pick),
+    FastCode::new(pick_fast, std::ptr::without_provenance(3), op::pick),
+];
+static DROP: [FastCode; 4] = [
+    FastCode::new(
+        param_drop_fast,
+        std::ptr::without_provenance(0),
+        op::param_drop,
+    ),
+    FastCode::new(
+        param_drop_fast,
+        std::ptr::without_provenance(1),
+        op::param_drop,
+    ),
+    FastCode::new(
+        param_drop_fast,
+        std::ptr::without_provenance(2),
+        op::param_drop,
+    ),
+    FastCode::new(
+        param_drop_fast,
+        std::ptr::without_provenance(3),
+        op::param_drop,
+    ),
+];
+
+///picks the fast or plain version of the same op
+struct Ops {
+    fast: bool,
+    //the FastCode of every push, the code points into these so they are boxed to stay put
+    #[allow(clippy::vec_box)]
+    pushes: Vec<Box<FastCode>>,
+}
+
+impl Ops {
+    fn new(fast: bool) -> Self {
+        Self {
+            fast,
+            pushes: Vec::new(),
+        }
+    }
+    ///the buildins the compiler would swap for a fast one
+    fn op(&self, b: Buildin) -> Code {
+        let code = Code::basic(b, 0);
+        match fast_variant(&code) {
+            Some(f) if self.fast => Code::fast(f),
+            _ => code,
+        }
+    }
+    fn pick(&self, n: usize) -> Code {
+        if self.fast {
+            Code::fast(&PICK[n])
+        } else {
+            Code::basic(op::pick, n as isize)
+        }
+    }
+    fn drop(&self, n: usize) -> Code {
+        if self.fast {
+            Code::fast(&DROP[n])
+        } else {
+            Code::basic(op::param_drop, n as isize)
+        }
+    }
+    fn push(&mut self, v: *const PalData) -> Code {
+        if self.fast {
+            let f = Box::new(FastCode::new(push_var_fast, v as *const Code, op::push_var));
+            let code = Code::fast(&f);
+            self.pushes.push(f);
+            code
+        } else {
+            Code::basic(op::push_var, v as isize)
+        }
+    }
+    fn one(&mut self) -> Code {
+        self.push(&ONE as *const i64 as *const PalData)
+    }
+    fn two(&mut self) -> Code {
+        self.push(&TWO as *const i64 as *const PalData)
+    }
+}
+
+/// fib ( out n -- out ) with 3 locals: the n<2 flag, k and fib(n-2)
+fn fib_code(o: &mut Ops) -> Box<[Code]> {
+    let mut code: Vec<Code> = Vec::with_capacity(32);
+    //the recursive calls need the final address so fill in after
+    let start = code.as_ptr();
+    code.extend([
+        Code::basic(op::frame_alloc, 3),
+        Code::basic(op::push_local, 0),
+        o.pick(1),
+        o.two(),
+        o.op(op::int_smaller),
+        Code::basic(op::branch, 19),
+        //k = n-1 ; out = fib(k)
+        Code::basic(op::push_local, 1),
+        o.pick(1),
+        Code::basic(op::inject, 8),
+        o.one(),
+        o.op(op::int_sub),
+        o.pick(2),
+        o.pick(1),
+        Code::word_raw(start),
+        o.drop(1),
+        //k = n-2 ; a = fib(k)
+        o.one(),
+        o.op(op::int_sub),
+        Code::basic(op::push_local, 2),
+        o.pick(1),
+        Code::word_raw(start),
+        //out += a
+        o.pick(3),
+        o.pick(1),
+        o.op(op::int_add),
+        o.drop(3),
+        Code::basic(op::jump, 4),
+        //out = n
+        o.pick(1),
+        o.pick(1),
+        Code::basic(op::inject, 8),
+        o.drop(1),
+        //done
+        o.drop(1),
+        Code::basic(op::frame_free, 3),
+        Code::basic(op::ret, 0),
+    ]);
+    assert_eq!(code.as_ptr(), start);
+    code.into_boxed_slice()
+}
+
+fn run(vm: &mut Vm, word: &Code, fast_loop: bool) {
+    unsafe {
+        if fast_loop {
+            vm.execute_code_fast(black_box(word));
+        } else {
+            vm.execute_code_plain(black_box(word));
+        }
+    }
+}
+
+fn run_fib(vm: &mut Vm, word: &Code, fast_loop: bool, n: i64) -> i64 {
+    let mut out = 0i64;
+    let mut n = n;
+    vm.param_stack.push(&mut out as *mut i64 as *mut _).unwrap();
+    vm.param_stack.push(&mut n as *mut i64 as *mut _).unwrap();
+    run(vm, word, fast_loop);
+    vm.param_stack.pop().unwrap();
+    out
+}
+
+///the variables of the sieve, it works on globals so it has no stack effect
+#[derive(Default)]
+struct SieveVars {
+    composite: i64,
+    i: i64,
+    j: i64,
+    tmp: i64,
+    reps: i64,
+    flag: i64,
+    zero: i64,
+    limit: i64,
+}
+
+///a jump or branch to fill in once the label is known
+enum Fix {
+    Branch(usize, usize),
+    Jump(usize, usize),
+}
+
+/// sieve of eratosthenes over 0..64 kept as a bitmask in composite, reps times (at least once)
+/// every bit of a composite number (and 0 1) is set at the end
+fn sieve_code(o: &mut Ops, v: &mut SieveVars) -> Box<[Code]> {
+    let p = |x: &mut i64| x as *mut i64 as *const PalData;
+    let (composite, i, j, tmp) = (p(&mut v.composite), p(&mut v.i), p(&mut v.j), p(&mut v.tmp));
+    let (reps, flag, zero, limit) = (
+        p(&mut v.reps),
+        p(&mut v.flag),
+        p(&mut v.zero),
+        p(&mut v.limit),
+    );
+
+    let mut code: Vec<Code> = Vec::new();
+    let mut fixes = Vec::new();
+    let mut labels = [0usize; 5];
+    const REP: usize = 0;
+    const OUTER: usize = 1;
+    const INNER: usize = 2;
+    const NEXT: usize = 3;
+    const DONE: usize = 4;
+
+    //composite = 0 ; i = 2
+    labels[REP] = code.len();
+    code.extend([
+        o.push(composite),
+        o.push(zero),
+        Code::basic(op::inject, 8),
+        o.drop(1),
+    ]);
+    code.extend([o.push(i), o.two(), Code::basic(op::inject, 8), o.drop(1)]);
+
+    //while i*i < limit
+    labels[OUTER] = code.len();
+    code.extend([o.push(tmp), o.push(i), Code::basic(op::inject, 8)]);
+    code.extend([o.push(i), o.op(op::int_mul), o.drop(1)]);
+    code.extend([
+        o.push(flag),
+        o.push(tmp),
+        o.push(limit),
+        o.op(op::int_smaller),
+    ]);
+    code.push(o.op(op::bool_not));
+    fixes.push(Fix::Branch(code.len(), DONE));
+    code.push(Code::basic(op::no_op, 0));
+
+    //skip i if it is allready marked
+    code.extend([o.push(tmp), o.push(composite), Code::basic(op::inject, 8)]);
+    code.extend([
+        o.push(i),
+        o.op(op::int_shr),
+        o.one(),
+        o.op(op::int_and),
+        o.drop(1),
+    ]);
+    code.extend([o.push(flag), o.push(tmp), o.push(zero), o.op(op::int_neq)]);
+    fixes.push(Fix::Branch(code.len(), NEXT));
+    code.push(Code::basic(op::no_op, 0));
+
+    //j = i*i
+    code.extend([o.push(j), o.push(i), Code::basic(op::inject, 8)]);
+    code.extend([o.push(i), o.op(op::int_mul), o.drop(1)]);
+
+    //while j < limit mark j
+    labels[INNER] = code.len();
+    code.extend([
+        o.push(flag),
+        o.push(j),
+        o.push(limit),
+        o.op(op::int_smaller),
+    ]);
+    code.push(o.op(op::bool_not));
+    fixes.push(Fix::Branch(code.len(), NEXT));
+    code.push(Code::basic(op::no_op, 0));
+    code.extend([o.push(tmp), o.one(), Code::basic(op::inject, 8)]);
+    code.extend([o.push(j), o.op(op::int_shl), o.drop(1)]);
+    code.extend([o.push(composite), o.push(tmp), o.op(op::int_or), o.drop(1)]);
+    code.extend([o.push(j), o.push(i), o.op(op::int_add), o.drop(1)]);
+    fixes.push(Fix::Jump(code.len(), INNER));
+    code.push(Code::basic(op::no_op, 0));
+
+    labels[NEXT] = code.len();
+    code.extend([o.push(i), o.one(), o.op(op::int_add), o.drop(1)]);
+    fixes.push(Fix::Jump(code.len(), OUTER));
+    code.push(Code::basic(op::no_op, 0));
+
+    //reps -= 1 ; again if reps > 0
+    labels[DONE] = code.len();
+    code.extend([o.push(reps), o.one(), o.op(op::int_sub), o.drop(1)]);
+    code.extend([
+        o.push(flag),
+        o.push(reps),
+        o.push(zero),
+        o.op(op::int_bigger),
+    ]);
+    fixes.push(Fix::Branch(code.len(), REP));
+    code.push(Code::basic(op::no_op, 0));
+    code.push(Code::basic(op::ret, 0));
+
+    //code pointers are allways 1 before the next instruction
+    for fix in fixes {
+        let (at, b, label) = match fix {
+            Fix::Branch(at, label) => (at, op::branch, label),
+            Fix::Jump(at, label) => (at, op::jump, label),
+        };
+        code[at] = Code::basic(b, labels[label] as isize - at as isize - 1);
+    }
+    code.into_boxed_slice()
+}
+
+fn run_sieve(vm: &mut Vm, word: &Code, vars: &mut SieveVars, fast_loop: bool) -> i64 {
+    vars.reps = 1;
+    run(vm, word, fast_loop);
+    assert_eq!(vm.param_stack.len(), 0);
+    //0 and 1 are never marked
+    (vars.composite | 0b11).count_zeros() as i64
+}
+
+const SETUPS: [(&str, bool, bool); 4] = [
+    ("plain_loop", false, false),
+    ("fast_loop_plain_buildins", true, false),
+    ("plain_loop_fast_buildins", false, true),
+    ("fast_loop_fast_buildins", true, true),
+];
+
+fn main() {
+    const N: i64 = 20;
+    let mut b = Bench::new("dispatch");
+    for (name, fast_loop, fast_code) in SETUPS {
+        let mut ops = Ops::new(fast_code);
+        let code = fib_code(&mut ops);
+        let word = Code::word(&code);
+        let mut mem = VmEasyMemory::<1024>::new();
+        let mut vm = mem.make_vm();
+        assert_eq!(run_fib(&mut vm, &word, fast_loop, N), 6765);
+
+        b.run(&format!("fib_{N}/{name}"), |iters| {
+            for _ in 0..iters {
+                black_box(run_fib(&mut vm, &word, fast_loop, N));
+            }
+        });
+    }
+    for (name, fast_loop, fast_code) in SETUPS {
+        let mut ops = Ops::new(fast_code);
+        let mut vars = Box::new(SieveVars {
+            limit: 64,
+            ..Default::default()
+        });
+        let code = sieve_code(&mut ops, &mut vars);
+        let word = Code::word(&code);
+        let mut mem = VmEasyMemory::<16>::new();
+        let mut vm = mem.make_vm();
+        //18 primes below 64
+        assert_eq!(run_sieve(&mut vm, &word, &mut vars, fast_loop), 18);
+
+        b.run(&format!("sieve_64/{name}"), |iters| {
+            for _ in 0..iters {
+                black_box(run_sieve(&mut vm, &word, &mut vars, fast_loop));
+            }
+        });
+    }
+    b.finish();
+}
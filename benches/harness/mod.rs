@@ -0,0 +1,127 @@
+//! a tiny std::time bench harness shared by the bench targets
+//! every bench is calibrated to run for a while then sampled a few times
+//! the table goes to stderr and a json report goes to stdout (and to $PAL_BENCH_JSON if set)
+
+#![allow(dead_code)] //not every bench target uses all of it
+
+use std::fmt::Write as _;
+use std::time::Duration;
+use std::time::Instant;
+
+const SAMPLES: usize = 10;
+const SAMPLE_TIME: Duration = Duration::from_millis(20);
+
+pub struct Measurement {
+    pub name: String,
+    pub iters: u64,
+    pub median_ns: f64,
+    pub min_ns: f64,
+    pub max_ns: f64,
+    ///bytes handled per iteration, used to report throughput
+    pub bytes: Option<u64>,
+}
+
+pub struct Bench {
+    suite: &'static str,
+    filter: Option<String>,
+    results: Vec<Measurement>,
+}
+
+impl Bench {
+    pub fn new(suite: &'static str) -> Self {
+        //cargo passes --bench along with whatever is after --
+        let filter = std::env::args().skip(1).find(|a| !a.starts_with('-'));
+        Self {
+            suite,
+            filter,
+            results: Vec::new(),
+        }
+    }
+
+    fn wanted(&self, name: &str) -> bool {
+        self.filter
+            .as_ref()
+            .is_none_or(|f| name.contains(f.as_str()))
+    }
+
+    ///f runs the thing being measured the given number of times
+    pub fn run(&mut self, name: &str, f: impl FnMut(u64)) {
+        self.run_inner(name, None, f)
+    }
+
+    ///same as [`Bench::run`] but also reports bytes per second
+    pub fn run_bytes(&mut self, name: &str, bytes: u64, f: impl FnMut(u64)) {
+        self.run_inner(name, Some(bytes), f)
+    }
+
+    fn run_inner(&mut self, name: &str, bytes: Option<u64>, mut f: impl FnMut(u64)) {
+        if !self.wanted(name) {
+            return;
+        }
+
+        //double untill a sample is long enough to time
+        let mut iters = 1;
+        loop {
+            let t = Instant::now();
+            f(iters);
+            if t.elapsed() >= SAMPLE_TIME || iters >= 1 << 40 {
+                break;
+            }
+            iters *= 2;
+        }
+
+        let mut samples: Vec<f64> = (0..SAMPLES)
+            .map(|_| {
+                let t = Instant::now();
+                f(iters);
+                t.elapsed().as_nanos() as f64 / iters as f64
+            })
+            .collect();
+        samples.sort_by(f64::total_cmp);
+
+        let m = Measurement {
+            name: name.to_string(),
+            iters,
+            median_ns: samples[SAMPLES / 2],
+            min_ns: samples[0],
+            max_ns: samples[SAMPLES - 1],
+            bytes,
+        };
+        eprint!("{:<40} {:>12.2} ns/iter", m.name, m.median_ns);
+        if let Some(b) = bytes {
+            eprint!("  {:>10.2} MB/s", b as f64 / m.median_ns * 1e3);
+        }
+        eprintln!();
+        self.results.push(m);
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut s = String::new();
+        write!(s, "{{\"suite\":\"{}\",\"results\":[", self.suite).unwrap();
+        for (i, m) in self.results.iter().enumerate() {
+            if i != 0 {
+                s.push(',');
+            }
+            write!(
+                s,
+                "{{\"name\":\"{}\",\"iters\":{},\"median_ns\":{:.3},\"min_ns\":{:.3},\"max_ns\":{:.3}",
+                m.name, m.iters, m.median_ns, m.min_ns, m.max_ns
+            )
+            .unwrap();
+            if let Some(b) = m.bytes {
+                write!(s, ",\"bytes\":{b}").unwrap();
+            }
+            s.push('}');
+        }
+        s.push_str("]}");
+        s
+    }
+
+    pub fn finish(self) {
+        let json = self.to_json();
+        println!("{json}");
+        if let Ok(path) = std::env::var("PAL_BENCH_JSON") {
+            std::fs::write(&path, &json).expect("failed writing the json report");
+        }
+    }
+}
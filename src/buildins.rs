@@ -3,10 +3,23 @@
 #![allow(clippy::assign_op_pattern)] //we use macros dont really dont need this
 #![allow(clippy::unnecessary_cast)]
 
+use crate::ir::TrapCheck;
+use crate::lex::StackAlloc;
+use crate::lex::StackWriter;
+use crate::vm::Buildin;
 use crate::vm::Code;
+use crate::vm::FastCode;
+use crate::vm::Op;
+use crate::vm::ParamHead;
 use crate::vm::Vm;
-use crate::{PalBool, PalData};
-use core::ptr::{copy, copy_nonoverlapping};
+use crate::{PalBool, PalData, PalFloat, PalInt};
+use core::ops::{Add, BitAnd, BitOr, BitXor, Div, Mul, Rem, Sub};
+use core::fmt;
+use core::fmt::Write;
+use core::mem::MaybeUninit;
+use core::ptr::{copy, copy_nonoverlapping, swap_nonoverlapping, NonNull};
+use core::slice;
+use core::str::FromStr;
 use core::sync::atomic::Ordering;
 
 /*════════════════ helpers ════════════════*/
@@ -40,6 +53,17 @@ pub unsafe fn unwrap_over<T>(v: Option<T>) -> T {
     }
 }
 
+#[inline(always)]
+pub fn check_bounds(idx: PalInt, len: PalInt) {
+    //negative indices wrap to huge ones so one compare covers both
+    #[cfg(not(feature = "unchecked_bounds"))]
+    if idx as u64 >= len as u64 {
+        panic!("index out of bounds: {idx} >= {len}");
+    }
+    #[cfg(feature = "unchecked_bounds")]
+    let _ = (idx, len);
+}
+
 macro_rules! pop {
     ($vm:expr) => {
         unwrap_under($vm.param_stack.pop())
@@ -120,9 +144,23 @@ pub unsafe extern "C-unwind" fn inject_non_unique(
     }
 }
 
+///swaps the param bytes held by the top two boxes, both stay on the stack
+pub unsafe extern "C-unwind" fn swap_bytes(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    unsafe {
+        let n = param(code_ptr) as usize;
+        let a = (*spot!(vm, 0)) as *mut u8;
+        let b = (*spot!(vm, 1)) as *mut u8;
+        swap_nonoverlapping(a, b, n);
+        code_ptr
+    }
+}
+
 pub unsafe extern "C-unwind" fn frame_alloc(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
     unsafe {
-        unwrap_over(vm.data_stack.alloc(param(code_ptr) as usize));
+        let n = param(code_ptr) as usize;
+        unwrap_over(vm.data_stack.alloc(n));
+        //locals start out zeroed so a destructor never sees garbage
+        vm.data_stack.get_head().write_bytes(0, n);
         code_ptr
     }
 }
@@ -171,6 +209,190 @@ pub unsafe extern "C-unwind" fn pick(code_ptr: *const Code, vm: &mut Vm) -> *con
     }
 }
 
+///moves the top box pointer param cells forward, used to get at a field of a cluster
+pub unsafe extern "C-unwind" fn field_at(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    unsafe {
+        let p = spot!(vm, 0);
+        *p = (*p).add(param(code_ptr) as usize);
+        code_ptr
+    }
+}
+
+/* ───────────────── raw pointers ───────────────── */
+//a ptr(T) box holds the address of a T in its cell, nothing tracks what it points at
+//so the words on them are only let through inside unsafe{ }
+
+///copies the param bytes the pointer on top points at into the box under it
+pub unsafe extern "C-unwind" fn ptr_load(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    unsafe {
+        let n = param(code_ptr) as usize;
+        let p = pop!(vm);
+        let dst = (*spot!(vm, 0)) as *mut u8;
+        copy((*p).ptr as *const u8, dst, n);
+        code_ptr
+    }
+}
+
+///copies the param bytes of the box under the pointer on top to where it points
+pub unsafe extern "C-unwind" fn ptr_store(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    unsafe {
+        let n = param(code_ptr) as usize;
+        let p = pop!(vm);
+        let src = pop!(vm) as *const u8;
+        copy(src, (*p).ptr as *mut u8, n);
+        code_ptr
+    }
+}
+
+///writes the address of the top box into the box under it
+pub unsafe extern "C-unwind" fn ptr_of(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    unsafe {
+        let src = pop!(vm);
+        (**spot!(vm, 0)).ptr = src;
+        code_ptr
+    }
+}
+
+/* ───────────────── heap ───────────────── */
+//a box(T) holds a pointer from the global allocator (which no_std hosts provide) or null
+//the param is the size of T and every byte taken is counted against the vm's limit
+
+#[cfg(feature = "heap")]
+#[inline(always)]
+fn heap_layout(size: usize) -> alloc::alloc::Layout {
+    alloc::alloc::Layout::from_size_align(size.max(1), align_of::<PalData>())
+        .expect("bad heap layout")
+}
+
+///releases what the box points at (if anything) and nulls it
+#[cfg(feature = "heap")]
+#[inline(always)]
+unsafe fn release_box(b: *mut PalData, size: usize, vm: &mut Vm) {
+    unsafe {
+        let p = (*b).ptr;
+        if p.is_null() {
+            return;
+        }
+        alloc::alloc::dealloc(p as *mut u8, heap_layout(size));
+        vm.heap.used -= size;
+        (*b).ptr = core::ptr::null_mut();
+    }
+}
+
+///points the box on top at fresh zeroed memory, whatever it held before is freed first
+#[cfg(feature = "heap")]
+pub unsafe extern "C-unwind" fn heap_alloc(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    unsafe {
+        let size = param(code_ptr) as usize;
+        let b = pop!(vm);
+        release_box(b, size, vm);
+        if vm.heap.limit.saturating_sub(vm.heap.used) < size {
+            panic!("heap limit reached");
+        }
+        let layout = heap_layout(size);
+        let p = alloc::alloc::alloc_zeroed(layout);
+        if p.is_null() {
+            alloc::alloc::handle_alloc_error(layout);
+        }
+        (*b).ptr = p as *mut PalData;
+        vm.heap.used += size;
+        vm.heap.peak = vm.heap.peak.max(vm.heap.used);
+        code_ptr
+    }
+}
+
+///frees the box on top, freeing an empty box does nothing
+#[cfg(feature = "heap")]
+pub unsafe extern "C-unwind" fn heap_free(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    unsafe {
+        let b = pop!(vm);
+        release_box(b, param(code_ptr) as usize, vm);
+        code_ptr
+    }
+}
+
+///turns the box on top into a box of what it points at
+#[cfg(feature = "heap")]
+pub unsafe extern "C-unwind" fn box_open(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    unsafe {
+        let b = spot!(vm, 0);
+        let p = (**b).ptr;
+        if p.is_null() {
+            panic!("empty box");
+        }
+        *b = p;
+        code_ptr
+    }
+}
+
+/* ───────────────── indexing ───────────────── */
+//fixed arrays are the elements back to back, slices are a (ptr,len) pair of cells
+//the index box is consumed and the array box is turned into a pointer at the element
+//the param is the stride in bytes so narrow elements can be packed densely
+
+///checks the index on top is below param (the length of a fixed array)
+pub unsafe extern "C-unwind" fn bounds_check(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    unsafe {
+        let idx = (**spot!(vm, 0)).int;
+        check_bounds(idx, param(code_ptr) as PalInt);
+        code_ptr
+    }
+}
+
+///indexes a fixed array, the bounds are checked by a [`bounds_check`] before it
+pub unsafe extern "C-unwind" fn array_at(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    unsafe {
+        let idx = (*pop!(vm)).int as usize;
+        let p = spot!(vm, 0);
+        *p = (*p).byte_add(idx * param(code_ptr) as usize);
+        code_ptr
+    }
+}
+
+///indexes a slice against its own length
+pub unsafe extern "C-unwind" fn slice_at(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    unsafe {
+        let idx = (*pop!(vm)).int;
+        let p = spot!(vm, 0);
+        let slice = *p;
+        check_bounds(idx, (*slice.add(1)).int);
+        *p = (*slice).ptr.byte_add(idx as usize * param(code_ptr) as usize);
+        code_ptr
+    }
+}
+
+/* ───────────────── variants ───────────────── */
+//a variant is a tag cell followed by the payload of the case the tag names
+
+///writes the tag of the case param into the variant on top
+pub unsafe extern "C-unwind" fn variant_tag(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    unsafe {
+        (**spot!(vm, 0)).int = param(code_ptr) as PalInt;
+        code_ptr
+    }
+}
+
+///consumes the payload on top and copies param bytes of it into the variant under it
+pub unsafe extern "C-unwind" fn variant_payload(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    unsafe {
+        let src = pop!(vm) as *const u8;
+        let dst = (*spot!(vm, 0)).add(1) as *mut u8;
+        copy_nonoverlapping(src, dst, param(code_ptr) as usize);
+        code_ptr
+    }
+}
+
+///skips the instruction after it (the jump to the next case) when the tag on top is param
+pub unsafe extern "C-unwind" fn variant_skip(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    unsafe {
+        if (**spot!(vm, 0)).int == param(code_ptr) as PalInt {
+            code_ptr.wrapping_add(1)
+        } else {
+            code_ptr
+        }
+    }
+}
+
 /* ───────────────── control flow ───────────────── */
 
 pub unsafe extern "C-unwind" fn branch(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
@@ -243,14 +465,16 @@ pub unsafe extern "C-unwind" fn call_dyn(call_site: *const Code, vm: &mut Vm) ->
 }
 
 ///we assume the code is good for threaded excution. also see [`call_dyn`]
-pub unsafe extern "C-unwind" fn call_dyn_threaded(_code: *const Code, vm: &mut Vm) -> *const Code {
+pub unsafe extern "C-unwind" fn call_dyn_threaded(call_site: *const Code, vm: &mut Vm) -> *const Code {
     unsafe {
         let code = (*pop!(vm)).code;
 
         #[cfg(feature = "trace_vm")]
         println!("calling {code:?} dynamically");
 
-        vm.execute_threaded(code)
+        //the callee finishing is not the caller finishing
+        vm.execute_threaded(code);
+        call_site
     }
 }
 
@@ -303,6 +527,82 @@ arith_fn!(int_and, &);
 arith_fn!(int_or , |);
 arith_fn!(int_xor, ^);
 
+/* ───────────────── overflow families ───────────────── */
+//the plain int words follow the build profile, these behave the same on every build
+//wrapping keeps the low bits and saturating clamps to the int range
+//checked leaves the lhs alone on overflow and says so through the bool on top
+//division by zero is defined too: wrapping div gives 0 and mod gives the lhs
+//saturating div clamps by the sign of the lhs and mod gives 0
+
+fn wrapping_div(a: PalInt, b: PalInt) -> PalInt {
+    if b == 0 { 0 } else { a.wrapping_div(b) }
+}
+
+fn wrapping_rem(a: PalInt, b: PalInt) -> PalInt {
+    if b == 0 { a } else { a.wrapping_rem(b) }
+}
+
+fn saturating_div(a: PalInt, b: PalInt) -> PalInt {
+    match (a.signum(), b) {
+        (1, 0) => PalInt::MAX,
+        (-1, 0) => PalInt::MIN,
+        (_, 0) => 0,
+        _ => a.saturating_div(b),
+    }
+}
+
+fn saturating_rem(a: PalInt, b: PalInt) -> PalInt {
+    //MIN % -1 is 0 it just overflows on the way
+    a.checked_rem(b).unwrap_or(0)
+}
+
+macro_rules! int_fn {
+    ($fname:ident, $f:expr) => {
+        pub unsafe extern "C-unwind" fn $fname(code_ptr:*const Code, vm:&mut Vm) -> *const Code { unsafe {
+            bin_int_op(vm, $f);
+            code_ptr
+        }}
+    };
+}
+int_fn!(int_add_wrapping, PalInt::wrapping_add);
+int_fn!(int_sub_wrapping, PalInt::wrapping_sub);
+int_fn!(int_mul_wrapping, PalInt::wrapping_mul);
+int_fn!(int_div_wrapping, wrapping_div);
+int_fn!(int_mod_wrapping, wrapping_rem);
+int_fn!(int_add_saturating, PalInt::saturating_add);
+int_fn!(int_sub_saturating, PalInt::saturating_sub);
+int_fn!(int_mul_saturating, PalInt::saturating_mul);
+int_fn!(int_div_saturating, saturating_div);
+int_fn!(int_mod_saturating, saturating_rem);
+
+#[inline(always)]
+unsafe extern "C-unwind" fn checked_int_op(vm: &mut Vm, op: impl Fn(i64, i64) -> Option<i64>) {
+    unsafe {
+        let rhs = pop!(vm) as *const PalData;
+        let ok = (*spot!(vm, 0)) as *mut PalBool;
+        let lhs = (*spot!(vm, 1)) as *mut PalData;
+        let v = op((*lhs).int, (*rhs).int);
+        *ok = v.is_some();
+        if let Some(v) = v {
+            (*lhs).int = v;
+        }
+    }
+}
+
+macro_rules! checked_fn {
+    ($fname:ident, $f:expr) => {
+        pub unsafe extern "C-unwind" fn $fname(code_ptr:*const Code, vm:&mut Vm) -> *const Code { unsafe {
+            checked_int_op(vm, $f);
+            code_ptr
+        }}
+    };
+}
+checked_fn!(int_add_checked, PalInt::checked_add);
+checked_fn!(int_sub_checked, PalInt::checked_sub);
+checked_fn!(int_mul_checked, PalInt::checked_mul);
+checked_fn!(int_div_checked, PalInt::checked_div);
+checked_fn!(int_mod_checked, PalInt::checked_rem);
+
 /* ───────────────── comparisons ───────────────── */
 
 #[inline(always)]
@@ -330,6 +630,498 @@ cmp_fn!(int_bigger, >);
 cmp_fn!(int_le, <=);
 cmp_fn!(int_ge, >=);
 
+/* ───────────────── floats ───────────────── */
+//only core float ops here, everything that needs a libm is behind the libm feature
+
+#[inline(always)]
+unsafe extern "C-unwind" fn bin_float_op(vm: &mut Vm, op: impl Fn(PalFloat, PalFloat) -> PalFloat) {
+    unsafe {
+        let rhs = pop!(vm) as *const PalData;
+        let lhs = (*spot!(vm, 0)) as *mut PalData;
+        let v = op((*lhs).float, (*rhs).float);
+        (*lhs).float = v;
+    }
+}
+
+macro_rules! float_arith_fn {
+    ($fname:ident, $op:tt) => {
+        pub unsafe extern "C-unwind" fn $fname(code_ptr:*const Code, vm:&mut Vm) -> *const Code { unsafe {
+            bin_float_op(vm, |a,b| a $op b);
+            code_ptr
+        }}
+    };
+}
+float_arith_fn!(float_add, +);
+float_arith_fn!(float_sub, -);
+float_arith_fn!(float_mul, *);
+float_arith_fn!(float_div, /);
+float_arith_fn!(float_mod, %);
+
+#[inline(always)]
+unsafe extern "C-unwind" fn float_cmp_op(vm: &mut Vm, op: impl Fn(PalFloat, PalFloat) -> bool) {
+    unsafe {
+        let rhs = pop!(vm) as *const PalData;
+        let lhs = pop!(vm) as *const PalData;
+        let dst = (*spot!(vm, 0)) as *mut PalBool;
+        *dst = op((*lhs).float, (*rhs).float);
+    }
+}
+
+macro_rules! float_cmp_fn {
+    ($fname:ident, $op:tt) => {
+        pub unsafe extern "C-unwind" fn $fname(code_ptr:*const Code, vm:&mut Vm) -> *const Code { unsafe {
+            float_cmp_op(vm, |a,b| a $op b);
+            code_ptr
+        }}
+    };
+}
+float_cmp_fn!(float_eq,  ==);
+float_cmp_fn!(float_neq, !=);
+float_cmp_fn!(float_smaller, <);
+float_cmp_fn!(float_bigger, >);
+float_cmp_fn!(float_le, <=);
+float_cmp_fn!(float_ge, >=);
+
+///rounds to the nearest float
+pub unsafe extern "C-unwind" fn int_to_float(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    unsafe {
+        let src = pop!(vm) as *const PalData;
+        let dst = (*spot!(vm, 0)) as *mut PalData;
+        (*dst).float = (*src).int as PalFloat;
+        code_ptr
+    }
+}
+
+///truncates towards zero, out of range values saturate and nan is 0
+pub unsafe extern "C-unwind" fn float_to_int(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    unsafe {
+        let src = pop!(vm) as *const PalData;
+        let dst = (*spot!(vm, 0)) as *mut PalData;
+        (*dst).int = (*src).float as PalInt;
+        code_ptr
+    }
+}
+
+#[cfg(feature = "libm")]
+macro_rules! float_math_fn {
+    ($fname:ident, $f:path) => {
+        pub unsafe extern "C-unwind" fn $fname(code_ptr:*const Code, vm:&mut Vm) -> *const Code { unsafe {
+            let dst = (*spot!(vm, 0)) as *mut PalData;
+            (*dst).float = $f((*dst).float);
+            code_ptr
+        }}
+    };
+}
+#[cfg(feature = "libm")]
+mod float_math {
+    use super::*;
+    float_math_fn!(float_sqrt, libm::sqrt);
+    float_math_fn!(float_floor, libm::floor);
+    float_math_fn!(float_ceil, libm::ceil);
+    float_math_fn!(float_sin, libm::sin);
+    float_math_fn!(float_cos, libm::cos);
+    float_math_fn!(float_exp, libm::exp);
+    float_math_fn!(float_ln, libm::log);
+
+    pub unsafe extern "C-unwind" fn float_pow(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+        unsafe {
+            bin_float_op(vm, libm::pow);
+            code_ptr
+        }
+    }
+}
+#[cfg(feature = "libm")]
+pub use float_math::*;
+
+/* ───────────────── sized ints ───────────────── */
+//narrow ints sit at the start of their box (or back to back inside a dense array)
+//so every access loads and stores exactly their width and never touches the bytes after
+//int is the i64 of this family, arithmetic behaves the same as the int words
+
+pub trait SizedInt:
+    Copy
+    + PartialOrd
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+    + BitXor<Output = Self>
+    + TryFrom<PalInt>
+    + TryInto<PalInt>
+{
+    ///keeps the low bits
+    fn truncate(v: PalInt) -> Self;
+    ///sign or zero extends (u64 reinterprets)
+    fn widen(self) -> PalInt;
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    fn checked_div(self, rhs: Self) -> Option<Self>;
+    fn checked_rem(self, rhs: Self) -> Option<Self>;
+}
+
+macro_rules! sized_int {
+    ($($t:ty),*) => {$(
+        impl SizedInt for $t {
+            #[inline(always)]
+            fn truncate(v: PalInt) -> Self { v as $t }
+            #[inline(always)]
+            fn widen(self) -> PalInt { self as PalInt }
+            fn checked_add(self, rhs: Self) -> Option<Self> { <$t>::checked_add(self, rhs) }
+            fn checked_sub(self, rhs: Self) -> Option<Self> { <$t>::checked_sub(self, rhs) }
+            fn checked_mul(self, rhs: Self) -> Option<Self> { <$t>::checked_mul(self, rhs) }
+            fn checked_div(self, rhs: Self) -> Option<Self> { <$t>::checked_div(self, rhs) }
+            fn checked_rem(self, rhs: Self) -> Option<Self> { <$t>::checked_rem(self, rhs) }
+        }
+    )*};
+}
+sized_int!(i8, i16, i32, u8, u16, u32, u64);
+
+#[inline(always)]
+unsafe fn load<T: Copy>(p: *const PalData) -> T {
+    unsafe { *(p as *const T) }
+}
+
+#[inline(always)]
+unsafe fn store<T: Copy>(p: *mut PalData, v: T) {
+    unsafe { *(p as *mut T) = v }
+}
+
+macro_rules! sized_arith_fn {
+    ($fname:ident, $op:tt) => {
+        pub unsafe extern "C-unwind" fn $fname<T: SizedInt>(code_ptr:*const Code, vm:&mut Vm) -> *const Code { unsafe {
+            let rhs = pop!(vm);
+            let lhs = *spot!(vm, 0);
+            store(lhs, load::<T>(lhs) $op load::<T>(rhs));
+            code_ptr
+        }}
+    };
+}
+sized_arith_fn!(sized_add, +);
+sized_arith_fn!(sized_sub, -);
+sized_arith_fn!(sized_mul, *);
+sized_arith_fn!(sized_div, /);
+sized_arith_fn!(sized_mod, %);
+sized_arith_fn!(sized_and, &);
+sized_arith_fn!(sized_or , |);
+sized_arith_fn!(sized_xor, ^);
+
+//the arithmetic that can trap, these say if it would on the literals the compiler wants to fold
+macro_rules! sized_trap_fn {
+    ($fname:ident, $checked:ident) => {
+        fn $fname<T: SizedInt>(v: &[PalData]) -> bool {
+            unsafe { load::<T>(&v[0]).$checked(load::<T>(&v[1])).is_none() }
+        }
+    };
+}
+sized_trap_fn!(sized_add_traps, checked_add);
+sized_trap_fn!(sized_sub_traps, checked_sub);
+sized_trap_fn!(sized_mul_traps, checked_mul);
+sized_trap_fn!(sized_div_traps, checked_div);
+sized_trap_fn!(sized_mod_traps, checked_rem);
+
+macro_rules! sized_cmp_fn {
+    ($fname:ident, $op:tt) => {
+        pub unsafe extern "C-unwind" fn $fname<T: SizedInt>(code_ptr:*const Code, vm:&mut Vm) -> *const Code { unsafe {
+            let rhs = pop!(vm);
+            let lhs = pop!(vm);
+            let dst = (*spot!(vm, 0)) as *mut PalBool;
+            *dst = load::<T>(lhs) $op load::<T>(rhs);
+            code_ptr
+        }}
+    };
+}
+sized_cmp_fn!(sized_eq,  ==);
+sized_cmp_fn!(sized_neq, !=);
+sized_cmp_fn!(sized_smaller, <);
+sized_cmp_fn!(sized_bigger, >);
+sized_cmp_fn!(sized_le, <=);
+sized_cmp_fn!(sized_ge, >=);
+
+///keeps the low bits of the int
+pub unsafe extern "C-unwind" fn int_to_sized<T: SizedInt>(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    unsafe {
+        let src = pop!(vm);
+        store(*spot!(vm, 0), T::truncate((*src).int));
+        code_ptr
+    }
+}
+
+///writes the int only if it fits, the bool on top says if it did
+pub unsafe extern "C-unwind" fn int_to_sized_checked<T: SizedInt>(
+    code_ptr: *const Code,
+    vm: &mut Vm,
+) -> *const Code {
+    unsafe {
+        let src = pop!(vm);
+        let ok = (*spot!(vm, 0)) as *mut PalBool;
+        let res = T::try_from((*src).int);
+        *ok = res.is_ok();
+        if let Ok(v) = res {
+            store(*spot!(vm, 1), v);
+        }
+        code_ptr
+    }
+}
+
+///widens into an int, u64 above the int range wraps to negative
+pub unsafe extern "C-unwind" fn sized_to_int<T: SizedInt>(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    unsafe {
+        let src = pop!(vm);
+        (**spot!(vm, 0)).int = load::<T>(src).widen();
+        code_ptr
+    }
+}
+
+///writes the value only if it fits in an int, the bool on top says if it did
+pub unsafe extern "C-unwind" fn sized_to_int_checked<T: SizedInt>(
+    code_ptr: *const Code,
+    vm: &mut Vm,
+) -> *const Code {
+    unsafe {
+        let src = pop!(vm);
+        let ok = (*spot!(vm, 0)) as *mut PalBool;
+        let res: Result<PalInt, _> = load::<T>(src).try_into();
+        *ok = res.is_ok();
+        if let Ok(v) = res {
+            (**spot!(vm, 1)).int = v;
+        }
+        code_ptr
+    }
+}
+
+///the op of word w of sized int t, these come after every other buildin
+const fn sized_op(t: u16, w: u16) -> Op {
+    Op(OpId::_Sized as u16 + 1 + t * 18 + w)
+}
+
+///the words of one sized int, arithmetic then comparisons then conversions
+macro_rules! sized_words {
+    ($t:ty, $i:expr) => {
+        [
+            (concat!(stringify!($t), "_add"), Buildin::new(sized_add::<$t>, sized_op($i, 0))),
+            (concat!(stringify!($t), "_sub"), Buildin::new(sized_sub::<$t>, sized_op($i, 1))),
+            (concat!(stringify!($t), "_mul"), Buildin::new(sized_mul::<$t>, sized_op($i, 2))),
+            (concat!(stringify!($t), "_div"), Buildin::new(sized_div::<$t>, sized_op($i, 3))),
+            (concat!(stringify!($t), "_mod"), Buildin::new(sized_mod::<$t>, sized_op($i, 4))),
+            (concat!(stringify!($t), "_and"), Buildin::new(sized_and::<$t>, sized_op($i, 5))),
+            (concat!(stringify!($t), "_or"), Buildin::new(sized_or::<$t>, sized_op($i, 6))),
+            (concat!(stringify!($t), "_xor"), Buildin::new(sized_xor::<$t>, sized_op($i, 7))),
+            (concat!(stringify!($t), "_eq"), Buildin::new(sized_eq::<$t>, sized_op($i, 8))),
+            (concat!(stringify!($t), "_neq"), Buildin::new(sized_neq::<$t>, sized_op($i, 9))),
+            (concat!(stringify!($t), "_smaller"), Buildin::new(sized_smaller::<$t>, sized_op($i, 10))),
+            (concat!(stringify!($t), "_bigger"), Buildin::new(sized_bigger::<$t>, sized_op($i, 11))),
+            (concat!(stringify!($t), "_le"), Buildin::new(sized_le::<$t>, sized_op($i, 12))),
+            (concat!(stringify!($t), "_ge"), Buildin::new(sized_ge::<$t>, sized_op($i, 13))),
+            (concat!(stringify!($t), "_from_int"), Buildin::new(int_to_sized::<$t>, sized_op($i, 14))),
+            (concat!(stringify!($t), "_from_int_checked"), Buildin::new(int_to_sized_checked::<$t>, sized_op($i, 15))),
+            (concat!(stringify!($t), "_to_int"), Buildin::new(sized_to_int::<$t>, sized_op($i, 16))),
+            (concat!(stringify!($t), "_to_int_checked"), Buildin::new(sized_to_int_checked::<$t>, sized_op($i, 17))),
+        ]
+    };
+}
+
+///the checks of the 5 words that can trap (add sub mul div mod)
+macro_rules! sized_traps {
+    ($t:ty) => {
+        [
+            sized_add_traps::<$t>,
+            sized_sub_traps::<$t>,
+            sized_mul_traps::<$t>,
+            sized_div_traps::<$t>,
+            sized_mod_traps::<$t>,
+        ]
+    };
+}
+
+pub type SizedWords = [(&'static str, Buildin); 18];
+
+///every sized int with its size in bytes, its words and when its arithmetic traps
+///the words are: 8 arithmetic, 6 comparisons, from_int, from_int_checked, to_int, to_int_checked
+pub static SIZED_INTS: [(&str, i32, SizedWords, [TrapCheck; 5]); 7] = [
+    ("i8", 1, sized_words!(i8, 0), sized_traps!(i8)),
+    ("i16", 2, sized_words!(i16, 1), sized_traps!(i16)),
+    ("i32", 4, sized_words!(i32, 2), sized_traps!(i32)),
+    ("u8", 1, sized_words!(u8, 3), sized_traps!(u8)),
+    ("u16", 2, sized_words!(u16, 4), sized_traps!(u16)),
+    ("u32", 4, sized_words!(u32, 5), sized_traps!(u32)),
+    ("u64", 8, sized_words!(u64, 6), sized_traps!(u64)),
+];
+
+/* ───────────────── strings ───────────────── */
+//a str box is a (ptr,len) pair of cells like a byte slice but the bytes are allways valid UTF-8
+//words that make strings write into a caller provided byte slice and hand it back as a str
+//that str is derived from the slice so the buffer stays borrowed for as long as the str lives
+
+#[inline(always)]
+unsafe fn as_bytes<'a>(p: *mut PalData) -> &'a [u8] {
+    unsafe { slice::from_raw_parts((*p).ptr as *const u8, (*p.add(1)).int as usize) }
+}
+
+#[inline(always)]
+unsafe fn as_str<'a>(p: *mut PalData) -> &'a str {
+    unsafe { core::str::from_utf8_unchecked(as_bytes(p)) }
+}
+
+///formats into the byte slice under spot and points spot at a str of what was written
+///the (ptr,len) of that str goes in the last 2 aligned cells of the slice and the text before them
+///the slice keeps its length, the str borrows it so neither is touched while the str lives
+unsafe fn write_to_buffer(spot: *mut *mut PalData, args: fmt::Arguments) {
+    unsafe {
+        let buf = *spot;
+        let start = (*buf).ptr as *mut u8;
+        let len = (*buf.add(1)).int as usize;
+        let header_size = 2 * size_of::<PalData>();
+        let Some(room) = len
+            .checked_sub(header_size)
+            .and_then(|room| room.checked_sub((start.addr() + room) % align_of::<PalData>()))
+        else {
+            panic!("string buffer too small: {len} bytes");
+        };
+        let raw = slice::from_raw_parts_mut(start as *mut MaybeUninit<u8>, room);
+        let mut arena = StackAlloc::from_slice(raw);
+        let mut writer = StackWriter::new(&mut arena);
+        if writer.write_fmt(args).is_err() {
+            panic!("string buffer too small: {len} bytes");
+        }
+        let written = writer.finish().len();
+        let header = start.add(room) as *mut PalData;
+        *header = PalData { ptr: start as *mut PalData };
+        *header.add(1) = PalData { int: written as PalInt };
+        *spot = header;
+    }
+}
+
+///what a failed [`str_from_bytes`] points at, it is never written
+struct EmptyStr([PalData; 2]);
+unsafe impl Sync for EmptyStr {}
+static EMPTY_STR: EmptyStr = EmptyStr([
+    PalData { ptr: NonNull::dangling().as_ptr() },
+    PalData { int: 0 },
+]);
+
+pub unsafe extern "C-unwind" fn str_len(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    unsafe {
+        let s = pop!(vm);
+        (**spot!(vm, 0)).int = (*s.add(1)).int;
+        code_ptr
+    }
+}
+
+///copies out a byte, strings are never written in place so this is not a derived box
+pub unsafe extern "C-unwind" fn str_byte_at(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    unsafe {
+        let idx = (*pop!(vm)).int;
+        let s = pop!(vm);
+        check_bounds(idx, (*s.add(1)).int);
+        store(*spot!(vm, 0), *as_bytes(s).get_unchecked(idx as usize));
+        code_ptr
+    }
+}
+
+///the code point of the idx'th char, this walks the string so it is linear
+///the check is kept even with unchecked_bounds since there is nothing to read past the end
+pub unsafe extern "C-unwind" fn str_char_at(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    unsafe {
+        let idx = (*pop!(vm)).int;
+        let s = as_str(pop!(vm));
+        let Some(c) = usize::try_from(idx).ok().and_then(|i| s.chars().nth(i)) else {
+            panic!("index out of bounds: {idx} >= {}", s.chars().count());
+        };
+        (**spot!(vm, 0)).int = c as PalInt;
+        code_ptr
+    }
+}
+
+macro_rules! str_cmp_fn {
+    ($fname:ident, $op:tt) => {
+        pub unsafe extern "C-unwind" fn $fname(code_ptr:*const Code, vm:&mut Vm) -> *const Code { unsafe {
+            let rhs = as_str(pop!(vm));
+            let lhs = as_str(pop!(vm));
+            let dst = (*spot!(vm, 0)) as *mut PalBool;
+            *dst = lhs $op rhs;
+            code_ptr
+        }}
+    };
+}
+//ordering is by bytes which for UTF-8 is the same as by code points
+str_cmp_fn!(str_eq, ==);
+str_cmp_fn!(str_neq, !=);
+str_cmp_fn!(str_smaller, <);
+str_cmp_fn!(str_bigger, >);
+
+///writes both strings one after the other into the byte slice under them
+pub unsafe extern "C-unwind" fn str_concat(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    unsafe {
+        let rhs = as_str(pop!(vm));
+        let lhs = as_str(pop!(vm));
+        write_to_buffer(spot!(vm, 0), format_args!("{lhs}{rhs}"));
+        code_ptr
+    }
+}
+
+pub unsafe extern "C-unwind" fn str_from_int(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    unsafe {
+        let v = (*pop!(vm)).int;
+        write_to_buffer(spot!(vm, 0), format_args!("{v}"));
+        code_ptr
+    }
+}
+
+pub unsafe extern "C-unwind" fn str_from_float(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    unsafe {
+        let v = (*pop!(vm)).float;
+        write_to_buffer(spot!(vm, 0), format_args!("{v}"));
+        code_ptr
+    }
+}
+
+///views a byte slice as a str if it is valid UTF-8, the bool under it says if it was
+///on failure the str is empty
+pub unsafe extern "C-unwind" fn str_from_bytes(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    unsafe {
+        let p = spot!(vm, 0);
+        let ok = core::str::from_utf8(as_bytes(*p)).is_ok();
+        *((*spot!(vm, 1)) as *mut PalBool) = ok;
+        if !ok {
+            *p = EMPTY_STR.0.as_ptr() as *mut PalData;
+        }
+        code_ptr
+    }
+}
+
+#[inline(always)]
+unsafe fn parse_op<T: FromStr>(vm: &mut Vm, set: impl Fn(&mut PalData, T)) {
+    unsafe {
+        let s = as_str(pop!(vm));
+        let ok = (*spot!(vm, 0)) as *mut PalBool;
+        let res = s.parse::<T>();
+        *ok = res.is_ok();
+        if let Ok(v) = res {
+            set(&mut **spot!(vm, 1), v);
+        }
+    }
+}
+
+///writes the int only if the whole string is one, the bool on top says if it was
+pub unsafe extern "C-unwind" fn str_to_int(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    unsafe {
+        parse_op(vm, |d, v| d.int = v);
+        code_ptr
+    }
+}
+
+///writes the float only if the whole string is one, the bool on top says if it was
+pub unsafe extern "C-unwind" fn str_to_float(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    unsafe {
+        parse_op(vm, |d, v| d.float = v);
+        code_ptr
+    }
+}
+
 /* ───────────────── boolean logic ───────────────── */
 
 macro_rules! bool_logic {
@@ -353,3 +1145,647 @@ pub unsafe extern "C-unwind" fn bool_not(code_ptr: *const Code, vm: &mut Vm) ->
         code_ptr
     }
 }
+
+/* ───────────────── fast buildins ───────────────── */
+//these only see the param stack so the fast dispatch loop can keep its head in a register
+//on the plain loop they run through fast_call
+//with fast_dispatch on the compiler emits them in place of the plain buildin (see fast_variant)
+
+#[inline(always)]
+unsafe fn fast_need(sp: ParamHead, above: ParamHead, n: usize) {
+    #[cfg(not(feature = "unchecked_underflow"))]
+    if (unsafe { above.offset_from(sp) } as usize) < n {
+        panic!("stack underflow")
+    }
+    #[cfg(feature = "unchecked_underflow")]
+    let _ = (sp, above, n);
+}
+
+#[inline(always)]
+unsafe fn fast_room(sp: ParamHead, end: ParamHead) {
+    #[cfg(not(feature = "unchecked_overflow"))]
+    if sp == end {
+        panic!("stack overflow")
+    }
+    #[cfg(feature = "unchecked_overflow")]
+    let _ = (sp, end);
+}
+
+///runs the [`FastCode`] its param points to
+pub unsafe extern "C-unwind" fn fast_call(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    unsafe {
+        let fast = &*(param(code_ptr) as *const FastCode);
+        let stack = &mut vm.param_stack;
+        let sp = (fast.f)(fast.param, stack.get_head(), stack.get_above(), stack.get_end());
+        stack.set_head(sp);
+        code_ptr
+    }
+}
+
+macro_rules! fast_arith {
+    ($slow:ident, $fname:ident, $code:ident, $op:tt) => {
+        pub unsafe extern "C-unwind" fn $fname(_:*const Code, sp:ParamHead, above:ParamHead, _:ParamHead) -> ParamHead { unsafe {
+            fast_need(sp, above, 2);
+            let rhs = *sp as *const PalData;
+            let lhs = *sp.add(1);
+            (*lhs).int = (*lhs).int $op (*rhs).int;
+            sp.add(1)
+        }}
+        pub static $code: FastCode = FastCode::new($fname, core::ptr::null(), op::$slow);
+    };
+}
+fast_arith!(int_add, int_add_fast, INT_ADD_FAST, +);
+fast_arith!(int_sub, int_sub_fast, INT_SUB_FAST, -);
+fast_arith!(int_mul, int_mul_fast, INT_MUL_FAST, *);
+fast_arith!(int_div, int_div_fast, INT_DIV_FAST, /);
+fast_arith!(int_mod, int_mod_fast, INT_MOD_FAST, %);
+fast_arith!(int_shl, int_shl_fast, INT_SHL_FAST, <<);
+fast_arith!(int_shr, int_shr_fast, INT_SHR_FAST, >>);
+fast_arith!(int_and, int_and_fast, INT_AND_FAST, &);
+fast_arith!(int_or, int_or_fast, INT_OR_FAST, |);
+fast_arith!(int_xor, int_xor_fast, INT_XOR_FAST, ^);
+
+macro_rules! fast_cmp {
+    ($slow:ident, $fname:ident, $code:ident, $op:tt) => {
+        pub unsafe extern "C-unwind" fn $fname(_:*const Code, sp:ParamHead, above:ParamHead, _:ParamHead) -> ParamHead { unsafe {
+            fast_need(sp, above, 3);
+            let rhs = *sp as *const PalData;
+            let lhs = *sp.add(1) as *const PalData;
+            let dst = *sp.add(2) as *mut PalBool;
+            *dst = (*lhs).int $op (*rhs).int;
+            sp.add(2)
+        }}
+        pub static $code: FastCode = FastCode::new($fname, core::ptr::null(), op::$slow);
+    };
+}
+fast_cmp!(int_eq, int_eq_fast, INT_EQ_FAST, ==);
+fast_cmp!(int_neq, int_neq_fast, INT_NEQ_FAST, !=);
+fast_cmp!(int_smaller, int_smaller_fast, INT_SMALLER_FAST, <);
+fast_cmp!(int_bigger, int_bigger_fast, INT_BIGGER_FAST, >);
+fast_cmp!(int_le, int_le_fast, INT_LE_FAST, <=);
+fast_cmp!(int_ge, int_ge_fast, INT_GE_FAST, >=);
+
+macro_rules! fast_logic {
+    ($slow:ident, $fname:ident, $code:ident, $op:tt) => {
+        pub unsafe extern "C-unwind" fn $fname(_:*const Code, sp:ParamHead, above:ParamHead, _:ParamHead) -> ParamHead { unsafe {
+            fast_need(sp, above, 2);
+            let src = *sp as *const PalBool;
+            let dst = *sp.add(1) as *mut PalBool;
+            *dst = (*src) $op (*dst);
+            sp.add(1)
+        }}
+        pub static $code: FastCode = FastCode::new($fname, core::ptr::null(), op::$slow);
+    };
+}
+fast_logic!(bool_and, bool_and_fast, BOOL_AND_FAST, &);
+fast_logic!(bool_or, bool_or_fast, BOOL_OR_FAST, |);
+fast_logic!(bool_xor, bool_xor_fast, BOOL_XOR_FAST, ^);
+
+pub unsafe extern "C-unwind" fn bool_not_fast(
+    _: *const Code,
+    sp: ParamHead,
+    above: ParamHead,
+    _: ParamHead,
+) -> ParamHead {
+    unsafe {
+        fast_need(sp, above, 1);
+        let dst = *sp as *mut PalBool;
+        *dst = !*dst;
+        sp
+    }
+}
+pub static BOOL_NOT_FAST: FastCode =
+    FastCode::new(bool_not_fast, core::ptr::null(), op::bool_not);
+
+///every param free fast buildin
+static FAST_TABLE: &[&FastCode] = &[
+    &INT_ADD_FAST,
+    &INT_SUB_FAST,
+    &INT_MUL_FAST,
+    &INT_DIV_FAST,
+    &INT_MOD_FAST,
+    &INT_SHL_FAST,
+    &INT_SHR_FAST,
+    &INT_AND_FAST,
+    &INT_OR_FAST,
+    &INT_XOR_FAST,
+    &INT_EQ_FAST,
+    &INT_NEQ_FAST,
+    &INT_SMALLER_FAST,
+    &INT_BIGGER_FAST,
+    &INT_LE_FAST,
+    &INT_GE_FAST,
+    &BOOL_AND_FAST,
+    &BOOL_OR_FAST,
+    &BOOL_XOR_FAST,
+    &BOOL_NOT_FAST,
+];
+
+///the fast buildin doing the same work as code, if there is one
+pub fn fast_variant(code: &Code) -> Option<&'static FastCode> {
+    FAST_TABLE.iter().find(|f| code.is(f.slow)).copied()
+}
+
+//these take a param so they need their own FastCode per use
+
+pub unsafe extern "C-unwind" fn push_var_fast(
+    p: *const Code,
+    sp: ParamHead,
+    _: ParamHead,
+    end: ParamHead,
+) -> ParamHead {
+    unsafe {
+        fast_room(sp, end);
+        let sp = sp.sub(1);
+        *sp = p as *mut PalData;
+        sp
+    }
+}
+
+pub unsafe extern "C-unwind" fn pick_fast(
+    p: *const Code,
+    sp: ParamHead,
+    above: ParamHead,
+    end: ParamHead,
+) -> ParamHead {
+    unsafe {
+        let n = p as usize;
+        fast_need(sp, above, n + 1);
+        fast_room(sp, end);
+        let v = *sp.add(n);
+        let sp = sp.sub(1);
+        *sp = v;
+        sp
+    }
+}
+
+pub unsafe extern "C-unwind" fn param_drop_fast(
+    p: *const Code,
+    sp: ParamHead,
+    above: ParamHead,
+    _: ParamHead,
+) -> ParamHead {
+    unsafe {
+        let n = p as usize;
+        fast_need(sp, above, n);
+        sp.add(n)
+    }
+}
+
+/* ───────────────── compile time ───────────────── */
+//these talk to the compiler so errors go to comp.error instead of crashing
+
+///reads the name (and maybe stack effect) of the next word and starts compiling it
+pub unsafe extern "C-unwind" fn colon(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    let comp = vm.comp.get_comp_crash();
+    match comp.read_name().and_then(|name| Ok((name, comp.read_sig()?))) {
+        Ok((name, declared)) => {
+            comp.begin_word(name);
+            comp.declared = declared;
+        }
+        Err(e) => {
+            comp.error = Some(e);
+            return code_ptr;
+        }
+    }
+    vm.comp.set_compiling(true);
+    code_ptr
+}
+
+///finishes the current word and goes back to running
+pub unsafe extern "C-unwind" fn semicolon(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    let comp = vm.comp.get_comp_crash();
+    if let Err(e) = comp.end_word() {
+        comp.error = Some(e);
+    }
+    vm.comp.set_compiling(false);
+    code_ptr
+}
+
+///reads a name and its fields and registers the cluster along with its field words
+pub unsafe extern "C-unwind" fn cluster(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    let comp = vm.comp.get_comp_crash();
+    if let Err(e) = comp.read_name().and_then(|name| comp.read_cluster(name)) {
+        comp.error = Some(e);
+    }
+    code_ptr
+}
+
+///drops the box on top while running
+pub unsafe extern "C-unwind" fn drop_box(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    let comp = vm.comp.get_comp_crash();
+    match comp.drop_box(true) {
+        Ok(()) => unsafe {
+            pop!(vm);
+        },
+        Err(e) => comp.error = Some(e.into()),
+    }
+    code_ptr
+}
+
+///compiles a drop of the box on top
+pub unsafe extern "C-unwind" fn drop_box_comp(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    let comp = vm.comp.get_comp_crash();
+    if let Err(e) = comp.drop_box(false) {
+        comp.error = Some(e.into());
+    }
+    code_ptr
+}
+
+///reads `name : type` and registers a zeroed global of that type
+pub unsafe extern "C-unwind" fn variable(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    let comp = vm.comp.get_comp_crash();
+    if let Err(e) = comp.read_name().and_then(|name| comp.read_variable(name)) {
+        comp.error = Some(e);
+    }
+    code_ptr
+}
+
+///reads `name = literal` and registers a global that can only be read
+pub unsafe extern "C-unwind" fn constant(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    let comp = vm.comp.get_comp_crash();
+    if let Err(e) = comp.read_name().and_then(|name| comp.read_constant(name)) {
+        comp.error = Some(e);
+    }
+    code_ptr
+}
+
+///reads a type and a word and makes the word its destructor
+pub unsafe extern "C-unwind" fn destructor(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    let comp = vm.comp.get_comp_crash();
+    if let Err(e) = comp.read_destructor() {
+        comp.error = Some(e);
+    }
+    code_ptr
+}
+
+///reads a name and a type and registers the name as an alias of the type
+pub unsafe extern "C-unwind" fn alias(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    let comp = vm.comp.get_comp_crash();
+    match comp.read_name().and_then(|name| Ok((name, comp.read_type()?))) {
+        Ok((name, tp)) => {
+            comp.lex.add_alias(name, tp);
+        }
+        Err(e) => comp.error = Some(e),
+    }
+    code_ptr
+}
+
+///reads a name and a type and registers a newtype of it along with its wrap and unwrap words
+pub unsafe extern "C-unwind" fn newtype(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    let comp = vm.comp.get_comp_crash();
+    match comp.read_name().and_then(|name| Ok((name, comp.read_type()?))) {
+        Ok((name, tp)) => {
+            comp.lex.add_newtype(name, tp);
+        }
+        Err(e) => comp.error = Some(e),
+    }
+    code_ptr
+}
+
+///opens an unsafe{ region
+pub unsafe extern "C-unwind" fn unsafe_begin(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    vm.comp.get_comp_crash().begin_unsafe();
+    code_ptr
+}
+
+///closes the innermost unsafe{ region
+pub unsafe extern "C-unwind" fn unsafe_end(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    let comp = vm.comp.get_comp_crash();
+    if let Err(e) = comp.end_unsafe() {
+        comp.error = Some(e);
+    }
+    code_ptr
+}
+
+///reads a s" literal while running and pushes a fresh box holding it
+pub unsafe extern "C-unwind" fn str_literal(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    let comp = vm.comp.get_comp_crash();
+    match comp.read_str() {
+        Ok((tp, cells)) => {
+            let data = comp.run_const(tp, &cells);
+            unsafe { push!(vm, data) };
+        }
+        Err(e) => comp.error = Some(e),
+    }
+    code_ptr
+}
+
+///reads a s" literal while compiling and pushes it as a constant
+pub unsafe extern "C-unwind" fn str_literal_comp(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    let comp = vm.comp.get_comp_crash();
+    match comp.read_str() {
+        Ok((tp, cells)) => comp.add_const(tp, &cells),
+        Err(e) => comp.error = Some(e),
+    }
+    code_ptr
+}
+
+///reads a { name:type ... } declaration of locals for the current word
+pub unsafe extern "C-unwind" fn locals(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    let comp = vm.comp.get_comp_crash();
+    if let Err(e) = comp.read_locals() {
+        comp.error = Some(e);
+        //a bad declaration throws the word away
+        vm.comp.set_compiling(false);
+    }
+    code_ptr
+}
+
+///reads a name and pushes a fresh box holding an xt of its word
+pub unsafe extern "C-unwind" fn tick(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    let comp = vm.comp.get_comp_crash();
+    match comp.read_xt() {
+        Ok((tp, xt)) => {
+            let data = comp.run_literal(tp, xt);
+            unsafe { push!(vm, data) };
+        }
+        Err(e) => comp.error = Some(e),
+    }
+    code_ptr
+}
+
+///reads a name and pushes an xt of its word as a constant
+pub unsafe extern "C-unwind" fn tick_comp(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    let comp = vm.comp.get_comp_crash();
+    match comp.read_xt() {
+        Ok((tp, xt)) => comp.add_literal(tp, xt),
+        Err(e) => comp.error = Some(e),
+    }
+    code_ptr
+}
+
+///checks the inputs under the xt on top against its type and calls it
+pub unsafe extern "C-unwind" fn execute(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    let comp = vm.comp.get_comp_crash();
+    match comp.execute_word(true).and_then(|word| Ok(comp.check_run(&word)?)) {
+        //the callee returns to the ret after us which ends the run
+        Ok(()) => unsafe { call_dyn(code_ptr, vm) },
+        Err(e) => {
+            comp.error = Some(e);
+            code_ptr
+        }
+    }
+}
+
+///compiles a checked call to the xt on top
+pub unsafe extern "C-unwind" fn execute_comp(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    let comp = vm.comp.get_comp_crash();
+    if let Err(e) = comp.execute_word(false).and_then(|word| Ok(comp.add_word(&word)?)) {
+        comp.error = Some(e);
+    }
+    code_ptr
+}
+
+///starts compiling a [: ;] quotation
+pub unsafe extern "C-unwind" fn quote_begin(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    let comp = vm.comp.get_comp_crash();
+    match comp.begin_quotation() {
+        Ok(()) => vm.comp.set_compiling(true),
+        Err(e) => comp.error = Some(e),
+    }
+    code_ptr
+}
+
+///finishes the current quotation and pushes a fresh box holding its xt
+pub unsafe extern "C-unwind" fn quote_end(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    let comp = vm.comp.get_comp_crash();
+    match comp.end_quotation() {
+        Ok(data) => unsafe { push!(vm, data) },
+        Err(e) => comp.error = Some(e),
+    }
+    vm.comp.set_compiling(false);
+    code_ptr
+}
+
+///reads a name and its cases and registers the variant along with its constructors
+pub unsafe extern "C-unwind" fn variant(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    let comp = vm.comp.get_comp_crash();
+    if let Err(e) = comp.read_name().and_then(|name| comp.read_variant(name)) {
+        comp.error = Some(e);
+    }
+    code_ptr
+}
+
+///opens a match on the variant on top
+pub unsafe extern "C-unwind" fn match_(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    let comp = vm.comp.get_comp_crash();
+    if let Err(e) = comp.begin_match() {
+        comp.error = Some(e);
+    }
+    code_ptr
+}
+
+///reads a case name and starts its arm with the payload on top
+pub unsafe extern "C-unwind" fn case(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    let comp = vm.comp.get_comp_crash();
+    if let Err(e) = comp.read_name().and_then(|name| comp.begin_case(name)) {
+        comp.error = Some(e);
+    }
+    code_ptr
+}
+
+///closes the innermost match checking every case got an arm
+pub unsafe extern "C-unwind" fn endmatch(code_ptr: *const Code, vm: &mut Vm) -> *const Code {
+    let comp = vm.comp.get_comp_crash();
+    if let Err(e) = comp.end_match() {
+        comp.error = Some(e);
+    }
+    code_ptr
+}
+
+/*═════════════════════════════ ids ════════════════════════════════*/
+
+macro_rules! buildin_ops {
+    ($($(#[$attr:meta])* $name:ident),* $(,)?) => {
+        #[allow(non_camel_case_types)]
+        #[repr(u16)]
+        enum OpId {
+            $($(#[$attr])* $name,)*
+            //the sized int words are numbered from here on
+            _Sized,
+        }
+
+        ///every buildin paired with its stable [`Op`], 0 is left for [`Op::NONE`]
+        ///code that should be recognized later (tail calls, tools) must be built from these
+        #[allow(non_upper_case_globals)]
+        pub mod op {
+            use crate::vm::{Buildin, Op};
+            $(
+                $(#[$attr])*
+                pub const $name: Buildin = Buildin::new(super::$name, Op(super::OpId::$name as u16 + 1));
+            )*
+        }
+
+        ///every buildin that is not specific to a platform by name
+        ///this is what tools such as [`crate::codegen_c`] use to recognize code
+        pub static BUILDIN_TABLE: &[(&str, Buildin)] = &[
+            $($(#[$attr])* (stringify!($name), op::$name),)*
+        ];
+    };
+}
+
+buildin_ops! {
+    no_op,
+    log_bytes,
+    inject,
+    inject_non_unique,
+    swap_bytes,
+    ptr_load,
+    ptr_store,
+    ptr_of,
+    #[cfg(feature = "heap")]
+    heap_alloc,
+    #[cfg(feature = "heap")]
+    heap_free,
+    #[cfg(feature = "heap")]
+    box_open,
+    frame_alloc,
+    frame_free,
+    param_drop,
+    push_local,
+    push_var,
+    pick,
+    field_at,
+    bounds_check,
+    array_at,
+    slice_at,
+    variant_tag,
+    variant_payload,
+    variant_skip,
+    branch,
+    _if,
+    maybe_backpatch,
+    jump,
+    tail_call,
+    call_dyn,
+    call_dyn_threaded,
+    ret,
+    fast_call,
+    #[cfg(feature = "std")]
+    const_print,
+    int_add,
+    int_sub,
+    int_mul,
+    int_div,
+    int_mod,
+    int_shl,
+    int_shr,
+    int_and,
+    int_or,
+    int_xor,
+    int_add_wrapping,
+    int_sub_wrapping,
+    int_mul_wrapping,
+    int_div_wrapping,
+    int_mod_wrapping,
+    int_add_saturating,
+    int_sub_saturating,
+    int_mul_saturating,
+    int_div_saturating,
+    int_mod_saturating,
+    int_add_checked,
+    int_sub_checked,
+    int_mul_checked,
+    int_div_checked,
+    int_mod_checked,
+    int_eq,
+    int_neq,
+    int_smaller,
+    int_bigger,
+    int_le,
+    int_ge,
+    float_add,
+    float_sub,
+    float_mul,
+    float_div,
+    float_mod,
+    float_eq,
+    float_neq,
+    float_smaller,
+    float_bigger,
+    float_le,
+    float_ge,
+    int_to_float,
+    float_to_int,
+    #[cfg(feature = "libm")]
+    float_sqrt,
+    #[cfg(feature = "libm")]
+    float_floor,
+    #[cfg(feature = "libm")]
+    float_ceil,
+    #[cfg(feature = "libm")]
+    float_sin,
+    #[cfg(feature = "libm")]
+    float_cos,
+    #[cfg(feature = "libm")]
+    float_exp,
+    #[cfg(feature = "libm")]
+    float_ln,
+    #[cfg(feature = "libm")]
+    float_pow,
+    bool_and,
+    bool_or,
+    bool_xor,
+    bool_not,
+    str_len,
+    str_byte_at,
+    str_char_at,
+    str_eq,
+    str_neq,
+    str_smaller,
+    str_bigger,
+    str_concat,
+    str_from_int,
+    str_from_float,
+    str_from_bytes,
+    str_to_int,
+    str_to_float,
+    str_literal,
+    str_literal_comp,
+    colon,
+    semicolon,
+    locals,
+    tick,
+    tick_comp,
+    execute,
+    execute_comp,
+    quote_begin,
+    quote_end,
+    cluster,
+    variant,
+    drop_box,
+    drop_box_comp,
+    variable,
+    constant,
+    destructor,
+    alias,
+    newtype,
+    match_,
+    case,
+    endmatch,
+    unsafe_begin,
+    unsafe_end,
+}
+
+///looks up the name of the buildin code calls (None for derived words and unknown buildins)
+///a [`Code::fast`] cell is named after the plain buildin it does the work of
+///sized int words are named by their word ("u8_add")
+pub fn buildin_name(code: &Code) -> Option<&'static str> {
+    if code.is(op::fast_call) {
+        //fast_call cells are only made by Code::fast so the param is a live FastCode
+        let fast = unsafe { &*(code.param.load(Ordering::Relaxed) as *const FastCode) };
+        return buildin_name(&Code::basic_raw(fast.slow, core::ptr::null()));
+    }
+    BUILDIN_TABLE
+        .iter()
+        .find(|(_, b)| code.is(*b))
+        .map(|(name, _)| *name)
+        .or_else(|| {
+            SIZED_INTS
+                .iter()
+                .flat_map(|(_, _, words, _)| words)
+                .find(|(_, b)| code.is(*b))
+                .map(|(name, _)| *name)
+        })
+}
@@ -0,0 +1,455 @@
+/* PalForth C runtime used by code generated from src/codegen_c.rs
+ * every buildin is a static inline function taking the vm and the code param
+ * stacks mirror the rust VM: both grow down and hold the same layouts
+ */
+#ifndef PALFORTH_CODEGEN_H
+#define PALFORTH_CODEGEN_H
+
+#include <math.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#ifndef PAL_STACK_SIZE
+#define PAL_STACK_SIZE 1024
+#endif
+
+typedef union PalData {
+    int64_t i;
+    double f;
+    bool b;
+    const void* code;
+    union PalData* ptr;
+} PalData;
+
+typedef struct PalVm {
+    PalData* param[PAL_STACK_SIZE];
+    PalData** param_head; /* lowest live element */
+    PalData data[PAL_STACK_SIZE];
+    PalData* data_head; /* lowest live element */
+} PalVm;
+
+static void pal_fail(const char* msg) {
+    fprintf(stderr, "%s\n", msg);
+    abort();
+}
+
+static inline void pal_vm_init(PalVm* vm) {
+    vm->param_head = vm->param + PAL_STACK_SIZE;
+    vm->data_head = vm->data + PAL_STACK_SIZE;
+}
+
+/* ───────────────── stack helpers ───────────────── */
+
+static inline size_t pal_param_len(PalVm* vm) {
+    return (size_t)(vm->param + PAL_STACK_SIZE - vm->param_head);
+}
+
+static inline void pal_push(PalVm* vm, PalData* p) {
+    if (vm->param_head == vm->param) pal_fail("stack overflow");
+    *--vm->param_head = p;
+}
+
+static inline PalData* pal_pop(PalVm* vm) {
+    if (pal_param_len(vm) == 0) pal_fail("stack underflow");
+    return *vm->param_head++;
+}
+
+static inline PalData* pal_spot(PalVm* vm, size_t n) {
+    if (pal_param_len(vm) <= n) pal_fail("stack underflow");
+    return vm->param_head[n];
+}
+
+/* ───────────────── memory / frame ops ───────────────── */
+
+static inline void pal_inject(PalVm* vm, intptr_t n) {
+    PalData* src = pal_pop(vm);
+    memcpy(pal_spot(vm, 0), src, (size_t)n);
+}
+
+static inline void pal_inject_non_unique(PalVm* vm, intptr_t n) {
+    PalData* src = pal_pop(vm);
+    memmove(pal_spot(vm, 0), src, (size_t)n);
+}
+
+static inline void pal_swap_bytes(PalVm* vm, intptr_t n) {
+    unsigned char* a = (unsigned char*)pal_spot(vm, 0);
+    unsigned char* b = (unsigned char*)pal_spot(vm, 1);
+    for (intptr_t i = 0; i < n; i++) {
+        unsigned char t = a[i];
+        a[i] = b[i];
+        b[i] = t;
+    }
+}
+
+static inline void pal_frame_alloc(PalVm* vm, intptr_t n) {
+    if (vm->data_head - vm->data < n) pal_fail("stack overflow");
+    vm->data_head -= n;
+    memset(vm->data_head, 0, (size_t)n * sizeof(PalData));
+}
+
+static inline void pal_frame_free(PalVm* vm, intptr_t n) {
+    if (vm->data + PAL_STACK_SIZE - vm->data_head < n) pal_fail("stack underflow");
+    vm->data_head += n;
+}
+
+static inline void pal_param_drop(PalVm* vm, intptr_t n) {
+    if ((intptr_t)pal_param_len(vm) < n) pal_fail("stack underflow");
+    vm->param_head += n;
+}
+
+/* ───────────────── stack access ───────────────── */
+
+static inline void pal_push_local(PalVm* vm, intptr_t idx) {
+    if (vm->data + PAL_STACK_SIZE - vm->data_head <= idx) pal_fail("stack underflow");
+    pal_push(vm, vm->data_head + idx);
+}
+
+static inline void pal_pick(PalVm* vm, intptr_t n) {
+    pal_push(vm, pal_spot(vm, (size_t)n));
+}
+
+static inline void pal_field_at(PalVm* vm, intptr_t n) {
+    if (pal_param_len(vm) == 0) pal_fail("stack underflow");
+    vm->param_head[0] += n;
+}
+
+/* ───────────────── indexing ───────────────── */
+
+static inline void pal_check_bounds(int64_t idx, int64_t len) {
+#ifndef PAL_UNCHECKED_BOUNDS
+    if ((uint64_t)idx >= (uint64_t)len) pal_fail("index out of bounds");
+#else
+    (void)idx;
+    (void)len;
+#endif
+}
+
+static inline void pal_bounds_check(PalVm* vm, intptr_t n) {
+    pal_check_bounds(pal_spot(vm, 0)->i, n);
+}
+
+/* the param is the stride in bytes */
+static inline void pal_array_at(PalVm* vm, intptr_t stride) {
+    int64_t idx = pal_pop(vm)->i;
+    vm->param_head[0] = (PalData*)((char*)pal_spot(vm, 0) + idx * stride);
+}
+
+static inline void pal_slice_at(PalVm* vm, intptr_t stride) {
+    int64_t idx = pal_pop(vm)->i;
+    PalData* slice = pal_spot(vm, 0);
+    pal_check_bounds(idx, slice[1].i);
+    vm->param_head[0] = (PalData*)((char*)slice[0].ptr + idx * stride);
+}
+
+/* ───────────────── raw pointers ───────────────── */
+
+static inline void pal_ptr_load(PalVm* vm, intptr_t n) {
+    PalData* p = pal_pop(vm);
+    memmove(pal_spot(vm, 0), p->ptr, (size_t)n);
+}
+
+static inline void pal_ptr_store(PalVm* vm, intptr_t n) {
+    PalData* p = pal_pop(vm);
+    PalData* src = pal_pop(vm);
+    memmove(p->ptr, src, (size_t)n);
+}
+
+static inline void pal_ptr_of(PalVm* vm, intptr_t unused) {
+    (void)unused;
+    PalData* src = pal_pop(vm);
+    pal_spot(vm, 0)->ptr = src;
+}
+
+/* ───────────────── variants ───────────────── */
+/* a tag cell followed by the payload, variant_skip is turned into a goto */
+
+static inline void pal_variant_tag(PalVm* vm, intptr_t tag) {
+    pal_spot(vm, 0)->i = tag;
+}
+
+static inline void pal_variant_payload(PalVm* vm, intptr_t n) {
+    PalData* src = pal_pop(vm);
+    memcpy(pal_spot(vm, 0) + 1, src, (size_t)n);
+}
+
+static inline bool pal_pop_bool(PalVm* vm) {
+    return pal_pop(vm)->b;
+}
+
+/* ───────────────── arithmetic ───────────────── */
+
+#define PAL_ARITH(name, expr)                              \
+    static inline void pal_##name(PalVm* vm, intptr_t _p) { \
+        (void)_p;                                          \
+        PalData* rhs = pal_pop(vm);                        \
+        PalData* lhs = pal_spot(vm, 0);                    \
+        int64_t a = lhs->i, b = rhs->i;                    \
+        lhs->i = (expr);                                   \
+    }
+
+/* division panics in rust no matter the build so we fail the same way */
+static inline void pal_check_div(int64_t a, int64_t b) {
+    if (b == 0) pal_fail("attempt to divide by zero");
+    if (a == INT64_MIN && b == -1) pal_fail("attempt to divide with overflow");
+}
+
+/* wrapping like a release build of the rust VM */
+PAL_ARITH(int_add, (int64_t)((uint64_t)a + (uint64_t)b))
+PAL_ARITH(int_sub, (int64_t)((uint64_t)a - (uint64_t)b))
+PAL_ARITH(int_mul, (int64_t)((uint64_t)a * (uint64_t)b))
+PAL_ARITH(int_div, (pal_check_div(a, b), a / b))
+PAL_ARITH(int_mod, (pal_check_div(a, b), a % b))
+PAL_ARITH(int_shl, (int64_t)((uint64_t)a << (b & 63)))
+PAL_ARITH(int_shr, a >> (b & 63))
+PAL_ARITH(int_and, a & b)
+PAL_ARITH(int_or, a | b)
+PAL_ARITH(int_xor, a ^ b)
+
+/* ───────────────── overflow families ───────────────── */
+/* same on every build, see the rust buildins for the division by zero results */
+
+static inline int64_t pal_sat(bool overflow, int64_t v, bool negative) {
+    if (!overflow) return v;
+    return negative ? INT64_MIN : INT64_MAX;
+}
+
+static inline int64_t pal_sat_add(int64_t a, int64_t b) {
+    int64_t r;
+    return pal_sat(__builtin_add_overflow(a, b, &r), r, b < 0);
+}
+
+static inline int64_t pal_sat_sub(int64_t a, int64_t b) {
+    int64_t r;
+    return pal_sat(__builtin_sub_overflow(a, b, &r), r, b > 0);
+}
+
+static inline int64_t pal_sat_mul(int64_t a, int64_t b) {
+    int64_t r;
+    return pal_sat(__builtin_mul_overflow(a, b, &r), r, (a < 0) != (b < 0));
+}
+
+static inline int64_t pal_sat_div(int64_t a, int64_t b) {
+    if (b == 0) return a > 0 ? INT64_MAX : a < 0 ? INT64_MIN : 0;
+    if (a == INT64_MIN && b == -1) return INT64_MAX;
+    return a / b;
+}
+
+PAL_ARITH(int_add_wrapping, (int64_t)((uint64_t)a + (uint64_t)b))
+PAL_ARITH(int_sub_wrapping, (int64_t)((uint64_t)a - (uint64_t)b))
+PAL_ARITH(int_mul_wrapping, (int64_t)((uint64_t)a * (uint64_t)b))
+PAL_ARITH(int_div_wrapping, b == 0 ? 0 : (a == INT64_MIN && b == -1) ? a : a / b)
+PAL_ARITH(int_mod_wrapping, b == 0 ? a : b == -1 ? 0 : a % b)
+PAL_ARITH(int_add_saturating, pal_sat_add(a, b))
+PAL_ARITH(int_sub_saturating, pal_sat_sub(a, b))
+PAL_ARITH(int_mul_saturating, pal_sat_mul(a, b))
+PAL_ARITH(int_div_saturating, pal_sat_div(a, b))
+PAL_ARITH(int_mod_saturating, (b == 0 || b == -1) ? 0 : a % b)
+
+/* the result is only written when it fits, the flag on top says if it did */
+#define PAL_CHECKED(name, fits, expr)                      \
+    static inline void pal_##name(PalVm* vm, intptr_t _p) { \
+        (void)_p;                                          \
+        PalData* rhs = pal_pop(vm);                        \
+        PalData* lhs = pal_spot(vm, 1);                    \
+        int64_t a = lhs->i, b = rhs->i, r = 0;             \
+        bool ok = (fits);                                  \
+        pal_spot(vm, 0)->b = ok;                           \
+        if (ok) lhs->i = (expr);                           \
+    }
+
+PAL_CHECKED(int_add_checked, !__builtin_add_overflow(a, b, &r), r)
+PAL_CHECKED(int_sub_checked, !__builtin_sub_overflow(a, b, &r), r)
+PAL_CHECKED(int_mul_checked, !__builtin_mul_overflow(a, b, &r), r)
+PAL_CHECKED(int_div_checked, b != 0 && !(a == INT64_MIN && b == -1), a / b)
+PAL_CHECKED(int_mod_checked, b != 0 && !(a == INT64_MIN && b == -1), a % b)
+
+/* ───────────────── comparisons ───────────────── */
+
+#define PAL_CMP(name, op)                                  \
+    static inline void pal_##name(PalVm* vm, intptr_t _p) { \
+        (void)_p;                                          \
+        PalData* rhs = pal_pop(vm);                        \
+        PalData* lhs = pal_pop(vm);                        \
+        pal_spot(vm, 0)->b = lhs->i op rhs->i;             \
+    }
+
+PAL_CMP(int_eq, ==)
+PAL_CMP(int_neq, !=)
+PAL_CMP(int_smaller, <)
+PAL_CMP(int_bigger, >)
+PAL_CMP(int_le, <=)
+PAL_CMP(int_ge, >=)
+
+/* ───────────────── floats ───────────────── */
+/* float_mod and the math words need libm at link time (-lm) */
+
+#define PAL_FARITH(name, expr)                             \
+    static inline void pal_##name(PalVm* vm, intptr_t _p) { \
+        (void)_p;                                          \
+        PalData* rhs = pal_pop(vm);                        \
+        PalData* lhs = pal_spot(vm, 0);                    \
+        double a = lhs->f, b = rhs->f;                     \
+        lhs->f = (expr);                                   \
+    }
+
+PAL_FARITH(float_add, a + b)
+PAL_FARITH(float_sub, a - b)
+PAL_FARITH(float_mul, a * b)
+PAL_FARITH(float_div, a / b)
+PAL_FARITH(float_mod, fmod(a, b))
+PAL_FARITH(float_pow, pow(a, b))
+
+#define PAL_FCMP(name, op)                                 \
+    static inline void pal_##name(PalVm* vm, intptr_t _p) { \
+        (void)_p;                                          \
+        PalData* rhs = pal_pop(vm);                        \
+        PalData* lhs = pal_pop(vm);                        \
+        pal_spot(vm, 0)->b = lhs->f op rhs->f;             \
+    }
+
+PAL_FCMP(float_eq, ==)
+PAL_FCMP(float_neq, !=)
+PAL_FCMP(float_smaller, <)
+PAL_FCMP(float_bigger, >)
+PAL_FCMP(float_le, <=)
+PAL_FCMP(float_ge, >=)
+
+#define PAL_FMATH(name, fn)                                \
+    static inline void pal_##name(PalVm* vm, intptr_t _p) { \
+        (void)_p;                                          \
+        PalData* dst = pal_spot(vm, 0);                    \
+        dst->f = fn(dst->f);                               \
+    }
+
+PAL_FMATH(float_sqrt, sqrt)
+PAL_FMATH(float_floor, floor)
+PAL_FMATH(float_ceil, ceil)
+PAL_FMATH(float_sin, sin)
+PAL_FMATH(float_cos, cos)
+PAL_FMATH(float_exp, exp)
+PAL_FMATH(float_ln, log)
+
+static inline void pal_int_to_float(PalVm* vm, intptr_t _p) {
+    (void)_p;
+    PalData* src = pal_pop(vm);
+    pal_spot(vm, 0)->f = (double)src->i;
+}
+
+/* saturating like a rust `as` cast */
+static inline void pal_float_to_int(PalVm* vm, intptr_t _p) {
+    (void)_p;
+    double v = pal_pop(vm)->f;
+    int64_t r;
+    if (v != v) r = 0;
+    else if (v >= 9223372036854775808.0) r = INT64_MAX;
+    else if (v <= -9223372036854775808.0) r = INT64_MIN;
+    else r = (int64_t)v;
+    pal_spot(vm, 0)->i = r;
+}
+
+/* ───────────────── sized ints ───────────────── */
+/* narrow ints sit at the start of their box so they are loaded and stored at their width */
+/* the overflow behavior matches the int words (wrapping like a release build) */
+
+#define PAL_SIZED_ARITH(t, ct, ut, name, expr)                   \
+    static inline void pal_##t##_##name(PalVm* vm, intptr_t _p) { \
+        (void)_p;                                                \
+        ct* rhs = (ct*)pal_pop(vm);                              \
+        ct* lhs = (ct*)pal_spot(vm, 0);                          \
+        ct a = *lhs, b = *rhs;                                   \
+        *lhs = (ct)(expr);                                       \
+    }
+
+#define PAL_SIZED_CMP(t, ct, name, op)                           \
+    static inline void pal_##t##_##name(PalVm* vm, intptr_t _p) { \
+        (void)_p;                                                \
+        ct* rhs = (ct*)pal_pop(vm);                              \
+        ct* lhs = (ct*)pal_pop(vm);                              \
+        pal_spot(vm, 0)->b = *lhs op *rhs;                       \
+    }
+
+#define PAL_SIZED(t, ct, ut, min)                                             \
+    PAL_SIZED_ARITH(t, ct, ut, add, (ut)a + (ut)b)                            \
+    PAL_SIZED_ARITH(t, ct, ut, sub, (ut)a - (ut)b)                            \
+    PAL_SIZED_ARITH(t, ct, ut, mul, (ut)a * (ut)b)                            \
+    PAL_SIZED_ARITH(t, ct, ut, div, (pal_check_sized_div(b == 0, (ct)-1 < 0 && a == min && b == (ct)-1), a / b)) \
+    PAL_SIZED_ARITH(t, ct, ut, mod, (pal_check_sized_div(b == 0, (ct)-1 < 0 && a == min && b == (ct)-1), a % b)) \
+    PAL_SIZED_ARITH(t, ct, ut, and, a & b)                                    \
+    PAL_SIZED_ARITH(t, ct, ut, or, a | b)                                     \
+    PAL_SIZED_ARITH(t, ct, ut, xor, a ^ b)                                    \
+    PAL_SIZED_CMP(t, ct, eq, ==)                                              \
+    PAL_SIZED_CMP(t, ct, neq, !=)                                             \
+    PAL_SIZED_CMP(t, ct, smaller, <)                                          \
+    PAL_SIZED_CMP(t, ct, bigger, >)                                           \
+    PAL_SIZED_CMP(t, ct, le, <=)                                              \
+    PAL_SIZED_CMP(t, ct, ge, >=)                                              \
+    static inline void pal_##t##_from_int(PalVm* vm, intptr_t _p) {           \
+        (void)_p;                                                             \
+        int64_t v = pal_pop(vm)->i;                                           \
+        *(ct*)pal_spot(vm, 0) = (ct)v;                                        \
+    }                                                                         \
+    static inline void pal_##t##_from_int_checked(PalVm* vm, intptr_t _p) {   \
+        (void)_p;                                                             \
+        int64_t v = pal_pop(vm)->i;                                           \
+        bool ok = (ct)v == v && ((v < 0) == ((ct)v < 0));                     \
+        pal_spot(vm, 0)->b = ok;                                              \
+        if (ok) *(ct*)pal_spot(vm, 1) = (ct)v;                                \
+    }                                                                         \
+    static inline void pal_##t##_to_int(PalVm* vm, intptr_t _p) {             \
+        (void)_p;                                                             \
+        ct v = *(ct*)pal_pop(vm);                                             \
+        pal_spot(vm, 0)->i = (int64_t)v;                                      \
+    }                                                                         \
+    static inline void pal_##t##_to_int_checked(PalVm* vm, intptr_t _p) {     \
+        (void)_p;                                                             \
+        ct v = *(ct*)pal_pop(vm);                                             \
+        bool ok = (int64_t)v >= 0 || v < 0;                                   \
+        pal_spot(vm, 0)->b = ok;                                              \
+        if (ok) pal_spot(vm, 1)->i = (int64_t)v;                              \
+    }
+
+static inline void pal_check_sized_div(bool zero, bool overflow) {
+    if (zero) pal_fail("attempt to divide by zero");
+    if (overflow) pal_fail("attempt to divide with overflow");
+}
+
+PAL_SIZED(i8, int8_t, uint8_t, INT8_MIN)
+PAL_SIZED(i16, int16_t, uint16_t, INT16_MIN)
+PAL_SIZED(i32, int32_t, uint32_t, INT32_MIN)
+PAL_SIZED(u8, uint8_t, uint8_t, 0)
+PAL_SIZED(u16, uint16_t, uint16_t, 0)
+PAL_SIZED(u32, uint32_t, uint32_t, 0)
+PAL_SIZED(u64, uint64_t, uint64_t, 0)
+
+/* ───────────────── boolean logic ───────────────── */
+
+#define PAL_LOGIC(name, op)                                \
+    static inline void pal_##name(PalVm* vm, intptr_t _p) { \
+        (void)_p;                                          \
+        PalData* src = pal_pop(vm);                        \
+        PalData* dst = pal_spot(vm, 0);                    \
+        dst->b = src->b op dst->b;                         \
+    }
+
+PAL_LOGIC(bool_and, &)
+PAL_LOGIC(bool_or, |)
+PAL_LOGIC(bool_xor, ^)
+
+static inline void pal_bool_not(PalVm* vm, intptr_t _p) {
+    (void)_p;
+    PalData* dst = pal_spot(vm, 0);
+    dst->b = !dst->b;
+}
+
+/* ───────────────── output ───────────────── */
+
+static inline void pal_const_print(PalVm* vm, const char* s) {
+    (void)vm;
+    fputs(s, stdout);
+}
+
+#endif
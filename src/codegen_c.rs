@@ -0,0 +1,520 @@
+//ahead of time compilation of a dictionary into a single C translation unit
+//every outlined word becomes one C function calling the runtime in codegen_c.h
+//static data is dumped as is so push_var addresses keep working
+//data holding addresses of its own (strings, xts, pointers, boxes) would not so words pushing it are refused
+
+use crate::PalData;
+use crate::buildins::buildin_name;
+use crate::ir::Exe;
+use crate::lex::Lex;
+use crate::vm::Code;
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::Write;
+use core::sync::atomic::Ordering;
+
+///the runtime every generated file starts with
+pub const C_HEADER: &str = include_str!("codegen_c.h");
+
+//the C data array is aligned to this so we keep the same phase as the rust arena
+const DATA_ALIGN: usize = 16;
+
+pub enum CodegenError<'lex> {
+    UnknownBuildin(&'lex str),
+    Unsupported {
+        word: &'lex str,
+        buildin: &'static str,
+    },
+    ForeignPointer {
+        word: &'lex str,
+        buildin: &'static str,
+    },
+    ///static data holding an address of this process, dumping it would leave a dangling pointer
+    HostAddress {
+        word: &'lex str,
+        tp: &'lex str,
+    },
+    UnknownCall(&'lex str),
+    BadJump(&'lex str),
+    Fmt,
+}
+
+impl fmt::Display for CodegenError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodegenError::UnknownBuildin(word) => {
+                write!(f, "word {word} calls a buildin C does not know about")
+            }
+            CodegenError::Unsupported { word, buildin } => {
+                write!(f, "word {word} uses {buildin} which has no C equivalent")
+            }
+            CodegenError::ForeignPointer { word, buildin } => write!(
+                f,
+                "word {word} passes a pointer outside of data memory to {buildin}"
+            ),
+            CodegenError::HostAddress { word, tp } => {
+                write!(f, "word {word} pushes a static {tp} which holds an address")
+            }
+            CodegenError::UnknownCall(word) => {
+                write!(f, "word {word} calls code which is not an outlined word")
+            }
+            CodegenError::BadJump(word) => write!(f, "word {word} jumps outside of itself"),
+            CodegenError::Fmt => write!(f, "failed writing the C output"),
+        }
+    }
+}
+
+impl fmt::Debug for CodegenError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "CodegenError(\"{self}\")")
+    }
+}
+
+impl From<fmt::Error> for CodegenError<'_> {
+    fn from(_: fmt::Error) -> Self {
+        CodegenError::Fmt
+    }
+}
+
+///writes name as a valid C identifier (words can have any non whitespace name)
+pub fn mangle<W: Write>(out: &mut W, name: &str) -> fmt::Result {
+    for b in name.bytes() {
+        match b {
+            b'_' => out.write_str("__")?,
+            b if b.is_ascii_alphanumeric() => out.write_char(b as char)?,
+            b => write!(out, "_x{b:02x}")?,
+        }
+    }
+    Ok(())
+}
+
+struct CWord<'lex> {
+    name: &'lex str,
+    ///how far down the overloads of its name the word is, 0 for the newest
+    overload: usize,
+    body: &'lex [Code],
+}
+
+///writes the C function name of a word, older overloads get an _o suffix (which mangle never makes)
+fn write_c_name<W: Write>(out: &mut W, w: &CWord<'_>) -> fmt::Result {
+    out.write_str("pal_word_")?;
+    mangle(out, w.name)?;
+    if w.overload > 0 {
+        write!(out, "_o{}", w.overload)?;
+    }
+    Ok(())
+}
+
+///writes the body of a C byte array initializer and closes it
+fn write_bytes<'a, W: Write>(out: &mut W, bytes: impl Iterator<Item = &'a u8>) -> fmt::Result {
+    for (i, b) in bytes.enumerate() {
+        if i % 16 == 0 {
+            out.write_str("\n   ")?;
+        }
+        write!(out, " {b},")?;
+    }
+    writeln!(out, "\n}};")
+}
+
+///emits a C file with one `void pal_word_<name>(PalVm*)` per outlined word in the lex
+///static data is available through the `PAL_DATA(offset)` macro (and constants through `PAL_CONST(offset)`)
+pub fn emit_c<'lex, W: Write>(lex: &Lex<'lex>, out: &mut W) -> Result<(), CodegenError<'lex>> {
+    let mut words: Vec<CWord<'lex>> = lex
+        .words
+        .values()
+        .flat_map(|w| w.overloads().enumerate())
+        .filter_map(|(overload, w)| match w.runtime.exe() {
+            Exe::Outlined(body) => Some(CWord {
+                name: w.name,
+                overload,
+                body,
+            }),
+            Exe::Inlined(_) => None,
+        })
+        .collect();
+    words.sort_by(|a, b| (a.name, a.overload).cmp(&(b.name, b.overload)));
+
+    out.write_str(C_HEADER)?;
+
+    /* ── static data ── */
+    let data = unsafe { lex.data_mem.bytes() };
+    let phase = data.as_ptr() as usize % DATA_ALIGN;
+    write!(
+        out,
+        "\nstatic _Alignas({DATA_ALIGN}) unsigned char pal_data_raw[{}] = {{",
+        phase + data.len() + 1
+    )?;
+    write_bytes(out, core::iter::repeat_n(&0u8, phase).chain(data))?;
+    writeln!(
+        out,
+        "#define PAL_DATA(off) ((void*)(pal_data_raw + {phase} + (off)))\n"
+    )?;
+
+    //constants stay read only in c as well, their pages are page aligned so there is no phase
+    #[cfg(all(unix, feature = "protected_consts"))]
+    {
+        let consts = lex.const_mem.bytes();
+        write!(
+            out,
+            "static const _Alignas({DATA_ALIGN}) unsigned char pal_const_raw[{}] = {{",
+            consts.len() + 1
+        )?;
+        write_bytes(out, consts.iter())?;
+        writeln!(out, "#define PAL_CONST(off) ((void*)(pal_const_raw + (off)))\n")?;
+    }
+
+    /* ── declarations ── */
+    for w in words.iter() {
+        out.write_str("void ")?;
+        write_c_name(out, w)?;
+        out.write_str("(PalVm* vm);\n")?;
+    }
+
+    /* ── definitions ── */
+    for w in words.iter() {
+        emit_word(lex, &words, w, out)?;
+    }
+    Ok(())
+}
+
+fn find_word<'a, 'lex>(words: &'a [CWord<'lex>], target: *const Code) -> Option<&'a CWord<'lex>> {
+    words
+        .iter()
+        .find(|w| core::ptr::eq(w.body.as_ptr(), target))
+}
+
+fn emit_word<'lex, W: Write>(
+    lex: &Lex<'lex>,
+    words: &[CWord<'lex>],
+    w: &CWord<'lex>,
+    out: &mut W,
+) -> Result<(), CodegenError<'lex>> {
+    let len = w.body.len();
+    let start = w.body.as_ptr();
+
+    //code pointers are allways 1 before the next instruction
+    let jump_target = |i: usize, off: isize| -> Result<usize, CodegenError<'lex>> {
+        let t = i as isize + off + 1;
+        if t < 0 || t as usize > len {
+            return Err(CodegenError::BadJump(w.name));
+        }
+        Ok(t as usize)
+    };
+    let abs_target = |p: *const Code| -> Result<usize, CodegenError<'lex>> {
+        let off = (p as isize - start as isize) / size_of::<Code>() as isize;
+        jump_target(0, off - 1)
+    };
+
+    //first pass finds which labels are actually used
+    let mut labels: Vec<bool> = core::iter::repeat_n(false, len + 1).collect();
+    for (i, c) in w.body.iter().enumerate() {
+        let param = c.param.load(Ordering::Relaxed);
+        match buildin_name(c) {
+            Some("branch") | Some("jump") => labels[jump_target(i, param as isize)?] = true,
+            Some("_if") => labels[abs_target(param)?] = true,
+            Some("variant_skip") => labels[jump_target(i, 1)?] = true,
+            Some("tail_call") if core::ptr::eq(param.wrapping_add(1), start) => labels[0] = true,
+            _ => {}
+        }
+    }
+
+    write!(out, "\n/* {} */\nvoid ", w.name)?;
+    write_c_name(out, w)?;
+    out.write_str("(PalVm* vm) {\n")?;
+
+    for (i, c) in w.body.iter().enumerate() {
+        if labels[i] {
+            writeln!(out, "L{i}:;")?;
+        }
+        let param = c.param.load(Ordering::Relaxed);
+
+        if c.f.load(Ordering::Relaxed).is_none() {
+            let callee = find_word(words, param).ok_or(CodegenError::UnknownCall(w.name))?;
+            out.write_str("    ")?;
+            write_c_name(out, callee)?;
+            out.write_str("(vm);\n")?;
+            continue;
+        }
+
+        #[cfg(feature = "std")]
+        if c.is(crate::buildins::op::const_print) {
+            let off = lex
+                .data_mem
+                .offset_of(param)
+                .ok_or(CodegenError::ForeignPointer {
+                    word: w.name,
+                    buildin: "const_print",
+                })?;
+            writeln!(
+                out,
+                "    pal_const_print(vm, (const char*)PAL_DATA({off}));"
+            )?;
+            continue;
+        }
+
+        let name = buildin_name(c).ok_or(CodegenError::UnknownBuildin(w.name))?;
+        match name {
+            "ret" => writeln!(out, "    return;")?,
+            "no_op" => {}
+            "tail_call" => {
+                let target = param.wrapping_add(1) as *const Code;
+                if core::ptr::eq(target, start) {
+                    writeln!(out, "    goto L0;")?;
+                } else {
+                    let callee =
+                        find_word(words, target).ok_or(CodegenError::UnknownCall(w.name))?;
+                    out.write_str("    ")?;
+                    write_c_name(out, callee)?;
+                    out.write_str("(vm);\n    return;\n")?;
+                }
+            }
+            "branch" => writeln!(
+                out,
+                "    if (pal_pop_bool(vm)) goto L{};",
+                jump_target(i, param as isize)?
+            )?,
+            "_if" => writeln!(
+                out,
+                "    if (pal_pop_bool(vm)) goto L{};",
+                abs_target(param)?
+            )?,
+            "jump" => writeln!(out, "    goto L{};", jump_target(i, param as isize)?)?,
+            "variant_skip" => writeln!(
+                out,
+                "    if (pal_spot(vm, 0)->i == {}) goto L{};",
+                param as isize,
+                jump_target(i, 1)?
+            )?,
+            "push_var" => {
+                if let Some(tp) = lex.address_data.get(&(param as *const PalData)) {
+                    return Err(CodegenError::HostAddress {
+                        word: w.name,
+                        tp: tp.name,
+                    });
+                }
+                #[cfg(all(unix, feature = "protected_consts"))]
+                if let Some(off) = lex.const_mem.offset_of(param) {
+                    writeln!(out, "    pal_push(vm, (PalData*)PAL_CONST({off}));")?;
+                    continue;
+                }
+                let off = lex
+                    .data_mem
+                    .offset_of(param)
+                    .ok_or(CodegenError::ForeignPointer {
+                        word: w.name,
+                        buildin: name,
+                    })?;
+                writeln!(out, "    pal_push(vm, (PalData*)PAL_DATA({off}));")?;
+            }
+            //heap use is counted on the rust vm which the generated C has no part of
+            "call_dyn" | "call_dyn_threaded" | "maybe_backpatch" | "log_bytes" | "heap_alloc"
+            | "heap_free" | "box_open" => {
+                return Err(CodegenError::Unsupported {
+                    word: w.name,
+                    buildin: name,
+                });
+            }
+            //strings carry pointers into the lex which the generated C does not share
+            _ if name.starts_with("str_") => {
+                return Err(CodegenError::Unsupported {
+                    word: w.name,
+                    buildin: name,
+                });
+            }
+            _ => writeln!(out, "    pal_{name}(vm, {});", param as isize)?,
+        }
+    }
+
+    if labels[len] {
+        writeln!(out, "L{len}:;")?;
+    }
+    writeln!(out, "}}")?;
+    Ok(())
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::PalData;
+    use crate::buildins::*;
+    use crate::ir::CompEasyMemory;
+    use crate::lex::LexEasyMemory;
+    use crate::vm::VmEasyMemory;
+    use alloc::string::String;
+    use core::mem::ManuallyDrop;
+    use std::process::Command;
+
+    #[test]
+    fn mangle_is_c_safe() {
+        let mut s = String::new();
+        mangle(&mut s, "int_add+1").unwrap();
+        assert_eq!(s, "int__add_x2b1");
+    }
+
+    #[test]
+    fn static_addresses_are_refused() {
+        use crate::prelude::load_prelude;
+
+        let mut lex_mem = LexEasyMemory::new();
+        let mut lex = ManuallyDrop::new(lex_mem.make_lex());
+        load_prelude(&mut lex);
+        let int = lex.named_type("int").unwrap();
+        let str = lex.named_type("str").unwrap();
+        let text = lex.data_mem.save_slice(b"hi").unwrap().as_ptr();
+
+        let mut comp_mem = CompEasyMemory::<16>::new();
+        let mut comp = comp_mem.make_comp(&mut lex);
+        comp.add_literal(int, PalData { int: 7 });
+        //the pushed boxes are left to the caller
+        comp.stack.stack.free(1);
+        comp.lex.code_mem.save(Code::basic(op::ret, 0)).unwrap();
+        comp.finalize_and_store_word("seven", &[], &[]).unwrap();
+        drop(comp);
+        let mut src = String::new();
+        emit_c(&lex, &mut src).unwrap();
+
+        //the literal is a (ptr,len) pair pointing at text in this process
+        let mut comp_mem = CompEasyMemory::<16>::new();
+        let mut comp = comp_mem.make_comp(&mut lex);
+        comp.add_const(str, &[PalData { ptr: text as *mut PalData }, PalData { int: 2 }]);
+        comp.stack.stack.free(1);
+        comp.lex.code_mem.save(Code::basic(op::ret, 0)).unwrap();
+        comp.finalize_and_store_word("greet", &[], &[]).unwrap();
+        drop(comp);
+        match emit_c(&lex, &mut String::new()) {
+            Err(CodegenError::HostAddress { word, tp }) => assert_eq!((word, tp), ("greet", "str")),
+            other => panic!("expected a host address error got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn emitted_c_matches_interpreter() {
+        let mut lex_mem = LexEasyMemory::new();
+        let mut lex = ManuallyDrop::new(lex_mem.make_lex());
+
+        let mut var =
+            |v: PalData| -> *mut PalData { lex.data_mem.alloc::<PalData>().unwrap().write(v) };
+        let one = var(PalData { int: 1 });
+        let zero = var(PalData { int: 0 });
+        let flag = var(PalData { bool: false });
+        let acc = var(PalData { int: 0 });
+        let n = var(PalData { int: 100 });
+        let a = var(PalData { int: 7 });
+        let b = var(PalData { int: 5 });
+        let c = var(PalData { int: 30 });
+
+        let mut comp_mem = CompEasyMemory::<16>::new();
+        let mut comp = comp_mem.make_comp(&mut lex);
+
+        macro_rules! define {
+            ($name:expr, |$start:ident| $codes:expr) => {{
+                let $start = comp.code_start();
+                for c in $codes {
+                    comp.lex.code_mem.save(c).unwrap();
+                }
+                comp.finalize_and_store_word($name, &[], &[]).unwrap();
+                comp.lex.words[$name].runtime.exe().inner_slice()
+            }};
+        }
+
+        let square = define!("square", |_s| [
+            Code::basic(op::pick, 0),
+            Code::basic(op::int_mul, 0),
+            Code::basic(op::ret, 0),
+        ]);
+        let dec = define!("dec-1", |_s| [
+            Code::basic(op::push_var, one as isize),
+            Code::basic(op::int_sub, 0),
+            Code::basic(op::ret, 0),
+        ]);
+        // ( acc n -- acc n ) adds n..1 into acc
+        let sum_down = define!("sum_down", |start| [
+            Code::basic(op::push_var, flag as isize),
+            Code::basic(op::pick, 1),
+            Code::basic(op::push_var, zero as isize),
+            Code::basic(op::int_le, 0),
+            Code::basic(op::branch, 7),
+            Code::basic(op::pick, 1),
+            Code::basic(op::pick, 1),
+            Code::basic(op::int_add, 0),
+            Code::basic(op::param_drop, 1),
+            Code::word(dec),
+            Code::word_raw(start),
+            Code::basic(op::ret, 0),
+            Code::basic(op::ret, 0),
+        ]);
+        let main = define!("main", |_s| [
+            Code::basic(op::push_var, acc as isize),
+            Code::basic(op::push_var, n as isize),
+            Code::word(sum_down),
+            Code::basic(op::param_drop, 2),
+            Code::basic(op::push_var, a as isize),
+            Code::word(square),
+            Code::basic(op::param_drop, 1),
+            //copy b into a local then add it to c
+            Code::basic(op::frame_alloc, 1),
+            Code::basic(op::push_local, 0),
+            Code::basic(op::push_var, b as isize),
+            Code::basic(op::inject, size_of::<PalData>() as isize),
+            Code::basic(op::push_var, c as isize),
+            Code::basic(op::pick, 1),
+            Code::basic(op::int_add, 0),
+            Code::basic(op::param_drop, 2),
+            Code::basic(op::frame_free, 1),
+            Code::basic(op::ret, 0),
+        ]);
+        drop(comp);
+
+        let mut src = String::new();
+        emit_c(&lex, &mut src).unwrap();
+        assert!(
+            src.contains("goto L0;"),
+            "self recursion should become a loop"
+        );
+
+        let offsets = [acc, n, a, c].map(|p| lex.data_mem.offset_of(p).unwrap());
+        src.push_str("\n#include <inttypes.h>\nint main(void) {\n    static PalVm vm;\n    pal_vm_init(&vm);\n    pal_word_main(&vm);\n");
+        for off in offsets {
+            src.push_str(&std::format!(
+                "    printf(\"%\" PRId64 \"\\n\", ((PalData*)PAL_DATA({off}))->i);\n"
+            ));
+        }
+        src.push_str("    return 0;\n}\n");
+
+        let dir = std::env::temp_dir().join(std::format!("pal_codegen_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let c_file = dir.join("out.c");
+        let exe = dir.join("out");
+        std::fs::write(&c_file, &src).unwrap();
+
+        let compiled = match Command::new("cc")
+            .args(["-std=c11", "-O2", "-o"])
+            .arg(&exe)
+            .arg(&c_file)
+            .status()
+        {
+            Ok(status) => status,
+            Err(_) => {
+                std::println!("no C compiler available skipping");
+                return;
+            }
+        };
+        assert!(compiled.success(), "generated C failed to compile:\n{src}");
+        let output = Command::new(&exe).output().unwrap();
+        assert!(output.status.success());
+        let c_out = String::from_utf8(output.stdout).unwrap();
+
+        let mut mem = VmEasyMemory::<64>::new();
+        let mut vm = mem.make_vm();
+        unsafe {
+            vm.execute_code(&Code::word(main));
+        }
+        let rust_out: String = [acc, n, a, c]
+            .map(|p| unsafe { std::format!("{}\n", (*p).int) })
+            .concat();
+
+        assert_eq!(c_out, rust_out);
+        assert_eq!(rust_out, "5050\n0\n49\n35\n");
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
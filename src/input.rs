@@ -5,6 +5,10 @@ use crate::io::{Error, ErrorKind, Read};
 pub trait InputStream {
     fn peek(&mut self) -> Result<Option<&str>, Error>;
     fn next_word(&mut self) -> Result<Option<&str>, Error>;
+    ///the line the last word read is on counting from 1, 0 when the stream does not know
+    fn line(&self) -> u32 {
+        0
+    }
 }
 
 /// lexes tokens from a ['Read'] stream
@@ -20,6 +24,7 @@ pub struct WordStream<R: Read, const N: usize = 4096> {
     start: usize,
     valid_len: usize,
     len: usize,
+    line: u32,
 }
 
 impl<R: Read, const N: usize> WordStream<R, N> {
@@ -31,6 +36,7 @@ impl<R: Read, const N: usize> WordStream<R, N> {
             start: 0,
             valid_len: 0,
             len: 0,
+            line: 1,
         }
     }
 
@@ -73,6 +79,7 @@ impl<R: Read, const N: usize> WordStream<R, N> {
         }
 
         if self.len == N {
+            #[allow(clippy::io_other_error)] //no_std_io has no Error::other
             return Err(Error::new(ErrorKind::Other, "Buffer Overflow on word"));
         }
         if self.start + self.len == N {
@@ -112,6 +119,9 @@ impl<R: Read, const N: usize> WordStream<R, N> {
         //skip whitespaces
         for c in s.chars() {
             if c.is_whitespace() {
+                if c == '\n' {
+                    self.line += 1;
+                }
                 self.len -= c.len_utf8();
                 self.valid_len -= c.len_utf8();
                 self.start += c.len_utf8();
@@ -154,6 +164,8 @@ impl<R: Read, const N: usize> WordStream<R, N> {
         unsafe { Ok(Some(str::from_utf8_unchecked(&spot[..total_len]))) }
     }
 
+    /// # Safety
+    /// `total_len` must not exceed the validated length returned by [`WordStream::scan`]
     pub unsafe fn consume_bytes(&mut self, total_len: usize) {
         self.len -= total_len;
         self.valid_len -= total_len;
@@ -174,7 +186,7 @@ impl<R: Read, const N: usize> InputStream for WordStream<R, N> {
             //only now do we try filling and then check again
             None => {
                 if self.fill()? == 0 {
-                    return Ok(None);
+                    Ok(None)
                 } else {
                     self.peek() //TCO 
                 }
@@ -185,7 +197,7 @@ impl<R: Read, const N: usize> InputStream for WordStream<R, N> {
         match self.scan()?.map(|s| s as *const str) {
             Some(s) => unsafe {
                 //we need to be careful not to make a ref that lives between calls
-                let len = (&*s).as_bytes().len();
+                let len = (&*s).len();
                 let addr = s.addr();
 
                 //borrows mut
@@ -206,6 +218,9 @@ impl<R: Read, const N: usize> InputStream for WordStream<R, N> {
             }
         }
     }
+    fn line(&self) -> u32 {
+        self.line
+    }
 }
 
 /*──────────────────────────── tests ────────────────────────────────*/
@@ -223,8 +238,8 @@ mod tests {
         let mut parts = src.split_whitespace();
         let first = parts.next().unwrap();
         let second = parts.next().unwrap();
-        assert_eq!(first.as_bytes().len(), 6);
-        assert_eq!(second.as_bytes().len(), 6);
+        assert_eq!(first.len(), 6);
+        assert_eq!(second.len(), 6);
 
         //choose a buffer which would require a memove
         let mut rdr = WordStream::<_, 9>::new(Cursor::new(bytes));
@@ -235,6 +250,18 @@ mod tests {
         assert_eq!(rdr.next_word().unwrap(), None);
     }
 
+    #[test]
+    fn lines_are_counted() {
+        let mut rdr = WordStream::<_, 8>::new(Cursor::new(&b"a b\n\nc\n d "[..]));
+        assert_eq!(rdr.next_word().unwrap(), Some("a"));
+        assert_eq!(rdr.line(), 1);
+        rdr.next_word().unwrap();
+        assert_eq!(rdr.next_word().unwrap(), Some("c"));
+        assert_eq!(rdr.line(), 3);
+        assert_eq!(rdr.next_word().unwrap(), Some("d"));
+        assert_eq!(rdr.line(), 4);
+    }
+
     #[test]
     fn eof_with_incomplete_seq_errors() {
         let bad = b"\xE2\x82"; // first two bytes of '€'
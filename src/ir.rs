@@ -1,4 +1,30 @@
+use crate::PalData;
+use crate::PalError;
+use crate::PalFloat;
+use crate::PalInt;
+use crate::buildins::SIZED_INTS;
+use crate::buildins::op;
+use crate::stack::make_storage;
+use crate::types::VariantCase;
+use crate::types::RwT;
+use crate::types::TypeInner;
+use crate::vm::Buildin;
+use crate::lex::StackWriter;
+use crate::stack::StackVec;
+use core::mem::MaybeUninit;
+use crate::types::CompVar;
+use core::cell::RefCell;
+use crate::types::BorrowSite;
+use crate::types::ConstBox;
+use crate::types::sig_fits;
+use crate::types::READ_FLAG;
 use crate::types::SigStackEasyMemory;
+use crate::types::TypeP;
+use crate::types::UNIQUE_FLAG;
+use crate::types::WRITE_FLAG;
+use crate::vm::VmEasyMemory;
+use core::fmt::Write;
+use core::sync::atomic::Ordering;
 use crate::lex::StackAllocator;
 use crate::input::InputStream;
 use crate::Code;
@@ -15,6 +41,120 @@ pub struct CompContext<'me, 'lex> {
     pub stack: SigStack<'me, 'lex>,
     immidate_stack: SigStack<'me, 'lex>,
     pub input: Option<&'me mut dyn InputStream>,
+    ///name of the word currently being defined
+    pub def_name: Option<&'lex str>,
+    ///errors raised by compile time buildins, these have no other way to report
+    pub error: Option<PalError<'lex>>,
+    ///the [inputs, outputs] the current definition said it has
+    pub declared: Option<[&'lex [SigItem<'lex>]; 2]>,
+    ///the open matches, innermost on top
+    matches: StackVec<'me, MatchFrame<'me, 'lex>>,
+    ///how many unsafe{ regions are open, words on raw pointers are only accepted inside one
+    pub unsafe_depth: u32,
+    ///the depth when the current definition started, regions opened in it have to close in it
+    unsafe_at_begin: u32,
+    ///the current definition is a [: ;] quotation rather than a named word
+    quoting: bool,
+}
+
+///an open match, see [`CompContext::begin_match`]
+#[derive(Clone, Copy)]
+pub struct MatchFrame<'me, 'lex> {
+    ///None untill the first case names the type of a caller provided variant
+    variant: Option<&'me RefCell<CompVar<'me, 'lex>>>,
+    cases: &'lex [VariantCase<'lex>],
+    ///the box under the variant
+    below: Option<&'me RefCell<CompVar<'me, 'lex>>>,
+    ///the stack length under the variant
+    depth: usize,
+    ///how many caller boxes were inferred so far
+    inferred: usize,
+    ///a bit per case that got an arm
+    covered: u64,
+    ///the case of the open arm
+    arm: Option<usize>,
+    ///index of the jump taken when the case of the open arm does not match
+    next: usize,
+    ///index of the last jump to the end, each holds the index of the one before it (-1 ends the chain)
+    ends: isize,
+}
+
+impl<'me, 'lex> MatchFrame<'me, 'lex> {
+    ///a match on a variant the first case will infer
+    fn pending() -> Self {
+        Self {
+            variant: None,
+            cases: &[],
+            below: None,
+            depth: 0,
+            inferred: 0,
+            covered: 0,
+            arm: None,
+            next: 0,
+            ends: -1,
+        }
+    }
+}
+
+///the most boxes a pure word can touch and still be folded
+const MAX_FOLD: usize = 8;
+
+///the most fields a cluster (or cases a variant or items a part of a signature) read from text can have
+const MAX_FIELDS: usize = 32;
+
+///how deep matches can nest
+pub const MAX_MATCH_DEPTH: usize = 16;
+
+///parses the literal syntax, None means s is not a literal
+pub fn parse_literal<'lex>(lex: &Lex<'lex>, s: &str) -> Option<(TypeP<'lex>, PalData)> {
+    match s {
+        "true" => Some((lex.basic_type("bool")?, PalData { bool: true })),
+        "false" => Some((lex.basic_type("bool")?, PalData { bool: false })),
+        _ => match s.parse() {
+            Ok(int) => Some((lex.basic_type("int")?, PalData { int })),
+            Err(_) => match parse_sized(lex, s) {
+                Some(lit) => Some(lit),
+                None => Some((lex.basic_type("float")?, PalData { float: parse_float(s)? })),
+            },
+        },
+    }
+}
+
+///ints with a sized suffix such as 200u8 or -5i16, out of range values are not literals
+fn parse_sized<'lex>(lex: &Lex<'lex>, s: &str) -> Option<(TypeP<'lex>, PalData)> {
+    let (name, size, _, _) = SIZED_INTS.iter().find(|(name, ..)| s.ends_with(name))?;
+    let v: i128 = s[..s.len() - name.len()].parse().ok()?;
+    let bits = 8 * *size as u32;
+    let fits = if name.starts_with('u') {
+        v >= 0 && v >> bits == 0
+    } else {
+        v >= -(1 << (bits - 1)) && v < 1 << (bits - 1)
+    };
+    if !fits {
+        return None;
+    }
+
+    //the value sits at the start of the box like the sized buildins expect
+    let mut data = PalData { int: 0 };
+    let p = &mut data as *mut PalData;
+    unsafe {
+        match size {
+            1 => *(p as *mut u8) = v as u8,
+            2 => *(p as *mut u16) = v as u16,
+            4 => *(p as *mut u32) = v as u32,
+            _ => *(p as *mut u64) = v as u64,
+        }
+    }
+    Some((lex.named_type(name)?, data))
+}
+
+///floats have to start like a number so words such as inf or nan stay words
+fn parse_float(s: &str) -> Option<PalFloat> {
+    let digits = s.trim_start_matches(['+', '-']);
+    if !digits.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+    s.parse().ok()
 }
 
 impl<'me, 'lex> CompContext<'me, 'lex> {
@@ -23,6 +163,7 @@ impl<'me, 'lex> CompContext<'me, 'lex> {
         stack: SigStack<'me, 'lex>,
         immidate_stack: SigStack<'me, 'lex>,
         input: Option<&'me mut dyn InputStream>,
+        matches: StackVec<'me, MatchFrame<'me, 'lex>>,
     ) -> Self {
         Self {
             start: lex.code_mem.check_point(),
@@ -30,51 +171,1039 @@ impl<'me, 'lex> CompContext<'me, 'lex> {
             stack,
             immidate_stack,
             input,
+            def_name: None,
+            error: None,
+            declared: None,
+            matches,
+            unsafe_depth: 0,
+            unsafe_at_begin: 0,
+            quoting: false,
         }
     }
 
     pub fn add_runtime_code(&mut self, runtime: &RuntimeCode<'lex>) -> Result<(), SigError<'lex>> {
+        //the epilogue is the only way out that frees the frame
+        if self.stack.local_cells() > 0 && runtime.exits_early() {
+            return Err(SigError::ExitWithLocals);
+        }
         runtime.check_sig(&mut self.stack)?;
         runtime.save_to_alloc(&mut self.lex.code_mem);
         Ok(())
     }
 
+    ///compiles a call to word folding it away if possible
+    pub fn add_word(&mut self, word: &Word<'lex>) -> Result<(), SigError<'lex>> {
+        if word.pure && self.try_fold(word)? {
+            return Ok(());
+        }
+        self.add_runtime_code(&word.runtime)
+    }
+
+    ///compiles a push of a read only constant
+    pub fn add_literal(&mut self, tp: TypeP<'lex>, value: PalData) {
+        self.add_const(tp, &[value])
+    }
+
+    ///compiles a push of a read only constant spanning several cells
+    pub fn add_const(&mut self, tp: TypeP<'lex>, cells: &[PalData]) {
+        let data = self
+            .lex
+            .data_mem
+            .save_slice(cells)
+            .expect("out of data mem")
+            .as_ptr();
+        self.lex.note_address_data(data, tp);
+        let code_at = self.lex.code_mem.check_point();
+        self.lex
+            .code_mem
+            .save(Code::basic(op::push_var, data as isize))
+            .expect("out of code mem");
+        self.stack
+            .push_box(tp, READ_FLAG, Some(ConstBox { data, code_at }));
+    }
+
+    ///makes a fresh box for a literal used while running
+    ///nothing else can see it so it is fully owned by the caller
+    pub fn run_literal(&mut self, tp: TypeP<'lex>, value: PalData) -> *mut PalData {
+        self.run_const(tp, &[value])
+    }
+
+    ///like [`CompContext::run_literal`] for values spanning several cells
+    pub fn run_const(&mut self, tp: TypeP<'lex>, cells: &[PalData]) -> *mut PalData {
+        self.immidate_stack
+            .push_box(tp, READ_FLAG | WRITE_FLAG | UNIQUE_FLAG, None);
+        let data = self
+            .lex
+            .data_mem
+            .save_slice(cells)
+            .expect("out of data mem")
+            .as_mut_ptr();
+        self.lex.note_address_data(data, tp);
+        data
+    }
+
+    ///throws away bookkeeping of finished expressions ran in run mode
+    pub fn reclaim_immidate(&mut self) {
+        self.immidate_stack.reclaim();
+    }
+
+    ///evaluates a pure word at compile time when every box it touches is a constant
+    ///whose push is at the very end of the code (so it can be removed)
+    ///nothing else can see those literals so they are the compiler's temporaries and the call may write them
+    ///the call is otherwise checked like any other and its outputs are pushed as new literals
+    fn try_fold(&mut self, word: &Word<'lex>) -> Result<bool, SigError<'lex>> {
+        let runtime = &word.runtime;
+        let outs = runtime.output_sig.len();
+        let n = outs + runtime.input_sig.len();
+        if n == 0 || n > MAX_FOLD {
+            return Ok(false);
+        }
+        let Some(boxes) = self.stack.stack.peek_many(n) else {
+            return Ok(false);
+        };
+
+        let mut vals = [PalData { int: 0 }; MAX_FOLD];
+        let mut perms = [0; MAX_FOLD];
+        let mut code_end = self.lex.code_mem.check_point();
+        let sigs = runtime.input_sig.iter().rev().chain(runtime.output_sig.iter().rev());
+        for (k, (b, sig)) in boxes.iter().zip(sigs).enumerate() {
+            let b = b.borrow();
+            let Some(c) = b.constant else {
+                return Ok(false);
+            };
+            //the pushes must be the last n pieces of code in stack order
+            if c.code_at.0 + 1 != code_end.0 || !b.tp.fits(sig.tp) || b.tp.cells != 1 {
+                return Ok(false);
+            }
+            code_end = c.code_at;
+            vals[n - 1 - k] = unsafe { *c.data };
+            perms[k] = b.permissions;
+        }
+        if word.traps.is_some_and(|traps| traps(&vals[..n])) {
+            return Err(SigError::ConstTrap(word.name));
+        }
+
+        for b in boxes {
+            b.borrow_mut().permissions = READ_FLAG | WRITE_FLAG | UNIQUE_FLAG;
+        }
+        //this consumes the inputs and leaves the outputs just like the call would
+        if let Err(e) = runtime.check_sig(&mut self.stack) {
+            //a failed check leaves the stack as it was
+            let boxes = self.stack.stack.peek_many(n).expect("stack is untouched");
+            for (b, p) in boxes.iter().zip(perms) {
+                b.borrow_mut().permissions = p;
+            }
+            return Err(e);
+        }
+
+        let mut mem = VmEasyMemory::<MAX_FOLD>::new();
+        let mut vm = mem.make_vm();
+        for v in vals[..n].iter_mut() {
+            vm.param_stack.push(v).expect("MAX_FOLD fits");
+        }
+        unsafe { runtime.run(&mut vm) };
+
+        unsafe { self.lex.code_mem.goto_checkpoint(code_end) };
+        self.stack.stack.free(outs);
+        for (v, sig) in vals[..outs].iter().zip(runtime.output_sig) {
+            self.add_literal(sig.tp, *v);
+        }
+        Ok(true)
+    }
+
+    fn keep_str(&mut self, s: &str) -> &'lex str {
+        let mut writer = StackWriter::new(&mut self.lex.comp_data_mem);
+        writer.write_str(s).expect("Out of memory in comp data");
+        writer.finish()
+    }
+
+    ///reads the next word from the input into the lex, used by defining words
+    pub fn read_name(&mut self) -> Result<&'lex str, PalError<'lex>> {
+        let name = self
+            .input
+            .as_mut()
+            .expect("need input to read a name")
+            .next_word()?
+            .ok_or(PalError::MissingName)?;
+        let name = name as *const str;
+        //# Safety
+        //the input is not touched untill we are done copying
+        Ok(self.keep_str(unsafe { &*name }))
+    }
+
+    ///reads the next word and looks it up as a type
+    pub fn read_type(&mut self) -> Result<TypeP<'lex>, PalError<'lex>> {
+        let name = self.read_name()?;
+        self.lex.parse_type(name).ok_or(PalError::UnknownType(name))
+    }
+
+    ///looks up the word a name refers to for the boxes on top of the stack
+    ///an overloaded name goes to the one overload whose inputs fit the stack
+    ///names that are not words may still be generic ones, None means neither
+    ///words on raw pointers are an error outside of unsafe{ }
+    ///borrows taken while checking the word are recorded as taken by it
+    pub fn find_word(&mut self, name: &str, run: bool) -> Result<Option<Word<'lex>>, PalError<'lex>> {
+        let word = self.resolve_word(name, run)?;
+        let Some(w) = &word else {
+            return Ok(None);
+        };
+        if self.unsafe_depth == 0 && w.runtime.is_raw() {
+            return Err(SigError::Unsafe(w.name).into());
+        }
+        let site = Some(BorrowSite {
+            word: w.name,
+            line: self.input.as_ref().map_or(0, |input| input.line()),
+        });
+        let stack = if run { &mut self.immidate_stack } else { &mut self.stack };
+        stack.site = site;
+        Ok(word)
+    }
+
+    ///reads a name and makes an xt of the word it refers to
+    ///overloaded names and bare generics are refused since nothing on the stack picks one
+    pub fn read_xt(&mut self) -> Result<(TypeP<'lex>, PalData), PalError<'lex>> {
+        let name = self.read_name()?;
+        let word = match self.lex.words.get(name) {
+            Some(word) if word.overload.is_some() => {
+                let candidates = self
+                    .lex
+                    .comp_data_mem
+                    .save_iter(word.overloads().map(|w| w.runtime.input_sig))
+                    .expect("Out of memory in comp data");
+                return Err(SigError::Ambiguous { name, candidates }.into());
+            }
+            Some(word) => word.clone(),
+            None if self.lex.generics.contains_key(name) => {
+                return Err(PalError::UnboundGeneric(name));
+            }
+            None => self
+                .generic_word(name, false)?
+                .ok_or(PalError::Missingword(name))?,
+        };
+        if word.immidate.is_some() {
+            return Err(PalError::NoXt(name));
+        }
+        if self.unsafe_depth == 0 && word.runtime.is_raw() {
+            return Err(SigError::Unsafe(word.name).into());
+        }
+        Ok(self.lex.xt_of(&word))
+    }
+
+    ///the word calling the xt on top of the stack with the inputs under it
+    pub fn execute_word(&mut self, run: bool) -> Result<Word<'lex>, PalError<'lex>> {
+        let stack = if run { &self.immidate_stack } else { &self.stack };
+        let top = stack.stack.peek().map(|b| b.borrow().tp);
+        let word = top
+            .and_then(|tp| self.lex.execute_word(tp))
+            .ok_or(SigError::NotCallable(top))?;
+        if self.unsafe_depth == 0 && word.runtime.is_raw() {
+            return Err(SigError::Unsafe(word.name).into());
+        }
+        Ok(word)
+    }
+
+    ///checks a call to word against the immidate stack without running it
+    pub fn check_run(&mut self, word: &Word<'lex>) -> Result<(), SigError<'lex>> {
+        word.runtime.check_sig(&mut self.immidate_stack)
+    }
+
+    ///opens an unsafe{ region
+    pub fn begin_unsafe(&mut self) {
+        self.unsafe_depth += 1;
+    }
+
+    ///closes the innermost unsafe{ region
+    pub fn end_unsafe(&mut self) -> Result<(), PalError<'lex>> {
+        let floor = if self.def_name.is_some() { self.unsafe_at_begin } else { 0 };
+        if self.unsafe_depth == floor {
+            return Err(PalError::BadUnsafe("}"));
+        }
+        self.unsafe_depth -= 1;
+        Ok(())
+    }
+
+    fn resolve_word(&mut self, name: &str, run: bool) -> Result<Option<Word<'lex>>, PalError<'lex>> {
+        let Some(word) = self.lex.words.get(name).cloned() else {
+            return self.generic_word(name, run);
+        };
+        if word.overload.is_none() {
+            return Ok(Some(word));
+        }
+
+        let stack = if run { &self.immidate_stack } else { &self.stack };
+        //overloads hidden by a newer one with the same inputs are not candidates
+        let candidates = word
+            .overloads()
+            .enumerate()
+            .filter(|&(i, w)| !word.overloads().take(i).any(|n| n.same_inputs(w)))
+            .map(|(_, w)| w);
+        let mut fitting = candidates.clone().filter(|w| stack.tops_fit(w.runtime.input_sig));
+        match (fitting.next(), fitting.next()) {
+            (Some(w), None) => Ok(Some(w.clone())),
+            (None, _) => {
+                let deepest = candidates.clone().map(|w| w.runtime.input_sig.len()).max();
+                let n = stack.stack.len().min(deepest.unwrap_or(0));
+                let tops = stack.stack.peek_many(n).expect("len was checked");
+                let mem = &mut self.lex.comp_data_mem;
+                let found = mem.save_iter(tops.iter().rev().map(|b| b.borrow().tp));
+                let candidates = mem.save_iter(candidates.map(|w| w.runtime.input_sig));
+                Err(PalError::SigError(SigError::NoOverload {
+                    name: word.name,
+                    found: found.expect("Out of memory in comp data"),
+                    candidates: candidates.expect("Out of memory in comp data"),
+                }))
+            }
+            (Some(_), Some(_)) => {
+                let fitting = candidates.filter(|w| stack.tops_fit(w.runtime.input_sig));
+                let candidates = self
+                    .lex
+                    .comp_data_mem
+                    .save_iter(fitting.map(|w| w.runtime.input_sig))
+                    .expect("Out of memory in comp data");
+                Err(PalError::SigError(SigError::Ambiguous {
+                    name: word.name,
+                    candidates,
+                }))
+            }
+        }
+    }
+
+    ///the specialisation of a generic word, None when name is not one
+    ///name is either `name<T>` or just the name with T worked out from the stack
+    ///running words are checked against the immidate stack so T comes from there
+    pub fn generic_word(&mut self, name: &str, run: bool) -> Result<Option<Word<'lex>>, PalError<'lex>> {
+        let explicit = name
+            .strip_suffix('>')
+            .and_then(|s| s.split_once('<'));
+        let base = explicit.map_or(name, |(base, _)| base);
+        let Some(generic) = self.lex.generics.get(base).copied() else {
+            return Ok(None);
+        };
+        let tp = match explicit {
+            Some((_, tp)) => self
+                .lex
+                .parse_type(tp)
+                .ok_or_else(|| PalError::UnknownType(self.keep_str(tp)))?,
+            None => {
+                let stack = if run { &self.immidate_stack } else { &self.stack };
+                generic
+                    .bind(stack)
+                    .ok_or(PalError::UnboundGeneric(generic.name))?
+            }
+        };
+        //owned boxes only move, a copy of one would be freed twice
+        if generic.copies && tp.owns_memory() {
+            return Err(PalError::MoveOnly(tp.name));
+        }
+        Ok(Some(self.lex.specialise(&generic, tp)))
+    }
+
+    ///reads an optional stack effect `( ins -- outs )` right after a name
+    ///items are listed bottom first as `type` or `type:flags` with flags from r w u (default r)
+    ///outputs come after the -- even though the caller pushes them before the inputs
+    pub fn read_sig(&mut self) -> Result<Option<[&'lex [SigItem<'lex>]; 2]>, PalError<'lex>> {
+        let input = self.input.as_mut().expect("need input to read a signature");
+        if input.peek()? != Some("(") {
+            return Ok(None);
+        }
+        input.next_word()?;
+
+        let mut sig = [&[][..]; 2];
+        let mut part = 0;
+        //naming a type can make one so the items are gathered before they are saved
+        let mut mem = [MaybeUninit::uninit(); MAX_FIELDS];
+        let mut items = StackVec::from_slice(&mut mem);
+        loop {
+            let input = self.input.as_mut().expect("need input to read a signature");
+            let Some(word) = input.next_word()? else {
+                return Err(PalError::BadSig("("));
+            };
+            let word = word as *const str;
+            //# Safety
+            //the input is not touched while word is in use
+            let word = unsafe { &*word };
+
+            if word == "--" || word == ")" {
+                if word == "--" && part == 1 {
+                    return Err(PalError::BadSig(self.keep_str(word)));
+                }
+                sig[part] = self.lex.save_sig(items.peek_many(items.len()).expect("len was checked"));
+                items.free(items.len());
+                part += 1;
+                if word == ")" {
+                    break;
+                }
+                continue;
+            }
+
+            let (tp, flags) = word.split_once(':').unwrap_or((word, "r"));
+            let mut permissions = 0;
+            for c in flags.chars() {
+                permissions |= match c {
+                    'r' => READ_FLAG,
+                    'w' => WRITE_FLAG,
+                    'u' => UNIQUE_FLAG,
+                    _ => return Err(PalError::BadSig(self.keep_str(word))),
+                };
+            }
+            let Some(tp) = self.lex.parse_type(tp) else {
+                return Err(PalError::BadSig(self.keep_str(word)));
+            };
+            if items.push(SigItem { tp, permissions }).is_err() {
+                return Err(PalError::BadSig(self.keep_str(word)));
+            }
+        }
+        Ok(Some(sig))
+    }
+
+    ///reads the rest of a s" literal up to the word ending in a quote
+    ///the input only hands out words so they are joined by a single space
+    ///the text lives in data mem and the returned cells are the (ptr,len) of a str box
+    pub fn read_str(&mut self) -> Result<(TypeP<'lex>, [PalData; 2]), PalError<'lex>> {
+        let tp = self.lex.named_type("str").expect("strings need a str type");
+        let input = self.input.as_mut().expect("need input to read a string");
+        let mut writer = StackWriter::new(&mut self.lex.data_mem);
+        let mut first = true;
+        loop {
+            let word = match input.next_word() {
+                Ok(Some(word)) => word,
+                Ok(None) => {
+                    writer.discard();
+                    return Err(PalError::UnclosedStr);
+                }
+                Err(e) => {
+                    writer.discard();
+                    return Err(e.into());
+                }
+            };
+            if !first {
+                writer.write_char(' ').expect("out of data mem");
+            }
+            first = false;
+            let (body, done) = match word.strip_suffix('"') {
+                Some(body) => (body, true),
+                None => (word, false),
+            };
+            writer.write_str(body).expect("out of data mem");
+            if done {
+                break;
+            }
+        }
+        let s = writer.finish();
+        let cells = [
+            PalData { ptr: s.as_mut_ptr() as *mut PalData },
+            PalData { int: s.len() as PalInt },
+        ];
+        Ok((tp, cells))
+    }
+
+    ///reads a list of the form ( word ... ) turning each word into an item with parse
+    fn read_fields<T>(
+        &mut self,
+        name: &'lex str,
+        items: &mut StackVec<'_, T>,
+        parse: impl Fn(&mut Lex<'lex>, &'lex str) -> Option<T>,
+    ) -> Result<(), PalError<'lex>> {
+        let input = self.input.as_mut().expect("need input to read fields");
+        if input.next_word()? != Some("(") {
+            return Err(PalError::BadField(name));
+        }
+
+        loop {
+            let input = self.input.as_mut().expect("need input to read fields");
+            let Some(word) = input.next_word()? else {
+                return Err(PalError::BadField("("));
+            };
+            if word == ")" {
+                return Ok(());
+            }
+            let word = word as *const str;
+            //# Safety
+            //the input is not touched while word is in use
+            let word = self.keep_str(unsafe { &*word });
+            let Some(item) = parse(self.lex, word) else {
+                return Err(PalError::BadField(word));
+            };
+            if items.push(item).is_err() {
+                return Err(PalError::BadField(word));
+            }
+        }
+    }
+
+    ///drops the box on top, a drop is compiled when not running
+    ///this is how a word gets rid of a box it made such as that of a global it wrote to
+    pub fn drop_box(&mut self, run: bool) -> Result<(), SigError<'lex>> {
+        if run {
+            return self.immidate_stack.drop_top();
+        }
+        self.stack.drop_top()?;
+        self.save_code(Code::basic(op::param_drop, 1));
+        Ok(())
+    }
+
+    ///reads `: type` after the name of a variable and registers it
+    pub fn read_variable(&mut self, name: &'lex str) -> Result<*mut PalData, PalError<'lex>> {
+        self.expect_word(":")?;
+        let tp = self.read_type()?;
+        Ok(self.lex.add_variable(name, tp))
+    }
+
+    ///reads `= literal` after the name of a constant and registers it
+    pub fn read_constant(&mut self, name: &'lex str) -> Result<*const PalData, PalError<'lex>> {
+        self.expect_word("=")?;
+        let word = self.read_name()?;
+        let (tp, value) = parse_literal(self.lex, word).ok_or(PalError::BadGlobal(word))?;
+        Ok(self.lex.add_constant(name, tp, value))
+    }
+
+    ///reads `type word` and makes word the destructor of the type
+    ///it has to take a single box of the type and leave nothing behind
+    ///it is inlined into the epilogue so it may not return early either
+    pub fn read_destructor(&mut self) -> Result<(), PalError<'lex>> {
+        let tp = self.read_type()?;
+        let name = self.read_name()?;
+        let word = self
+            .lex
+            .words
+            .get(name)
+            .ok_or(PalError::Missingword(name))?
+            .overloads()
+            .find(|w| {
+                let rt = &w.runtime;
+                w.immidate.is_none()
+                    && rt.output_sig.is_empty()
+                    && matches!(rt.input_sig, [arg] if tp.fits(arg.tp))
+                    && !rt.exits_early()
+            })
+            .ok_or(PalError::BadDestructor(name))?
+            .clone();
+        //nothing marks where a destructor runs so it can not be raw
+        if word.runtime.is_raw() {
+            return Err(SigError::Unsafe(word.name).into());
+        }
+        self.lex.destructors.insert(tp.resolved(), word);
+        Ok(())
+    }
+
+    ///reads the next word which has to be want
+    fn expect_word(&mut self, want: &'static str) -> Result<(), PalError<'lex>> {
+        let word = self.read_name()?;
+        if word != want {
+            return Err(PalError::BadGlobal(word));
+        }
+        Ok(())
+    }
+
+    ///reads the fields of a cluster of the form ( name:type ... ) and registers it
+    pub fn read_cluster(&mut self, name: &'lex str) -> Result<TypeP<'lex>, PalError<'lex>> {
+        let mut mem = [MaybeUninit::uninit(); MAX_FIELDS];
+        let mut fields = StackVec::from_slice(&mut mem);
+        self.read_fields(name, &mut fields, |lex, word| {
+            let (field, tp) = word.split_once(':')?;
+            Some((field, lex.parse_type(tp)?))
+        })?;
+        let fields = fields.peek_many(fields.len()).expect("len was checked");
+        //fields are read and written byte for byte and nothing frees them
+        if let Some((_, tp)) = fields.iter().find(|(_, tp)| tp.owns_memory()) {
+            return Err(PalError::MoveOnly(tp.name));
+        }
+        Ok(self.lex.add_cluster(name, fields))
+    }
+
+    ///reads the cases of a variant of the form ( name:type name ... ) and registers it
+    ///a case without a type has no payload
+    pub fn read_variant(&mut self, name: &'lex str) -> Result<TypeP<'lex>, PalError<'lex>> {
+        let mut mem = [MaybeUninit::uninit(); MAX_FIELDS];
+        let mut cases = StackVec::from_slice(&mut mem);
+        self.read_fields(name, &mut cases, |lex, word| match word.split_once(':') {
+            Some((case, tp)) => Some((case, Some(lex.parse_type(tp)?))),
+            None => Some((word, None)),
+        })?;
+        let cases = cases.peek_many(cases.len()).expect("len was checked");
+        //constructors copy the payload in byte for byte and nothing frees it
+        if let Some(tp) = cases.iter().filter_map(|(_, tp)| *tp).find(|tp| tp.owns_memory()) {
+            return Err(PalError::MoveOnly(tp.name));
+        }
+        Ok(self.lex.add_variant(name, cases))
+    }
+
+    ///opens a match on the variant on top, each case then gets an arm with [`CompContext::begin_case`]
+    ///an arm starts with the payload of its case in place of the variant and has to consume it
+    ///every arm must leave the stack under the variant as it found it, results go into boxes under it
+    ///
+    ///while inferring a match on an empty stack is on a caller box whose type the first case names
+    pub fn begin_match(&mut self) -> Result<(), PalError<'lex>> {
+        let frame = match self.stack.stack.peek() {
+            None if self.stack.infer => MatchFrame::pending(),
+            _ => self.frame_for_top("match")?,
+        };
+        self.matches
+            .push(frame)
+            .map_err(|_| PalError::BadMatch("match"))
+    }
+
+    ///a match on the variant on top
+    fn frame_for_top(&self, word: &'lex str) -> Result<MatchFrame<'me, 'lex>, PalError<'lex>> {
+        let variant = *self.stack.stack.peek().ok_or(PalError::BadMatch(word))?;
+        let cases = variant
+            .borrow()
+            .tp
+            .cases()
+            .filter(|c| c.len() <= u64::BITS as usize)
+            .ok_or(PalError::BadMatch(word))?;
+        Ok(MatchFrame {
+            variant: Some(variant),
+            cases,
+            below: self.stack.stack.peek_many(2).map(|b| b[1]),
+            depth: self.stack.stack.len() - 1,
+            inferred: self.stack.inferred().count(),
+            covered: 0,
+            arm: None,
+            next: 0,
+            ends: -1,
+        })
+    }
+
+    ///starts the arm of a case in the innermost match, cases are named like their constructor (Type.case)
+    ///when the tag matches the variant is turned into a box of its payload (derived from it)
+    pub fn begin_case(&mut self, name: &'lex str) -> Result<(), PalError<'lex>> {
+        let mut frame = *self.matches.peek().ok_or(PalError::BadMatch(name))?;
+        let (tp, case) = name.rsplit_once('.').ok_or(PalError::BadMatch(name))?;
+        let tp = self.lex.named_type(tp).ok_or(PalError::BadMatch(name))?;
+        if frame.variant.is_none() {
+            self.stack.push_inferred_below(tp);
+            frame = self.frame_for_top(name)?;
+        }
+        let variant = frame.variant.expect("was just inferred");
+        let tag = frame
+            .cases
+            .iter()
+            .position(|c| c.name == case)
+            .filter(|&tag| frame.covered & 1 << tag == 0)
+            .filter(|_| variant.borrow().tp.fits(tp))
+            .ok_or(PalError::BadMatch(name))?;
+        match frame.arm {
+            //nothing may happen between the match and its first case
+            None => {
+                let on_top = self.stack.stack.peek().is_some_and(|b| core::ptr::eq(*b, variant));
+                if !on_top || self.stack.stack.len() != frame.depth + 1 {
+                    return Err(PalError::BadMatch(name));
+                }
+            }
+            Some(_) => {
+                self.close_arm(&mut frame, false)?;
+                self.stack.stack.push(variant).expect("overflow sig stack");
+            }
+        }
+
+        //the variant is only taken off the stack, it stays alive untill the match is done
+        self.save_code(Code::basic(op::variant_skip, tag as isize));
+        frame.next = self.save_code(Code::basic(op::jump, 0));
+        self.stack.stack.free(1);
+        match frame.cases[tag].payload {
+            Some(tp) => {
+                self.save_code(Code::basic(op::field_at, 1));
+                let rwu = READ_FLAG | WRITE_FLAG | UNIQUE_FLAG;
+                self.stack.push_derived(variant, tp, rwu, None);
+            }
+            None => {
+                self.save_code(Code::basic(op::param_drop, 1));
+            }
+        }
+        frame.covered |= 1 << tag;
+        frame.arm = Some(tag);
+        *self.matches.peek_mut().expect("was just peeked") = frame;
+        Ok(())
+    }
+
+    ///closes the innermost match, every case of the variant needs an arm
+    pub fn end_match(&mut self) -> Result<(), PalError<'lex>> {
+        let mut frame = self.matches.pop().ok_or(PalError::BadMatch("endmatch"))?;
+        if let Some((_, missing)) = frame
+            .cases
+            .iter()
+            .enumerate()
+            .find(|(tag, _)| frame.covered & 1 << tag == 0)
+        {
+            return Err(PalError::NonExhaustive(missing.name));
+        }
+
+        let end = self.lex.code_mem.len();
+        let Some(variant) = frame.variant else {
+            return Err(PalError::BadMatch("endmatch"));
+        };
+        match frame.arm {
+            //a variant without cases can not exist so there is nothing to run
+            None => {
+                self.save_code(Code::basic(op::param_drop, 1));
+                self.stack.drop_top()?;
+                return Ok(());
+            }
+            //the last case can not fail but its jump still has to land somewhere
+            Some(_) => {
+                self.close_arm(&mut frame, true)?;
+                self.patch_jump(frame.next, end);
+            }
+        }
+        let mut at = frame.ends;
+        while at >= 0 {
+            let prev = self.lex.code_mem[at as usize].param.load(Ordering::Relaxed) as isize;
+            self.patch_jump(at as usize, end);
+            at = prev;
+        }
+        self.stack.retire(variant);
+        Ok(())
+    }
+
+    ///checks the open arm left the stack as it found it and jumps from its end past the match
+    ///boxes inferred inside the arm are caller boxes under everything so they do not count
+    fn close_arm(&mut self, frame: &mut MatchFrame<'me, 'lex>, last: bool) -> Result<(), PalError<'lex>> {
+        let arm = frame.arm.expect("there is an open arm");
+        let inferred = self.stack.inferred().count();
+        frame.depth += inferred - frame.inferred;
+        frame.inferred = inferred;
+        let top = self.stack.stack.peek().copied();
+        let same_top = match (top, frame.below) {
+            (Some(a), Some(b)) => core::ptr::eq(a, b),
+            (None, None) => true,
+            //an empty stack may have grown inferred boxes
+            (Some(a), None) => a.borrow().inferred,
+            (None, Some(_)) => false,
+        };
+        if self.stack.stack.len() != frame.depth || !same_top {
+            return Err(PalError::UnevenArm(frame.cases[arm].name));
+        }
+        if last {
+            return Ok(());
+        }
+
+        //the jumps to the end are chained through their params untill the end is known
+        let end = self.save_code(Code::basic(op::jump, frame.ends));
+        frame.ends = end as isize;
+        let here = self.lex.code_mem.len();
+        self.patch_jump(frame.next, here);
+        Ok(())
+    }
+
+    ///saves code and returns its index in code mem
+    fn save_code(&mut self, code: Code) -> usize {
+        let at = self.lex.code_mem.len();
+        self.lex.code_mem.save(code).expect("out of code mem");
+        at
+    }
+
+    ///points the jump at index at to land on index target
+    fn patch_jump(&mut self, at: usize, target: usize) {
+        //the executor steps 1 past where the jump takes it
+        let offset = target as isize - at as isize - 1;
+        self.lex.code_mem[at] = Code::basic(op::jump, offset);
+    }
+
+    pub fn begin_word(&mut self, name: &'lex str) {
+        self.def_name = Some(name);
+        self.start = self.lex.code_mem.check_point();
+        self.stack.infer = true;
+        self.unsafe_at_begin = self.unsafe_depth;
+    }
+
+    ///finishes the current definition and stores it in the lex
+    pub fn end_word(&mut self) -> Result<(), PalError<'lex>> {
+        if self.quoting {
+            self.abandon_word();
+            return Err(PalError::BadQuotation(";"));
+        }
+        let word = self.finish_word()?;
+        self.lex.add_word(word);
+        Ok(())
+    }
+
+    ///starts compiling an anonymous word, only at the top level since its code has to be contiguous
+    pub fn begin_quotation(&mut self) -> Result<(), PalError<'lex>> {
+        if self.def_name.is_some() {
+            return Err(PalError::BadQuotation("[:"));
+        }
+        self.begin_word("[:");
+        self.quoting = true;
+        Ok(())
+    }
+
+    ///finishes the current quotation without naming it and leaves its xt on the immidate stack
+    pub fn end_quotation(&mut self) -> Result<*mut PalData, PalError<'lex>> {
+        if self.def_name.is_none() {
+            return Err(PalError::NotCompiling);
+        }
+        if !self.quoting {
+            self.abandon_word();
+            return Err(PalError::BadQuotation(";]"));
+        }
+        self.quoting = false;
+        let word = self.finish_word()?;
+        let (tp, xt) = self.lex.xt_of(&word);
+        Ok(self.run_literal(tp, xt))
+    }
+
+    ///the signature is inferred from the body and checked against the declared one if there is one
+    ///on failure the whole definition is thrown away
+    fn finish_word(&mut self) -> Result<Word<'lex>, PalError<'lex>> {
+        let name = self.def_name.take().ok_or(PalError::NotCompiling)?;
+        if !self.matches.is_empty() {
+            self.abandon_word();
+            return Err(PalError::BadMatch(";"));
+        }
+        if self.unsafe_depth != self.unsafe_at_begin {
+            self.abandon_word();
+            return Err(PalError::BadUnsafe(";"));
+        }
+        let declared = self.declared.take();
+        let res = self.infer_sig(name).and_then(|inferred| match declared {
+            Some(declared) => {
+                sig_fits(declared, inferred)?;
+                Ok(declared)
+            }
+            None => Ok(inferred),
+        });
+        let [input_sig, output_sig] = match res {
+            Ok(sig) => sig,
+            Err(e) => {
+                self.abandon_word();
+                return Err(e);
+            }
+        };
+
+        self.epilogue();
+        self.stack.clear();
+        self.stack.infer = false;
+        Ok(self
+            .finalize_word(name, input_sig, output_sig)
+            .expect("stack was cleared"))
+    }
+
+    ///the way out of a word, the frame of its locals is freed before returning
+    ///every exit has to go through here or the data stack is left unbalanced
+    ///so code returning early is refused while there are locals (see add_runtime_code)
+    ///locals with a destructor get it called on them first, newest first
+    fn epilogue(&mut self) {
+        let frame = self.stack.local_cells();
+        for var in self.stack.locals().rev() {
+            let (tp, offset) = {
+                let v = var.borrow();
+                (v.tp, v.offset_from_start)
+            };
+            let Some(destructor) = self.lex.destructor(tp).cloned() else {
+                continue;
+            };
+            let idx = frame - offset - tp.cells;
+            let code = &mut self.lex.code_mem;
+            code.save(Code::basic(op::push_local, idx as isize))
+                .expect("out of code mem");
+            destructor.runtime.save_to_alloc(code);
+        }
+        if frame > 0 {
+            self.save_code(Code::basic(op::frame_free, frame as isize));
+        }
+        self.save_code(Code::basic(op::ret, 0));
+    }
+
+    ///reads a `{ name:type ... }` declaration and makes room for the locals in the frame
+    ///each name pushes its local from there to the end of the word, a name declared twice is shadowed
+    ///a match is left before the frame grows so every path out of the word frees the same amount
+    pub fn read_locals(&mut self) -> Result<(), PalError<'lex>> {
+        if self.def_name.is_none() {
+            return Err(PalError::NotCompiling);
+        }
+        let res = self.read_locals_inner();
+        if res.is_err() {
+            self.abandon_word();
+        }
+        res
+    }
+
+    fn read_locals_inner(&mut self) -> Result<(), PalError<'lex>> {
+        if !self.matches.is_empty() {
+            return Err(PalError::BadLocal("{"));
+        }
+        let before = self.stack.local_cells();
+        loop {
+            let input = self.input.as_mut().expect("need input to read locals");
+            let Some(word) = input.next_word()? else {
+                return Err(PalError::BadLocal("{"));
+            };
+            if word == "}" {
+                break;
+            }
+            let word = word as *const str;
+            //# Safety
+            //the input is not touched while word is in use
+            let word = self.keep_str(unsafe { &*word });
+            let (name, tp) = word.split_once(':').ok_or(PalError::BadLocal(word))?;
+            let tp = self.lex.parse_type(tp).ok_or(PalError::UnknownType(tp))?;
+            self.stack.add_local(tp).borrow_mut().name = Some(name);
+        }
+
+        let cells = self.stack.local_cells() - before;
+        if cells > 0 {
+            self.save_code(Code::basic(op::frame_alloc, cells as isize));
+        }
+        Ok(())
+    }
+
+    ///compiles a push of the local called name, false when there is none
+    ///locals are laid out from the base of the frame down so older ones keep their place as it grows
+    pub fn push_named_local(&mut self, name: &str) -> bool {
+        let Some(var) = self.stack.find_local(name) else {
+            return false;
+        };
+        let end = {
+            let v = var.borrow();
+            v.offset_from_start + v.tp.cells
+        };
+        let idx = self.stack.local_cells() - end;
+        self.save_code(Code::basic(op::push_local, idx as isize));
+        self.stack.stack.push(var).expect("overflow sig stack");
+        true
+    }
+
+    ///the caller provided boxes that got consumed are inputs and the ones still on the stack are outputs
+    ///anything else on the stack at the end is an error
+    fn infer_sig(&mut self, name: &'lex str) -> Result<[&'lex [SigItem<'lex>]; 2], PalError<'lex>> {
+        let live = self.stack.stack.len();
+        let made = self.stack.inferred().count();
+        let boxes = self.stack.stack.peek_many(live).expect("len is live");
+        if boxes.iter().any(|b| !b.borrow().inferred) {
+            return Err(PalError::LeftOnStack(name));
+        }
+
+        //outputs must all be below the inputs, so they are the last ones made
+        let consumed = made - live;
+        if !boxes
+            .iter()
+            .zip(self.stack.inferred().skip(consumed))
+            .all(|(a, b)| core::ptr::eq(*a, b))
+        {
+            return Err(PalError::UnorderedOutputs(name));
+        }
+
+        let item = |b: &RefCell<CompVar<'_, 'lex>>| {
+            let b = b.borrow();
+            SigItem {
+                tp: b.tp,
+                permissions: b.permissions,
+            }
+        };
+        //sigs are stored bottom first
+        let mem = &mut self.lex.comp_data_mem;
+        let inputs = mem
+            .save_iter(self.stack.inferred().rev().skip(live).map(item))
+            .expect("Out of memory in comp data");
+        let outputs = mem
+            .save_iter(self.stack.inferred().rev().take(live).map(item))
+            .expect("Out of memory in comp data");
+        Ok([inputs, outputs])
+    }
+
+    ///drops all the code and boxes of the current definition
+    pub fn abandon_word(&mut self) {
+        self.def_name = None;
+        self.declared = None;
+        self.quoting = false;
+        self.matches.free(self.matches.len());
+        self.unsafe_depth = self.unsafe_at_begin;
+        unsafe { self.lex.code_mem.goto_checkpoint(self.start) };
+        self.stack.clear();
+        self.stack.infer = false;
+    }
+
+    ///the address the code of the current word starts at
+    ///calling it (with [`Code::word_raw`]) is how a word recurses
+    #[inline]
+    pub fn code_start(&self) -> *const Code {
+        self.lex.code_mem.checkpoint_ptr(self.start)
+    }
+
     ///verifies the stack is empty and returns the generated code
+    ///calls directly followed by a ret are turned into tail calls
     #[inline]
-    pub fn finalize_code(&self) -> Result<&'lex [Code], ()> {
+    #[allow(clippy::result_unit_err)]
+    pub fn finalize_code(&mut self) -> Result<&'lex [Code], ()> {
         if self.stack.stack.is_empty() {
+            let code = self.lex.code_mem.index_checkpoint(self.start);
+            self.eliminate_tail_calls(code.len());
             Ok(self.lex.code_mem.index_checkpoint(self.start))
         } else {
             Err(())
         }
     }
 
+    ///rewrites every [call, ret] pair into [tail_call, ret]
+    ///the ret is kept in place since a jump may still land on it
+    fn eliminate_tail_calls(&mut self, len: usize) {
+        let start = self.lex.code_mem.len() - len;
+        for i in start..(start + len).saturating_sub(1) {
+            let call = &self.lex.code_mem[i];
+            if call.f.load(Ordering::Relaxed).is_some() || !self.lex.code_mem[i + 1].is(op::ret) {
+                continue;
+            }
+
+            //tail_call returns its param and the executor steps 1 past it
+            let target = call.param.load(Ordering::Relaxed) as *const Code;
+            self.lex.code_mem[i] = Code::basic_raw(op::tail_call, target.wrapping_sub(1));
+        }
+    }
+
+    #[allow(clippy::result_unit_err)]
     pub fn finalize_and_store_word(
         &mut self,
         name: &'lex str,
         input_sig: &'lex [SigItem<'lex>],
         output_sig: &'lex [SigItem<'lex>],
     ) -> Result<(), ()> {
+        let word = self.finalize_word(name, input_sig, output_sig)?;
+        self.lex.add_word(word);
+        Ok(())
+    }
+
+    ///like [`CompContext::finalize_and_store_word`] but hands the word back instead of storing it
+    #[allow(clippy::result_unit_err)]
+    pub fn finalize_word(
+        &mut self,
+        name: &'lex str,
+        input_sig: &'lex [SigItem<'lex>],
+        output_sig: &'lex [SigItem<'lex>],
+    ) -> Result<Word<'lex>, ()> {
         let code = self.finalize_code()?;
         let runtime = RuntimeCode {
             exe: Exe::Outlined(code),
             input_sig,
             output_sig,
+            field: None,
         };
         let word = Word {
             name,
             runtime,
             immidate: None,
+            pure: false,
+            traps: None,
+            overload: None,
         };
-        self.lex.words.insert(name, word);
-        Ok(())
+        self.start = self.lex.code_mem.check_point();
+        Ok(word)
     }
 }
 
-#[derive(Default)]
 pub struct CompEasyMemory<'me, 'lex, const STACK_SIZE: usize>{
 	stack:SigStackEasyMemory<'me, 'lex,STACK_SIZE>,
 	immidate_stack:SigStackEasyMemory<'me, 'lex,STACK_SIZE>,
+	matches:[MaybeUninit<MatchFrame<'me, 'lex>>; MAX_MATCH_DEPTH],
+}
+
+impl<'me, 'lex, const STACK_SIZE: usize> Default for CompEasyMemory<'me, 'lex,STACK_SIZE>{
+	fn default()->Self{
+		Self{
+			stack:SigStackEasyMemory::default(),
+			immidate_stack:SigStackEasyMemory::default(),
+			matches:make_storage(),
+		}
+	}
 }
 
 
@@ -92,6 +1221,13 @@ impl<'me, 'lex, const STACK_SIZE: usize> CompEasyMemory<'me, 'lex,STACK_SIZE>{
 			immidate_stack:self.immidate_stack.make_sig_stack(),
 			stack:self.stack.make_sig_stack(),
 			input:None,
+			def_name:None,
+			error:None,
+			declared:None,
+			matches:StackVec::from_slice(&mut self.matches),
+			unsafe_depth:0,
+			unsafe_at_begin:0,
+			quoting:false,
 		}
 	}
 }
@@ -101,11 +1237,133 @@ pub struct Word<'lex> {
     pub name: &'lex str,
     pub runtime: RuntimeCode<'lex>,
     pub immidate: Option<&'lex Code>,
+    ///the result only depends on the inputs and nothing else is touched
+    ///such words are ran at compile time when all their boxes are constants
+    pub pure: bool,
+    ///for pure words that can trap, whether they would on these values (bottom of the stack first)
+    pub traps: Option<TrapCheck>,
+    ///an older word of the same name that takes other input types
+    pub overload: Option<&'lex Word<'lex>>,
+}
+
+impl<'lex> Word<'lex> {
+    ///this word followed by the ones it overloads, newest first
+    pub fn overloads(&self) -> impl Iterator<Item = &Word<'lex>> + Clone {
+        core::iter::successors(Some(self), |w| w.overload)
+    }
+
+    ///whether the two take the same input types and so can not be told apart by a call
+    pub fn same_inputs(&self, other: &Word<'lex>) -> bool {
+        let (a, b) = (self.runtime.input_sig, other.runtime.input_sig);
+        a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.tp.fits(b.tp))
+    }
+}
+
+pub type TrapCheck = fn(&[PalData]) -> bool;
+
+///a type in the signature of a generic word, T is worked out at each call site
+#[derive(Debug, Clone, Copy)]
+pub enum GenericTp<'lex> {
+    Known(TypeP<'lex>),
+    ///the type variable itself
+    Var,
+    ///a slice of T
+    SliceOf,
+    ///a raw pointer to a T
+    PtrTo,
+    ///an owned heap box of a T
+    #[cfg(feature = "heap")]
+    BoxOf,
+}
+
+impl<'lex> GenericTp<'lex> {
+    ///what T has to be for a box of type tp to fit here (None if this does not say)
+    pub fn bind(self, tp: TypeP<'lex>) -> Option<TypeP<'lex>> {
+        match (self, tp.resolved().inner) {
+            (GenericTp::Var, _) => Some(tp),
+            (GenericTp::SliceOf, TypeInner::Array(elem, None)) => Some(elem),
+            (GenericTp::PtrTo, TypeInner::Ptr(elem)) => Some(elem),
+            #[cfg(feature = "heap")]
+            (GenericTp::BoxOf, TypeInner::Box(elem)) => Some(elem),
+            _ => None,
+        }
+    }
+
+    pub fn specialise(self, lex: &mut Lex<'lex>, tp: TypeP<'lex>) -> TypeP<'lex> {
+        match self {
+            GenericTp::Known(known) => known,
+            GenericTp::Var => tp,
+            GenericTp::SliceOf => TypeInner::Array(tp, None).get_type_ref(lex),
+            GenericTp::PtrTo => lex.add_ptr(tp),
+            #[cfg(feature = "heap")]
+            GenericTp::BoxOf => lex.add_box(tp),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct GenericItem<'lex> {
+    pub tp: GenericTp<'lex>,
+    pub permissions: RwT,
+}
+
+///what a specialisation bakes into the param of a buildin
+#[derive(Debug, Clone, Copy)]
+pub enum GenericParam {
+    Fixed(isize),
+    Size,
+    Cells,
+    ///bytes a T takes up in an array (and in its box)
+    Stride,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct GenericCode {
+    pub f: Buildin,
+    pub param: GenericParam,
+}
+
+impl GenericCode {
+    pub fn specialise(&self, tp: TypeP<'_>) -> Code {
+        let param = match self.param {
+            GenericParam::Fixed(v) => v,
+            GenericParam::Size => tp.size as isize,
+            GenericParam::Cells => tp.cells as isize,
+            GenericParam::Stride => tp.stride() as isize,
+        };
+        Code::basic(self.f, param)
+    }
+}
+
+///a word over a type variable T, each T it is used with gets its own specialised word
+#[derive(Debug, Clone, Copy)]
+pub struct GenericWord<'lex> {
+    pub name: &'lex str,
+    pub code: &'lex [GenericCode],
+    pub input_sig: &'lex [GenericItem<'lex>],
+    pub output_sig: &'lex [GenericItem<'lex>],
+    ///the code copies a T byte for byte so it can not be made for types that own memory
+    pub copies: bool,
+}
+
+impl<'lex> GenericWord<'lex> {
+    ///works out T from the boxes on top of the stack
+    ///the outputs sit under the inputs so the top box is matched with the last input
+    pub fn bind(&self, stack: &SigStack<'_, 'lex>) -> Option<TypeP<'lex>> {
+        let items = self.output_sig.iter().chain(self.input_sig);
+        let n = stack.stack.len().min(items.clone().count());
+        let tops = stack.stack.peek_many(n)?;
+        items
+            .rev()
+            .zip(tops)
+            .find_map(|(item, b)| item.tp.bind(b.borrow().tp))
+    }
 }
 
 ///a moveble peice of code that may or may not be inlined
 ///for the most part inlined code should be reserved for buildins
 ///inlining derived words can be good but it requires the JIT to do double work
+///inlined code is allways directly followed by a ret (outside the slice) so it can run on its own
 #[derive(Debug,Clone)]
 pub enum Exe<'lex> {
     Inlined(&'lex [Code]),
@@ -113,7 +1371,7 @@ pub enum Exe<'lex> {
 }
 
 impl<'lex> Exe<'lex> {
-	fn inner_slice(&self)-> &'lex [Code]{
+	pub fn inner_slice(&self)-> &'lex [Code]{
 		match self{
 			Exe::Inlined(s)|Exe::Outlined(s)=>s
 		}
@@ -131,6 +1389,12 @@ impl<'lex> Exe<'lex> {
 			},
 			Exe::Inlined(slice) => {
 				for c in slice.iter() {
+					//the fast loop keeps the stack in registers across these
+					#[cfg(feature = "fast_dispatch")]
+					let c = &match crate::buildins::fast_variant(c) {
+						Some(fast) => Code::fast(fast),
+						None => c.shallow_clone(),
+					};
 					alloc.save(c.shallow_clone())
 					.expect("out of code mem");
 				}
@@ -144,9 +1408,34 @@ pub struct RuntimeCode<'lex> {
     exe: Exe<'lex>,
     pub input_sig: &'lex [SigItem<'lex>],
     pub output_sig: &'lex [SigItem<'lex>],
+    ///INDEX outputs point at this field of the first input (None is anywhere in it)
+    pub field: Option<u32>,
 }
 
 impl<'lex> RuntimeCode<'lex> {
+    pub fn new(
+        exe: Exe<'lex>,
+        input_sig: &'lex [SigItem<'lex>],
+        output_sig: &'lex [SigItem<'lex>],
+    ) -> Self {
+        Self {
+            exe,
+            input_sig,
+            output_sig,
+            field: None,
+        }
+    }
+
+    #[inline]
+    pub fn exe(&self) -> &Exe<'lex> {
+        &self.exe
+    }
+
+    ///whether a box it takes or leaves holds a raw pointer
+    pub fn is_raw(&self) -> bool {
+        self.input_sig.iter().chain(self.output_sig).any(|item| item.tp.is_raw())
+    }
+
     ///# Safety
     /// same as [`Vm::execute_code`]
     #[inline(always)]
@@ -158,11 +1447,20 @@ impl<'lex> RuntimeCode<'lex> {
     	self.exe.save_to_alloc(alloc)
     }
 
+    ///whether inlining this returns from the word it is put in
+    ///a called word returns to its caller so only inlined code can
+    pub fn exits_early(&self) -> bool {
+        match self.exe {
+            Exe::Outlined(_) => false,
+            Exe::Inlined(code) => code.iter().any(|c| c.is(op::ret) || c.is(op::tail_call)),
+        }
+    }
+
     ///# Safety
     ///the type stack must hold correct information
     ///other than that checks handle everything
     #[inline]
-    pub unsafe fn comp_run_checked<'comp>(&self, vm: &mut Vm<'_,'lex, '_>) -> Result<(), SigError<'lex>> {
+    pub unsafe fn comp_run_checked(&self, vm: &mut Vm<'_,'lex, '_>) -> Result<(), SigError<'lex>> {
         let comp = vm.comp.get_comp_crash();
 
         self.check_sig(&mut comp.immidate_stack)?;
@@ -174,6 +1472,6 @@ impl<'lex> RuntimeCode<'lex> {
 
     #[inline]
     fn check_sig(&self, sig: &mut SigStack<'_, 'lex>) -> Result<(), SigError<'lex>> {
-        sig.call_sig(self.output_sig, self.input_sig)
+        sig.call_sig_derived(self.output_sig, self.input_sig, self.field)
     }
 }
@@ -1,10 +1,25 @@
 use crate::Code;
+use crate::PalData;
 use crate::PalHash;
+use crate::buildins::op;
+use crate::ir::Exe;
+use crate::ir::GenericCode;
+use crate::ir::GenericItem;
+use crate::ir::GenericWord;
+use crate::ir::RuntimeCode;
 use crate::ir::Word;
+use crate::vm::Buildin;
 use crate::stack::StackVec;
+use crate::types::INDEX_FLAG;
+use crate::types::READ_FLAG;
+use crate::types::RwT;
+use crate::types::SigItem;
 use crate::types::Type;
 use crate::types::TypeInner;
 use crate::types::TypeP;
+use crate::types::UNIQUE_FLAG;
+use crate::types::VariantCase;
+use crate::types::WRITE_FLAG;
 use core::fmt;
 use core::fmt::Display;
 use core::fmt::Formatter;
@@ -20,45 +35,595 @@ use core::slice;
 
 pub struct Lex<'lex> {
     pub code_mem: StackAllocator<'lex, Code>,
+    ///code of specialised generic words, they can be made in the middle of a definition
+    ///so they get their own memory instead of splitting (or being thrown away with) its body
+    pub generic_code_mem: StackAllocator<'lex, Code>,
     pub data_mem: StackAlloc<'lex>,
+    ///constants are kept apart from the rest of data_mem so writing to them faults
+    #[cfg(all(unix, feature = "protected_consts"))]
+    pub const_mem: ProtectedAlloc<'lex>,
 
     pub comp_data_mem: StackAlloc<'lex>,
     pub types_mem: StackAllocator<'lex, Type<'lex>>,
     pub type_map: PalHash<&'lex TypeInner<'lex>, TypeP<'lex>>,
+    ///types that can be refered to by name (basic types, named clusters, variants, aliases and newtypes)
+    pub type_names: PalHash<&'lex str, TypeP<'lex>>,
 
+    ///the newest word of each name, older overloads hang off of it
     pub words: PalHash<&'lex str, Word<'lex>>,
+    pub overload_mem: StackAllocator<'lex, Word<'lex>>,
+    ///words over a type variable, looked up when a name is not in words
+    pub generics: PalHash<&'lex str, GenericWord<'lex>>,
+    ///every specialisation made so far by generic name and type
+    pub specialised: PalHash<(&'lex str, TypeP<'lex>), Word<'lex>>,
+    ///the word called on a local of a type when the frame it lives in goes away
+    pub destructors: PalHash<TypeP<'lex>, Word<'lex>>,
+    ///static data whose type holds an address by where it starts, these only make sense in this process
+    pub address_data: PalHash<*const PalData, TypeP<'lex>>,
 }
 
 pub const CODE_MEM_SIZE: usize = 1024 * 10;
+pub const GENERIC_CODE_MEM_SIZE: usize = 1024;
 pub const DATA_MEM_SIZE: usize = 1024 * 10;
 pub const COMP_DATA_MEM_SIZE: usize = 1024 * 10;
+#[cfg(all(unix, feature = "protected_consts"))]
+pub const CONST_MEM_SIZE: usize = 1024 * 4;
 pub const TYPES_MEM_SIZE: usize = 1024;
+pub const OVERLOAD_MEM_SIZE: usize = 256;
+///the most items a signature of a generic word can have
+pub const MAX_GENERIC_SIG: usize = 8;
 
 pub struct LexEasyMemory<'lex> {
     code_mem: [MaybeUninit<crate::vm::Code>; CODE_MEM_SIZE],
+    generic_code_mem: [MaybeUninit<crate::vm::Code>; GENERIC_CODE_MEM_SIZE],
     data_mem: [MaybeUninit<u8>; DATA_MEM_SIZE],
     comp_data_mem: [MaybeUninit<u8>; COMP_DATA_MEM_SIZE],
     types_mem: [MaybeUninit<Type<'lex>>; TYPES_MEM_SIZE],
+    overload_mem: [MaybeUninit<Word<'lex>>; OVERLOAD_MEM_SIZE],
+}
+
+impl<'lex> Lex<'lex> {
+    ///registers a new basic type, basic types are only distinguished by name
+    pub fn add_basic_type(&mut self, name: &'lex str, size: i32, cells: i32) -> TypeP<'lex> {
+        let me = self
+            .types_mem
+            .save(Type {
+                inner: TypeInner::Basic(name),
+                name,
+                cells,
+                size,
+            })
+            .expect("Out of memory in types arena");
+        self.type_map.insert(&me.inner, me);
+        self.type_names.insert(name, me);
+        me
+    }
+
+    #[inline]
+    pub fn basic_type(&self, name: &str) -> Option<TypeP<'lex>> {
+        //the map is covariant so looking up with a shorter lived name is fine
+        let map: &PalHash<&TypeInner<'_>, TypeP<'lex>> = &self.type_map;
+        map.get(&TypeInner::Basic(name)).copied()
+    }
+
+    ///remembers that the static data at data is a tp, if that holds an address it can not be dumped as is
+    #[inline]
+    pub fn note_address_data(&mut self, data: *const PalData, tp: TypeP<'lex>) {
+        if tp.holds_address() {
+            self.address_data.insert(data, tp);
+        }
+    }
+
+    #[inline]
+    pub fn named_type(&self, name: &str) -> Option<TypeP<'lex>> {
+        self.type_names.get(name).copied()
+    }
+
+    ///a type by the name it is written with, `ptr(T)` (and `box(T)`) types are made the first time they are named
+    pub fn parse_type(&mut self, name: &str) -> Option<TypeP<'lex>> {
+        if let Some(tp) = self.named_type(name) {
+            return Some(tp);
+        }
+        #[cfg(feature = "heap")]
+        if let Some(elem) = name.strip_prefix("box(").and_then(|s| s.strip_suffix(')')) {
+            let elem = self.parse_type(elem)?;
+            return Some(self.add_box(elem));
+        }
+        let elem = name.strip_prefix("ptr(")?.strip_suffix(')')?;
+        let elem = self.parse_type(elem)?;
+        Some(self.add_ptr(elem))
+    }
+
+    ///registers the type of raw pointers to elem
+    pub fn add_ptr(&mut self, elem: TypeP<'lex>) -> TypeP<'lex> {
+        let me = TypeInner::Ptr(elem).get_type_ref(self);
+        self.type_names.insert(me.name, me);
+        me
+    }
+
+    ///the destructor of a type, aliases share the one of their parent
+    #[inline]
+    pub fn destructor(&self, tp: TypeP<'lex>) -> Option<&Word<'lex>> {
+        self.destructors.get(tp.resolved())
+    }
+
+    ///registers the type of heap boxes of elem with free<elem> as its destructor
+    #[cfg(feature = "heap")]
+    pub fn add_box(&mut self, elem: TypeP<'lex>) -> TypeP<'lex> {
+        let me = TypeInner::Box(elem).get_type_ref(self);
+        //specialising free names the box type again so it has to be known by then
+        if self.type_names.insert(me.name, me).is_some() {
+            return me;
+        }
+        if let Some(free) = self.generics.get("free").copied() {
+            let word = self.specialise(&free, elem);
+            self.destructors.insert(me, word);
+        }
+        me
+    }
+
+    ///the type of xts for words taking inputs and leaving outputs
+    pub fn xt_type(&mut self, inputs: &'lex [SigItem<'lex>], outputs: &'lex [SigItem<'lex>]) -> TypeP<'lex> {
+        TypeInner::Xt([inputs.into(), outputs.into()]).get_type_ref(self)
+    }
+
+    ///an xt calling word along with its type
+    ///the header pointing at the code is kept in data mem so taking one mid definition is fine
+    pub fn xt_of(&mut self, word: &Word<'lex>) -> (TypeP<'lex>, PalData) {
+        let tp = self.xt_type(word.runtime.input_sig, word.runtime.output_sig);
+        let header: &'lex Code = self
+            .data_mem
+            .alloc::<Code>()
+            .expect("out of data mem")
+            .write(word.runtime.exe().as_outer());
+        (tp, PalData { code: header })
+    }
+
+    ///the word calling an xt of type xt, it takes the callee's inputs with the xt on top
+    ///and leaves the callee's outputs, None when xt is not an xt type
+    pub fn execute_word(&mut self, xt: TypeP<'lex>) -> Option<Word<'lex>> {
+        let TypeInner::Xt([inputs, outputs]) = xt.inner else {
+            return None;
+        };
+        if let Some(word) = self.specialised.get(&("execute", xt)) {
+            return Some(word.clone());
+        }
+
+        let mut writer = StackWriter::new(&mut self.comp_data_mem);
+        write!(writer, "execute<{}>", xt.name).expect("Out of memory in comp data");
+        let name: &'lex str = writer.finish();
+
+        let input_sig = self
+            .comp_data_mem
+            .save_iter(inputs.iter().copied().chain([SigItem { tp: xt, permissions: READ_FLAG }]))
+            .expect("Out of memory in comp data");
+        let code = save_runnable_in(&mut self.generic_code_mem, [Code::basic(op::call_dyn, 0)]);
+        let word = Word {
+            name,
+            runtime: RuntimeCode::new(Exe::Inlined(&code[..1]), input_sig, outputs.as_slice()),
+            immidate: None,
+            pure: false,
+            traps: None,
+            overload: None,
+        };
+        self.specialised.insert(("execute", xt), word.clone());
+        Some(word)
+    }
+
+    ///registers an array type (None is a slice) and its "type.at" word
+    ///the word consumes the array and an int leaving a box derived from the array
+    ///fixed arrays are checked against their length and slices against the one they carry
+    ///elements may not own memory since nothing frees them
+    pub fn add_array(&mut self, elem: TypeP<'lex>, len: Option<i32>) -> TypeP<'lex> {
+        assert!(!elem.owns_memory(), "arrays of {} would leak their elements", elem.name);
+        let int = self.basic_type("int").expect("indexing needs an int type");
+        let me = TypeInner::Array(elem, len).get_type_ref(self);
+        self.type_names.insert(me.name, me);
+
+        let stride = elem.stride() as isize;
+        let code = match len {
+            None => self.save_runnable([Code::basic(op::slice_at, stride)]),
+            Some(_) if cfg!(feature = "unchecked_bounds") => {
+                self.save_runnable([Code::basic(op::array_at, stride)])
+            }
+            Some(n) => self.save_runnable([
+                Code::basic(op::bounds_check, n as isize),
+                Code::basic(op::array_at, stride),
+            ]),
+        };
+
+        let mut writer = StackWriter::new(&mut self.comp_data_mem);
+        write!(writer, "{}.at", me.name).expect("Out of memory in comp data");
+        let name: &'lex str = writer.finish();
+        let runtime = RuntimeCode::new(
+            Exe::Inlined(&code[..code.len() - 1]),
+            self.save_sig(&[
+                SigItem { tp: me, permissions: 0 },
+                SigItem { tp: int, permissions: READ_FLAG },
+            ]),
+            self.save_sig(&[SigItem {
+                tp: elem,
+                permissions: READ_FLAG | WRITE_FLAG | UNIQUE_FLAG | INDEX_FLAG,
+            }]),
+        );
+        self.add_word(Word {
+            name,
+            runtime,
+            immidate: None,
+            pure: false,
+            traps: None,
+            overload: None,
+        });
+        me
+    }
+
+    ///registers a named cluster and a "name.field" word for each of its fields
+    ///the field words consume the cluster box and leave a box derived from it
+    ///so borrowing one field does not block the others
+    pub fn add_cluster(&mut self, name: &'lex str, fields: &[(&str, TypeP<'lex>)]) -> TypeP<'lex> {
+        let types = self
+            .comp_data_mem
+            .save_iter(fields.iter().map(|(_, tp)| *tp))
+            .expect("Out of memory in comp data");
+        let cluster = TypeInner::Cluster(types.into()).get_type_ref(self);
+        let me = TypeInner::Newtype(cluster, name).get_type_ref(self);
+        self.type_names.insert(name, me);
+
+        let mut offset = 0;
+        for (i, (field, tp)) in fields.iter().enumerate() {
+            let mut writer = StackWriter::new(&mut self.comp_data_mem);
+            write!(writer, "{name}.{field}").expect("Out of memory in comp data");
+            let word_name: &'lex str = writer.finish();
+
+            let code = self.save_runnable([Code::basic(op::field_at, offset as isize)]);
+            let mut runtime = RuntimeCode::new(
+                Exe::Inlined(&code[..1]),
+                self.save_sig(&[SigItem { tp: me, permissions: 0 }]),
+                self.save_sig(&[SigItem {
+                    tp,
+                    permissions: READ_FLAG | WRITE_FLAG | UNIQUE_FLAG | INDEX_FLAG,
+                }]),
+            );
+            runtime.field = Some(i as u32);
+            self.add_word(Word {
+                name: word_name,
+                runtime,
+                immidate: None,
+                pure: false,
+                traps: None,
+            overload: None,
+            });
+            offset += tp.cells;
+        }
+        me
+    }
+
+    ///registers a named variant and a "name.case" constructor for each of its cases
+    ///a constructor writes the tag and consumes the payload (if there is one) into the variant under it
+    pub fn add_variant(
+        &mut self,
+        name: &'lex str,
+        cases: &[(&'lex str, Option<TypeP<'lex>>)],
+    ) -> TypeP<'lex> {
+        let cases: &'lex [VariantCase<'lex>] = self
+            .comp_data_mem
+            .save_iter(cases.iter().map(|&(name, payload)| VariantCase { name, payload }))
+            .expect("Out of memory in comp data");
+        let variant = TypeInner::Variant(cases.into()).get_type_ref(self);
+        let me = TypeInner::Newtype(variant, name).get_type_ref(self);
+        self.type_names.insert(name, me);
+
+        for (tag, case) in cases.iter().enumerate() {
+            let mut writer = StackWriter::new(&mut self.comp_data_mem);
+            write!(writer, "{name}.{}", case.name).expect("Out of memory in comp data");
+            let word_name: &'lex str = writer.finish();
+
+            let out = [SigItem { tp: me, permissions: WRITE_FLAG }];
+            let (code, input) = match case.payload {
+                Some(tp) => (
+                    self.save_runnable([
+                        Code::basic(op::variant_payload, tp.stride() as isize),
+                        Code::basic(op::variant_tag, tag as isize),
+                    ]),
+                    Some(SigItem { tp, permissions: READ_FLAG }),
+                ),
+                None => (self.save_runnable([Code::basic(op::variant_tag, tag as isize)]), None),
+            };
+            let runtime = RuntimeCode::new(
+                Exe::Inlined(&code[..code.len() - 1]),
+                self.save_sig(input.as_slice()),
+                self.save_sig(&out),
+            );
+            self.add_word(Word {
+                name: word_name,
+                runtime,
+                immidate: None,
+                pure: false,
+                traps: None,
+            overload: None,
+            });
+        }
+        me
+    }
+
+    ///registers another name for parent, the two fit wherever either is wanted
+    pub fn add_alias(&mut self, name: &'lex str, parent: TypeP<'lex>) -> TypeP<'lex> {
+        let me = TypeInner::Alias(parent, name).get_type_ref(self);
+        self.type_names.insert(name, me);
+        me
+    }
+
+    ///registers a type laid out like parent that only fits where it is named
+    ///along with "name.wrap" and "name.unwrap" words converting between the two
+    ///both consume their input and leave a box derived from it so nothing is copied
+    pub fn add_newtype(&mut self, name: &'lex str, parent: TypeP<'lex>) -> TypeP<'lex> {
+        let me = TypeInner::Newtype(parent, name).get_type_ref(self);
+        self.type_names.insert(name, me);
+
+        let code = self.save_runnable([Code::basic(op::field_at, 0)]);
+        for (op, from, to) in [("wrap", parent, me), ("unwrap", me, parent)] {
+            let mut writer = StackWriter::new(&mut self.comp_data_mem);
+            write!(writer, "{name}.{op}").expect("Out of memory in comp data");
+            let word_name: &'lex str = writer.finish();
+
+            let runtime = RuntimeCode::new(
+                Exe::Inlined(&code[..1]),
+                self.save_sig(&[SigItem { tp: from, permissions: 0 }]),
+                self.save_sig(&[SigItem {
+                    tp: to,
+                    permissions: READ_FLAG | WRITE_FLAG | UNIQUE_FLAG | INDEX_FLAG,
+                }]),
+            );
+            self.add_word(Word {
+                name: word_name,
+                runtime,
+                immidate: None,
+                pure: false,
+                traps: None,
+            overload: None,
+            });
+        }
+        me
+    }
+
+    ///registers a global of type tp in data mem, it starts out zeroed
+    ///a global can be reached from anywhere so its box is never unique
+    pub fn add_variable(&mut self, name: &'lex str, tp: TypeP<'lex>) -> *mut PalData {
+        let cells = (tp.cells as usize).max(1);
+        let data = self
+            .data_mem
+            .save_iter(core::iter::repeat_n(PalData { int: 0 }, cells))
+            .expect("out of data mem")
+            .as_mut_ptr();
+        self.note_address_data(data, tp);
+        self.add_global(name, tp, data, READ_FLAG | WRITE_FLAG);
+        data
+    }
+
+    ///registers a global holding value that can only be read
+    pub fn add_constant(&mut self, name: &'lex str, tp: TypeP<'lex>, value: PalData) -> *const PalData {
+        #[cfg(all(unix, feature = "protected_consts"))]
+        let data: *const PalData = self.const_mem.save(value).expect("out of constant mem");
+        #[cfg(not(all(unix, feature = "protected_consts")))]
+        let data: *const PalData = self
+            .data_mem
+            .alloc::<PalData>()
+            .expect("out of data mem")
+            .write(value);
+        self.note_address_data(data, tp);
+        self.add_global(name, tp, data as *mut PalData, READ_FLAG);
+        data
+    }
+
+    ///a word pushing a box of the global at data, the box is not derived from anything since it lives as long as the lex
+    fn add_global(&mut self, name: &'lex str, tp: TypeP<'lex>, data: *mut PalData, permissions: RwT) {
+        let code = self.save_runnable([Code::basic(op::push_var, data as isize)]);
+        let runtime = RuntimeCode::new(
+            Exe::Inlined(&code[..1]),
+            &[],
+            self.save_sig(&[SigItem {
+                tp,
+                permissions: permissions | INDEX_FLAG,
+            }]),
+        );
+        self.add_word(Word {
+            name,
+            runtime,
+            immidate: None,
+            pure: false,
+            traps: None,
+            overload: None,
+        });
+    }
+
+    ///registers a buildin as a word, the code is saved with a ret after it so it can run on its own
+    ///the word is returned so callers can add to it (say how a pure word traps)
+    pub fn add_buildin(
+        &mut self,
+        name: &'lex str,
+        b: Buildin,
+        output_sig: &[SigItem<'lex>],
+        input_sig: &[SigItem<'lex>],
+        pure: bool,
+    ) -> &mut Word<'lex> {
+        let code = self.save_runnable([Code::basic(b, 0)]);
+        let runtime = RuntimeCode::new(
+            Exe::Inlined(&code[..1]),
+            self.save_sig(input_sig),
+            self.save_sig(output_sig),
+        );
+        self.add_word(Word {
+            name,
+            runtime,
+            immidate: None,
+            pure,
+            traps: None,
+            overload: None,
+        });
+        self.words.get_mut(name).expect("was just added")
+    }
+
+    ///stores a word under its name, a word of the same name with other input types is kept as an overload
+    ///one with the same input types is replaced like before
+    pub fn add_word(&mut self, mut word: Word<'lex>) {
+        if let Some(old) = self.words.remove(word.name) {
+            word.overload = if old.same_inputs(&word) {
+                old.overload
+            } else {
+                let old = self.overload_mem.save(old).map_err(|_| ()).expect("out of overload mem");
+                Some(&*old)
+            };
+        }
+        self.words.insert(word.name, word);
+    }
+
+    ///registers a word over a type variable, see [`GenericWord`]
+    pub fn add_generic(
+        &mut self,
+        name: &'lex str,
+        code: &[GenericCode],
+        output_sig: &[GenericItem<'lex>],
+        input_sig: &[GenericItem<'lex>],
+    ) {
+        let input_sig = self.comp_data_mem.save_slice(input_sig).expect("Out of memory in comp data");
+        let output_sig = self.comp_data_mem.save_slice(output_sig).expect("Out of memory in comp data");
+        let code = self.comp_data_mem.save_slice(code).expect("Out of memory in comp data");
+        self.generics.insert(
+            name,
+            GenericWord {
+                name,
+                code,
+                input_sig,
+                output_sig,
+                copies: false,
+            },
+        );
+    }
+
+    ///the word generic turns into for T, made on first use and cached after that
+    ///the specialisation is named "name<T>" and has T's size baked into its code
+    pub fn specialise(&mut self, generic: &GenericWord<'lex>, tp: TypeP<'lex>) -> Word<'lex> {
+        if let Some(word) = self.specialised.get(&(generic.name, tp)) {
+            return word.clone();
+        }
+
+        let mut writer = StackWriter::new(&mut self.comp_data_mem);
+        write!(writer, "{}<{}>", generic.name, tp.name).expect("Out of memory in comp data");
+        let name: &'lex str = writer.finish();
+
+        let input_sig = self.specialise_sig(generic.input_sig, tp);
+        let output_sig = self.specialise_sig(generic.output_sig, tp);
+        let code = save_runnable_in(
+            &mut self.generic_code_mem,
+            generic.code.iter().map(|c| c.specialise(tp)),
+        );
+        let word = Word {
+            name,
+            runtime: RuntimeCode::new(Exe::Inlined(&code[..code.len() - 1]), input_sig, output_sig),
+            immidate: None,
+            pure: false,
+            traps: None,
+            overload: None,
+        };
+        self.specialised.insert((generic.name, tp), word.clone());
+        word
+    }
+
+    fn specialise_sig(&mut self, items: &[GenericItem<'lex>], tp: TypeP<'lex>) -> &'lex [SigItem<'lex>] {
+        //specialising may make new types so the items are gathered before they are saved
+        let mut mem = [MaybeUninit::uninit(); MAX_GENERIC_SIG];
+        let mut sig = StackVec::from_slice(&mut mem);
+        for item in items {
+            let item = SigItem {
+                tp: item.tp.specialise(self, tp),
+                permissions: item.permissions,
+            };
+            sig.push(item).expect("generic signature too long");
+        }
+        self.save_sig(sig.peek_many(sig.len()).expect("len was checked"))
+    }
+
+    ///registers a buildin that runs while compiling, it has no runtime behivior
+    pub fn add_immidate(&mut self, name: &'lex str, b: Buildin) {
+        let code = self.save_runnable([Code::basic(b, 0)]);
+        let header = self
+            .code_mem
+            .save(Code::word(code))
+            .expect("out of code mem");
+        let runtime = RuntimeCode::new(Exe::Inlined(&[]), &[], &[]);
+        self.add_word(Word {
+            name,
+            runtime,
+            immidate: Some(header),
+            pure: false,
+            traps: None,
+            overload: None,
+        });
+    }
+
+    ///registers a word that reads its own input such as s"
+    ///run is called as an unchecked buildin while running and comp as an immidate while compiling
+    pub fn add_parsing(&mut self, name: &'lex str, run: Buildin, comp: Buildin) {
+        self.add_immidate(name, comp);
+        let code = self.save_runnable([Code::basic(run, 0)]);
+        let word = self.words.get_mut(name).expect("was just added");
+        word.runtime = RuntimeCode::new(Exe::Inlined(&code[..1]), &[], &[]);
+    }
+
+    ///saves code followed by a ret, inline it by leaving the ret out
+    fn save_runnable(&mut self, code: impl IntoIterator<Item = Code>) -> &'lex [Code] {
+        save_runnable_in(&mut self.code_mem, code)
+    }
+
+    ///moves a signature into the lex
+    pub fn save_sig(&mut self, sig: &[SigItem<'lex>]) -> &'lex [SigItem<'lex>] {
+        self.comp_data_mem
+            .save_slice(sig)
+            .expect("Out of memory in comp data")
+    }
+}
+
+fn save_runnable_in<'lex>(
+    mem: &mut StackAllocator<'lex, Code>,
+    code: impl IntoIterator<Item = Code>,
+) -> &'lex [Code] {
+    let start = mem.check_point();
+    for c in code.into_iter().chain([Code::basic(op::ret, 0)]) {
+        mem.save(c).expect("out of code mem");
+    }
+    mem.index_checkpoint(start)
 }
 
 impl<'lex> LexEasyMemory<'lex> {
     pub fn new() -> Self {
         Self {
             code_mem: unsafe { MaybeUninit::uninit().assume_init() },
-            data_mem: unsafe { MaybeUninit::uninit().assume_init() },
+            generic_code_mem: unsafe { MaybeUninit::uninit().assume_init() },
+            //codegen dumps data mem as bytes so padding has to be initialized
+            data_mem: [MaybeUninit::new(0); DATA_MEM_SIZE],
             comp_data_mem: unsafe { MaybeUninit::uninit().assume_init() },
             types_mem: unsafe { MaybeUninit::uninit().assume_init() },
+            overload_mem: unsafe { MaybeUninit::uninit().assume_init() },
         }
     }
 
     pub fn make_lex(&'lex mut self) -> Lex<'lex> {
         Lex {
             code_mem: StackAllocator::new(&mut self.code_mem),
+            generic_code_mem: StackAllocator::new(&mut self.generic_code_mem),
             data_mem: StackAlloc::from_slice(&mut self.data_mem),
+            #[cfg(all(unix, feature = "protected_consts"))]
+            const_mem: ProtectedAlloc::new(CONST_MEM_SIZE).expect("could not map constant memory"),
             comp_data_mem: StackAlloc::from_slice(&mut self.comp_data_mem),
             types_mem: StackAllocator::new(&mut self.types_mem),
             type_map: PalHash::new(),
+            type_names: PalHash::new(),
             words: PalHash::new(),
+            overload_mem: StackAllocator::new(&mut self.overload_mem),
+            generics: PalHash::new(),
+            specialised: PalHash::new(),
+            destructors: PalHash::new(),
+            address_data: PalHash::new(),
         }
     }
 }
@@ -69,6 +634,90 @@ impl<'lex> Default for LexEasyMemory<'lex> {
     }
 }
 
+// ───────────── PROTECTED ALLOC (read only once written) ────────────────
+///a bump arena on pages of its own that are only writable while something is being saved
+///the pages are unmapped when it is dropped so whatever is saved lives as long as it does
+#[cfg(all(unix, feature = "protected_consts"))]
+pub struct ProtectedAlloc<'a> {
+    base: *mut u8,
+    len: usize,
+    cap: usize,
+    page: usize,
+    _ph: PhantomData<&'a [u8]>,
+}
+
+#[cfg(all(unix, feature = "protected_consts"))]
+impl<'lex> ProtectedAlloc<'lex> {
+    ///maps at least size bytes of read only memory
+    pub fn new(size: usize) -> Option<Self> {
+        use libc::{_SC_PAGESIZE, MAP_ANON, MAP_FAILED, MAP_PRIVATE, PROT_READ, mmap, sysconf};
+        unsafe {
+            let page = sysconf(_SC_PAGESIZE) as usize;
+            let cap = size.max(1).div_ceil(page) * page;
+            let base = mmap(core::ptr::null_mut(), cap, PROT_READ, MAP_PRIVATE | MAP_ANON, -1, 0);
+            if base == MAP_FAILED {
+                return None;
+            }
+            Some(Self {
+                base: base as *mut u8,
+                len: 0,
+                cap,
+                page,
+                _ph: PhantomData,
+            })
+        }
+    }
+
+    ///copies value in and seals the pages it landed on again
+    pub fn save<T: Copy>(&mut self, value: T) -> Option<&T> {
+        use libc::{PROT_READ, PROT_WRITE, c_void, mprotect};
+        let pad = self.base.wrapping_add(self.len).align_offset(align_of::<T>());
+        let start = self.len + pad;
+        let end = start + size_of::<T>();
+        if end > self.cap {
+            return None;
+        }
+        unsafe {
+            let first = start / self.page * self.page;
+            let span = end.div_ceil(self.page) * self.page - first;
+            let pages = self.base.add(first) as *mut c_void;
+            assert_eq!(mprotect(pages, span, PROT_READ | PROT_WRITE), 0, "could not unseal constant memory");
+            let p = self.base.add(start) as *mut T;
+            p.write(value);
+            assert_eq!(mprotect(pages, span, PROT_READ), 0, "could not seal constant memory");
+            self.len = end;
+            Some(&*p)
+        }
+    }
+
+    /// the live part of the arena
+    #[inline]
+    pub fn bytes(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.base, self.len) }
+    }
+
+    /// the offset of p inside the live part of the arena
+    #[inline]
+    pub fn offset_of<T>(&self, p: *const T) -> Option<usize> {
+        let base = self.base as usize;
+        let addr = p as usize;
+        if addr >= base && addr < base + self.len {
+            Some(addr - base)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(all(unix, feature = "protected_consts"))]
+impl Drop for ProtectedAlloc<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.base as *mut libc::c_void, self.cap);
+        }
+    }
+}
+
 // ───────────── STACK ALLOC (untyped, bytes) ────────────────────────────
 pub struct StackAlloc<'a>(StackVec<'a, u8>);
 
@@ -98,6 +747,58 @@ impl<'lex> StackAlloc<'lex> {
         }
     }
 
+    ///copies a slice into the arena
+    #[inline]
+    pub fn save_slice<T: Copy>(&mut self, src: &[T]) -> Option<&'lex mut [T]> {
+        let curr_len = self.0.len();
+        let curr_ptr = unsafe { self.0.get_base().add(curr_len) };
+        let pad = curr_ptr.align_offset(align_of::<T>());
+
+        let total = pad + size_of_val(src);
+        unsafe {
+            self.0.alloc(total)?;
+            let p = curr_ptr.add(pad) as *mut T;
+            p.copy_from_nonoverlapping(src.as_ptr(), src.len());
+            Some(slice::from_raw_parts_mut(p, src.len()))
+        }
+    }
+
+    ///saves every item of iter next to each other
+    #[inline]
+    pub fn save_iter<T: Copy>(&mut self, iter: impl Iterator<Item = T>) -> Option<&'lex mut [T]> {
+        let mut start: *mut T = core::ptr::NonNull::dangling().as_ptr();
+        let mut len = 0;
+        for x in iter {
+            //same type back to back so there is never padding after the first
+            let p = self.alloc::<T>()?.write(x) as *mut T;
+            if len == 0 {
+                start = p;
+            }
+            len += 1;
+        }
+        unsafe { Some(slice::from_raw_parts_mut(start, len)) }
+    }
+
+    /// the live part of the arena
+    /// # Safety
+    /// every byte of the backing memory was initialized (data_mem is zeroed up front for this)
+    #[inline]
+    pub unsafe fn bytes(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.0.get_base(), self.0.len()) }
+    }
+
+    /// the offset of p inside the live part of the arena
+    #[inline]
+    pub fn offset_of<T>(&self, p: *const T) -> Option<usize> {
+        let base = self.0.get_base() as usize;
+        let addr = p as usize;
+        if addr >= base && addr < base + self.0.len() {
+            Some(addr - base)
+        } else {
+            None
+        }
+    }
+
     #[inline]
     pub fn check_point(&self) -> StackAllocCheckPoint {
         StackAllocCheckPoint(self.0.len())
@@ -167,7 +868,7 @@ impl<'me, 'lex> StackWriter<'me, 'lex> {
 pub struct StackAllocator<'a, T>(StackVec<'a, T>);
 
 #[derive(Debug, Clone, Copy)]
-pub struct StackAllocatorCheckPoint(usize); // length in elements
+pub struct StackAllocatorCheckPoint(pub usize); // length in elements
 
 impl<'a, T> StackAllocator<'a, T> {
     #[inline]
@@ -201,11 +902,20 @@ impl<'a, T> StackAllocator<'a, T> {
     #[inline]
     pub fn try_index_checkpoint(&self, cp: StackAllocatorCheckPoint) -> Option<&'a [T]> {
         let live = self.0.len() - cp.0;
+        if live == 0 {
+            return Some(&[]);
+        }
         let addr = self.0.peek_many(live)?.as_ptr().addr();
         let p = self.0.peek_raw()?.with_addr(addr);
         unsafe { Some(slice::from_raw_parts(p, live)) }
     }
 
+    ///where the first element saved after the checkpoint is (or will be) placed
+    #[inline]
+    pub fn checkpoint_ptr(&self, cp: StackAllocatorCheckPoint) -> *mut T {
+        self.0.get_base().wrapping_add(cp.0)
+    }
+
     #[inline]
     pub fn index_checkpoint(&self, cp: StackAllocatorCheckPoint) -> &'a [T] {
         self.try_index_checkpoint(cp)
@@ -311,12 +1021,7 @@ mod tests {
         assert_eq!(a4 % align_of::<[u64; 3]>(), 0, "array mis-aligned");
 
         /* ── near-exhaustion check: fill what’s left in 8-byte chunks ─ */
-        loop {
-            match arena.alloc::<u64>() {
-                Some(_) => continue,
-                None => break, // expected out-of-memory
-            }
-        }
+        while arena.alloc::<u64>().is_some() {} // runs until out-of-memory
         assert!(arena.alloc::<u64>().is_none(), "OOM must remain OOM");
     }
 
@@ -525,7 +1230,13 @@ impl<'a, T> From<&'a mut [T]> for DelayedSlice<'a, T> {
     }
 }
 
-impl<T> DelayedSlice<'_, T> {
+impl<'a, T> DelayedSlice<'a, T> {
+    ///the slice for as long as the memory lives
+    #[inline]
+    pub fn as_slice(&self) -> &'a [T] {
+        unsafe { slice::from_raw_parts(self.ptr, self.len) }
+    }
+
     ///# Safety
     /// no safe code is allowed to deref it untill a proper offset_from is called
     pub unsafe fn new_offset(base: *const u8, slice: &[T]) -> Self {
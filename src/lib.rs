@@ -17,9 +17,11 @@ pub use no_std_io::io::{self,Write};
 pub mod stack;
 
 pub mod buildins;
+pub mod codegen_c;
 pub mod input;
 pub mod ir;
 pub mod lex;
+pub mod prelude;
 pub mod types;
 pub mod vm;
 
@@ -29,6 +31,7 @@ pub mod test;
 type PalHash<K, V> = HashMap<K, V>; // use default hasher and allocator
 
 pub type PalInt = i64;
+pub type PalFloat = f64;
 pub type PalBool = bool;
 pub const TRUE: PalBool = true;
 pub const FALSE: PalBool = false;
@@ -37,8 +40,9 @@ pub const FALSE: PalBool = false;
 #[derive(Copy, Clone)]
 pub union PalData {
     int: PalInt,
+    float: PalFloat,
     bool: PalBool,
-    // ptr:*mut PalData,
+    ptr: *mut PalData,
     code: *const Code,
 }
 
@@ -48,7 +52,42 @@ pub enum PalError<'a> {
     // StackOverFlow,
     SigError(SigError<'a>),
     Io(io::Error),
-    Missingword(&'a str)
+    Missingword(&'a str),
+    MissingName,
+    NotCompiling,
+    LeftOnStack(&'a str),
+    ///an output of the word is above one of its inputs
+    UnorderedOutputs(&'a str),
+    ///a stack effect that could not be read, holds the offending word
+    BadSig(&'a str),
+    ///a cluster field that could not be read, holds the offending word
+    BadField(&'a str),
+    ///a s" literal that ran out of input before its closing quote
+    UnclosedStr,
+    ///a match that could not be compiled, holds the offending word
+    BadMatch(&'a str),
+    ///an arm of a match that did not leave the stack as it found it, holds its case
+    UnevenArm(&'a str),
+    ///a match with no arm for a case, holds the case
+    NonExhaustive(&'a str),
+    ///a name that was used as a type but is not one
+    UnknownType(&'a str),
+    ///a generic word whose type could not be worked out from the stack, spell it as name<T>
+    UnboundGeneric(&'a str),
+    ///an unsafe{ region that was closed without being open or left open at the end of a word
+    BadUnsafe(&'a str),
+    ///an xt of a word that only acts while compiling, holds its name
+    NoXt(&'a str),
+    ///a [: ;] quotation opened inside a definition or closed by the wrong word, holds the offending word
+    BadQuotation(&'a str),
+    ///a { } declaration of locals that could not be read or is not allowed where it is, holds the offending word
+    BadLocal(&'a str),
+    ///a variable or constant declaration that could not be read, holds the offending word
+    BadGlobal(&'a str),
+    ///a word that can not be a destructor since it does not take just a box of the type, holds its name
+    BadDestructor(&'a str),
+    ///a type owning memory where it would be copied byte for byte (a copying word, a field or a payload), holds the type name
+    MoveOnly(&'a str),
 }
 
 impl<'a> From<SigError<'a>> for PalError<'a>{
@@ -4,6 +4,7 @@ use pal_forth::vm::CompMode;
 use pal_forth::ir::CompEasyMemory;
 use pal_forth::lex::LexEasyMemory;
 use pal_forth::vm::VmEasyMemory;
+use pal_forth::prelude::load_prelude;
 
 
 fn main() -> () {
@@ -13,6 +14,7 @@ fn main() -> () {
 
 
     let mut lex = ManuallyDrop::new(lex_mem.make_lex());
+    load_prelude(&mut lex);
     let mut vm = vm_mem.make_vm();
     let mut comp = comp_mem.make_comp(&mut lex);
 
@@ -0,0 +1,318 @@
+//! the basic types and words every lex starts with
+
+use crate::PalBool;
+use crate::PalData;
+use crate::PalFloat;
+use crate::PalInt;
+use crate::ir::TrapCheck;
+use crate::buildins::SIZED_INTS;
+use crate::buildins::op;
+use crate::ir::GenericCode;
+use crate::ir::GenericItem;
+use crate::ir::GenericParam;
+use crate::ir::GenericTp;
+use crate::lex::Lex;
+use crate::types::INDEX_FLAG;
+use crate::types::READ_FLAG;
+use crate::types::SigItem;
+use crate::types::TypeInner;
+use crate::types::TypeP;
+use crate::types::RwT;
+use crate::types::UNIQUE_FLAG;
+use crate::types::WRITE_FLAG;
+use crate::vm::Buildin;
+
+fn r(tp: TypeP<'_>) -> SigItem<'_> {
+    SigItem {
+        tp,
+        permissions: READ_FLAG,
+    }
+}
+
+fn w(tp: TypeP<'_>) -> SigItem<'_> {
+    SigItem {
+        tp,
+        permissions: WRITE_FLAG,
+    }
+}
+
+fn rw(tp: TypeP<'_>) -> SigItem<'_> {
+    SigItem {
+        tp,
+        permissions: READ_FLAG | WRITE_FLAG,
+    }
+}
+
+///whether lhs op rhs traps given the checked version of op
+fn int_trap(v: &[PalData], checked: fn(PalInt, PalInt) -> Option<PalInt>) -> bool {
+    unsafe { checked(v[0].int, v[1].int).is_none() }
+}
+
+pub fn load_prelude(lex: &mut Lex<'_>) {
+    let int = lex.add_basic_type("int", size_of::<PalInt>() as i32, 1);
+    let bool = lex.add_basic_type("bool", size_of::<PalBool>() as i32, 1);
+    let float = lex.add_basic_type("float", size_of::<PalFloat>() as i32, 1);
+
+    //the ones that can trap (overflow, div by zero, shifting too far) only fold when these say they wont
+    let arith: [(&str, Buildin, Option<TrapCheck>); 10] = [
+        ("int_add", op::int_add, Some(|v| int_trap(v, PalInt::checked_add))),
+        ("int_sub", op::int_sub, Some(|v| int_trap(v, PalInt::checked_sub))),
+        ("int_mul", op::int_mul, Some(|v| int_trap(v, PalInt::checked_mul))),
+        ("int_div", op::int_div, Some(|v| int_trap(v, PalInt::checked_div))),
+        ("int_mod", op::int_mod, Some(|v| int_trap(v, PalInt::checked_rem))),
+        ("int_shl", op::int_shl, Some(|v| int_trap(v, |a, b| a.checked_shl(b.try_into().ok()?)))),
+        ("int_shr", op::int_shr, Some(|v| int_trap(v, |a, b| a.checked_shr(b.try_into().ok()?)))),
+        ("int_and", op::int_and, None),
+        ("int_or", op::int_or, None),
+        ("int_xor", op::int_xor, None),
+    ];
+    for (name, f, traps) in arith {
+        lex.add_buildin(name, f, &[rw(int)], &[r(int)], true).traps = traps;
+    }
+
+    //these never trap so all of them can be folded
+    let families: [(&str, Buildin); 10] = [
+        ("int_add_wrapping", op::int_add_wrapping),
+        ("int_sub_wrapping", op::int_sub_wrapping),
+        ("int_mul_wrapping", op::int_mul_wrapping),
+        ("int_div_wrapping", op::int_div_wrapping),
+        ("int_mod_wrapping", op::int_mod_wrapping),
+        ("int_add_saturating", op::int_add_saturating),
+        ("int_sub_saturating", op::int_sub_saturating),
+        ("int_mul_saturating", op::int_mul_saturating),
+        ("int_div_saturating", op::int_div_saturating),
+        ("int_mod_saturating", op::int_mod_saturating),
+    ];
+    for (name, f) in families {
+        lex.add_buildin(name, f, &[rw(int)], &[r(int)], true);
+    }
+
+    let checked: [(&str, Buildin); 5] = [
+        ("int_add_checked", op::int_add_checked),
+        ("int_sub_checked", op::int_sub_checked),
+        ("int_mul_checked", op::int_mul_checked),
+        ("int_div_checked", op::int_div_checked),
+        ("int_mod_checked", op::int_mod_checked),
+    ];
+    for (name, f) in checked {
+        lex.add_buildin(name, f, &[rw(int), w(bool)], &[r(int)], true);
+    }
+
+    let cmp: [(&str, Buildin); 6] = [
+        ("int_eq", op::int_eq),
+        ("int_neq", op::int_neq),
+        ("int_smaller", op::int_smaller),
+        ("int_bigger", op::int_bigger),
+        ("int_le", op::int_le),
+        ("int_ge", op::int_ge),
+    ];
+    for (name, f) in cmp {
+        lex.add_buildin(name, f, &[w(bool)], &[r(int), r(int)], true);
+    }
+
+    let logic: [(&str, Buildin); 3] = [
+        ("bool_and", op::bool_and),
+        ("bool_or", op::bool_or),
+        ("bool_xor", op::bool_xor),
+    ];
+    for (name, f) in logic {
+        lex.add_buildin(name, f, &[rw(bool)], &[r(bool)], true);
+    }
+    lex.add_buildin("bool_not", op::bool_not, &[rw(bool)], &[], true);
+
+    //floats never trap, division by zero is inf or nan
+    let float_arith: [(&str, Buildin); 5] = [
+        ("float_add", op::float_add),
+        ("float_sub", op::float_sub),
+        ("float_mul", op::float_mul),
+        ("float_div", op::float_div),
+        ("float_mod", op::float_mod),
+    ];
+    for (name, f) in float_arith {
+        lex.add_buildin(name, f, &[rw(float)], &[r(float)], true);
+    }
+
+    //the operators are overloads of both, the types on the stack pick which one runs
+    //the int ones trap like the int words they stand for
+    let ops: [(&str, &str, Buildin, Buildin); 4] = [
+        ("+", "int_add", op::int_add, op::float_add),
+        ("-", "int_sub", op::int_sub, op::float_sub),
+        ("*", "int_mul", op::int_mul, op::float_mul),
+        ("/", "int_div", op::int_div, op::float_div),
+    ];
+    for (name, int_name, int_f, float_f) in ops {
+        let traps = lex.words[int_name].traps;
+        lex.add_buildin(name, int_f, &[rw(int)], &[r(int)], true).traps = traps;
+        lex.add_buildin(name, float_f, &[rw(float)], &[r(float)], true);
+    }
+
+    let float_cmp: [(&str, Buildin); 6] = [
+        ("float_eq", op::float_eq),
+        ("float_neq", op::float_neq),
+        ("float_smaller", op::float_smaller),
+        ("float_bigger", op::float_bigger),
+        ("float_le", op::float_le),
+        ("float_ge", op::float_ge),
+    ];
+    for (name, f) in float_cmp {
+        lex.add_buildin(name, f, &[w(bool)], &[r(float), r(float)], true);
+    }
+
+    lex.add_buildin("int_to_float", op::int_to_float, &[w(float)], &[r(int)], true);
+    lex.add_buildin("float_to_int", op::float_to_int, &[w(int)], &[r(float)], true);
+
+    #[cfg(feature = "libm")]
+    {
+        let math: [(&str, Buildin); 7] = [
+            ("float_sqrt", op::float_sqrt),
+            ("float_floor", op::float_floor),
+            ("float_ceil", op::float_ceil),
+            ("float_sin", op::float_sin),
+            ("float_cos", op::float_cos),
+            ("float_exp", op::float_exp),
+            ("float_ln", op::float_ln),
+        ];
+        for (name, f) in math {
+            lex.add_buildin(name, f, &[rw(float)], &[], true);
+        }
+        lex.add_buildin("float_pow", op::float_pow, &[rw(float)], &[r(float)], true);
+    }
+
+    //sized ints, int doubles as i64
+    lex.type_names.insert("i64", int);
+    for (name, size, words, traps) in &SIZED_INTS {
+        let tp = lex.add_basic_type(name, *size, 1);
+        let (arith, rest) = words.split_at(8);
+        let (cmp, conv) = rest.split_at(6);
+        //add sub mul div mod trap like the int words, and or xor never do
+        for (i, (word, f)) in arith.iter().enumerate() {
+            lex.add_buildin(word, *f, &[rw(tp)], &[r(tp)], true).traps = traps.get(i).copied();
+        }
+        for (word, f) in cmp {
+            lex.add_buildin(word, *f, &[w(bool)], &[r(tp), r(tp)], true);
+        }
+        let [from, from_checked, to, to_checked] = conv else {
+            unreachable!()
+        };
+        lex.add_buildin(from.0, from.1, &[w(tp)], &[r(int)], true);
+        lex.add_buildin(from_checked.0, from_checked.1, &[w(tp), w(bool)], &[r(int)], true);
+        lex.add_buildin(to.0, to.1, &[w(int)], &[r(tp)], true);
+        lex.add_buildin(to_checked.0, to_checked.1, &[w(int), w(bool)], &[r(tp)], true);
+    }
+
+    //strings are byte slices that are known to be UTF-8
+    //a newtype without wrap so bytes only turn into a str through str_from_bytes
+    //they hold pointers so none of their words are folded
+    let u8_t = lex.named_type("u8").expect("sized ints were just added");
+    let bytes = lex.add_array(u8_t, None);
+    let str = TypeInner::Newtype(bytes, "str").get_type_ref(lex);
+    lex.type_names.insert("str", str);
+    let view = SigItem {
+        tp: str,
+        permissions: READ_FLAG | INDEX_FLAG,
+    };
+
+    lex.add_buildin("str_len", op::str_len, &[w(int)], &[r(str)], false);
+    lex.add_buildin("str_byte_at", op::str_byte_at, &[w(u8_t)], &[r(str), r(int)], false);
+    lex.add_buildin("str_char_at", op::str_char_at, &[w(int)], &[r(str), r(int)], false);
+    let str_cmp: [(&str, Buildin); 4] = [
+        ("str_eq", op::str_eq),
+        ("str_neq", op::str_neq),
+        ("str_smaller", op::str_smaller),
+        ("str_bigger", op::str_bigger),
+    ];
+    for (name, f) in str_cmp {
+        lex.add_buildin(name, f, &[w(bool)], &[r(str), r(str)], false);
+    }
+    //these write the text and the str itself into the buffer so it stays borrowed while the str lives
+    lex.add_buildin("str_concat", op::str_concat, &[view], &[rw(bytes), r(str), r(str)], false);
+    lex.add_buildin("str_from_int", op::str_from_int, &[view], &[rw(bytes), r(int)], false);
+    lex.add_buildin("str_from_float", op::str_from_float, &[view], &[rw(bytes), r(float)], false);
+    lex.add_buildin("str_from_bytes", op::str_from_bytes, &[w(bool), view], &[r(bytes)], false);
+    lex.add_buildin("str_to_int", op::str_to_int, &[w(int), w(bool)], &[r(str)], false);
+    lex.add_buildin("str_to_float", op::str_to_float, &[w(float), w(bool)], &[r(str)], false);
+    lex.add_parsing("s\"", op::str_literal, op::str_literal_comp);
+
+    //generic words are specialised for the T on the stack (or named as copy<T>)
+    //with the size of T baked into the buildin they run
+    let var = |permissions| GenericItem {
+        tp: GenericTp::Var,
+        permissions,
+    };
+    let by_stride = |f| [GenericCode {
+        f,
+        param: GenericParam::Stride,
+    }];
+    const RWU: RwT = READ_FLAG | WRITE_FLAG | UNIQUE_FLAG;
+    lex.add_generic("copy", &by_stride(op::inject_non_unique), &[var(WRITE_FLAG)], &[var(READ_FLAG)]);
+    lex.add_generic("swap", &by_stride(op::swap_bytes), &[var(RWU), var(RWU)], &[]);
+    let slice = GenericItem {
+        tp: GenericTp::SliceOf,
+        permissions: 0,
+    };
+    let index = GenericItem {
+        tp: GenericTp::Known(int),
+        permissions: READ_FLAG,
+    };
+    lex.add_generic("array_at", &by_stride(op::slice_at), &[var(RWU | INDEX_FLAG)], &[slice, index]);
+
+    //raw pointers, the signatures have a ptr(T) in them so they need unsafe{ }
+    let ptr = GenericItem {
+        tp: GenericTp::PtrTo,
+        permissions: READ_FLAG,
+    };
+    lex.add_generic("@", &by_stride(op::ptr_load), &[var(WRITE_FLAG)], &[ptr]);
+    lex.add_generic("!", &by_stride(op::ptr_store), &[], &[var(READ_FLAG), ptr]);
+    let fixed = |f| [GenericCode {
+        f,
+        param: GenericParam::Fixed(0),
+    }];
+    let out_ptr = GenericItem {
+        tp: GenericTp::PtrTo,
+        permissions: WRITE_FLAG,
+    };
+    lex.add_generic("addr", &fixed(op::ptr_of), &[out_ptr], &[var(READ_FLAG)]);
+    //these duplicate a T so they are refused for types owning memory, swap only trades owners
+    for name in ["copy", "@", "!"] {
+        lex.generics.get_mut(name).expect("added above").copies = true;
+    }
+
+    //heap boxes own what they point at, free<T> is their destructor so locals clean up after themselves
+    //alloc and free need the box unique so nothing opened from it can outlive the memory
+    #[cfg(feature = "heap")]
+    {
+        let boxed = |permissions| GenericItem {
+            tp: GenericTp::BoxOf,
+            permissions,
+        };
+        lex.add_generic("alloc", &by_stride(op::heap_alloc), &[], &[boxed(RWU)]);
+        lex.add_generic("free", &by_stride(op::heap_free), &[], &[boxed(RWU)]);
+        lex.add_generic(
+            "open",
+            &fixed(op::box_open),
+            &[var(RWU | INDEX_FLAG)],
+            &[boxed(0)],
+        );
+    }
+    lex.add_parsing("unsafe{", op::unsafe_begin, op::unsafe_begin);
+    lex.add_parsing("}", op::unsafe_end, op::unsafe_end);
+
+    lex.add_buildin(":", op::colon, &[], &[], false);
+    lex.add_immidate(";", op::semicolon);
+    lex.add_parsing("{", op::locals, op::locals);
+    lex.add_parsing("'", op::tick, op::tick_comp);
+    lex.add_parsing("execute", op::execute, op::execute_comp);
+    lex.add_parsing("[:", op::quote_begin, op::quote_begin);
+    lex.add_immidate(";]", op::quote_end);
+    lex.add_buildin("cluster", op::cluster, &[], &[], false);
+    lex.add_buildin("variant", op::variant, &[], &[], false);
+    lex.add_parsing("drop", op::drop_box, op::drop_box_comp);
+    lex.add_buildin("variable", op::variable, &[], &[], false);
+    lex.add_buildin("constant", op::constant, &[], &[], false);
+    lex.add_buildin("destructor", op::destructor, &[], &[], false);
+    lex.add_buildin("alias", op::alias, &[], &[], false);
+    lex.add_buildin("newtype", op::newtype, &[], &[], false);
+    lex.add_immidate("match", op::match_);
+    lex.add_immidate("case", op::case);
+    lex.add_immidate("endmatch", op::endmatch);
+}
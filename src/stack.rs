@@ -357,6 +357,25 @@ impl<'mem, T> StackRef<'mem, T> {
     pub fn get_head(&self) -> *mut T {
         self.head
     }
+
+    /// one past the highest live element
+    #[inline(always)]
+    pub fn get_above(&self) -> *mut T {
+        self.above
+    }
+
+    /// lowest address in the backing buffer
+    #[inline(always)]
+    pub fn get_end(&self) -> *mut T {
+        self.end
+    }
+
+    /// # Safety
+    /// head must be in end..=above and everything from head up must be initialized
+    #[inline(always)]
+    pub unsafe fn set_head(&mut self, head: *mut T) {
+        self.head = head
+    }
 }
 /*──────────────────── iterator ─────────────────────────*/
 impl<T> Iterator for StackRef<'_, T> {
@@ -916,7 +935,7 @@ impl<T> StackVec<'_, T> {
     }
 
     #[inline]
-    pub fn peek_mut(&self) -> Option<&mut T> {
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
         if self.len == 0 {
             None
         } else {
@@ -1014,6 +1033,7 @@ impl Write for StackVec<'_, u8> {
 /*──────────────────── tests ───────────────────────────*/
 
 #[cfg(test)]
+#[allow(clippy::items_after_test_module)]
 mod tests {
     use super::*;
 
@@ -1079,6 +1099,8 @@ mod tests {
 
 /*────────── OS STACK ──────────*/
 
+/// # Safety
+/// the returned stack is never unmapped and relies on the OS growing it on access
 #[cfg(all(unix, feature = "std"))]
 pub unsafe fn new_os_stack<T>() -> Option<StackRef<'static, T>> {
     unsafe {
@@ -1141,6 +1163,8 @@ fn test_stack_grows_down_on_access() {
     }
 }
 
+/// # Safety
+/// the returned stack is never freed and must not be accessed past its guard page
 #[cfg(all(windows, feature = "std"))]
 pub unsafe fn new_os_stack<T>() -> Option<StackRef<'static, T>> {
     unsafe {
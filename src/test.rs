@@ -2,18 +2,10 @@ use no_std_io::io::Write;
 use crate::FALSE;
 use crate::PalData;
 use crate::TRUE;
-use crate::buildins::frame_alloc;
-use crate::buildins::frame_free;
-use crate::buildins::inject;
-use crate::buildins::param_drop;
-use crate::buildins::pick;
-use crate::buildins::push_local;
-use crate::buildins::ret;
 use crate::buildins::*;
 use crate::vm::Code;
 use crate::vm::VmEasyMemory;
 use core::cell::UnsafeCell;
-use core::panic::AssertUnwindSafe;
 
 #[test]
 fn round_trip_inject() {
@@ -21,20 +13,20 @@ fn round_trip_inject() {
     let mut vm = mem.make_vm();
 
     let code = [
-        Code::basic(frame_alloc, 5),
+        Code::basic(op::frame_alloc, 5),
         //inject to the stack
-        Code::basic(push_local, 0),
-        Code::basic(pick, 1),
-        Code::basic(inject, 5 * size_of::<PalData>() as isize),
-        Code::basic(param_drop, 1),
+        Code::basic(op::push_local, 0),
+        Code::basic(op::pick, 1),
+        Code::basic(op::inject, 5 * size_of::<PalData>() as isize),
+        Code::basic(op::param_drop, 1),
         //inject back out
-        Code::basic(pick, 1),
-        Code::basic(push_local, 0),
-        Code::basic(inject, 5 * size_of::<PalData>() as isize),
-        Code::basic(param_drop, 1),
+        Code::basic(op::pick, 1),
+        Code::basic(op::push_local, 0),
+        Code::basic(op::inject, 5 * size_of::<PalData>() as isize),
+        Code::basic(op::param_drop, 1),
         //epilogue
-        Code::basic(frame_free, 5),
-        Code::basic(ret, 0),
+        Code::basic(op::frame_free, 5),
+        Code::basic(op::ret, 0),
     ];
 
     let word = Code::word(&code);
@@ -73,11 +65,11 @@ fn stack_underflow_panics() {
     let mut mem = VmEasyMemory::<8>::new();
     let mut vm = mem.make_vm();
 
-    let prog = [Code::basic(param_drop, 1), Code::basic(ret, 0)];
+    let prog = [Code::basic(op::param_drop, 1), Code::basic(op::ret, 0)];
     let word = Code::word(&prog);
 
     extern crate std;
-    let res = std::panic::catch_unwind(AssertUnwindSafe(|| unsafe {
+    let res = std::panic::catch_unwind(core::panic::AssertUnwindSafe(|| unsafe {
         vm.execute_code(&word as *const Code); // empty stack → should panic
     }));
 
@@ -91,7 +83,7 @@ fn core_operations() {
     let mut vm = mem.make_vm();
 
     /* ---- pick 0 (duplicate top) ---- */
-    let dup_code = [Code::basic(pick, 0), Code::basic(ret, 0)];
+    let dup_code = [Code::basic(op::pick, 0), Code::basic(op::ret, 0)];
     let dup_word = Code::word(&dup_code);
 
     let canary = 321usize as *mut _;
@@ -103,9 +95,9 @@ fn core_operations() {
 
     /* ---- branch-if test ---- */
     let maybe_dup_code = [
-        Code::basic(branch, 1),
-        Code::basic(pick, 0),
-        Code::basic(ret, 0),
+        Code::basic(op::branch, 1),
+        Code::basic(op::pick, 0),
+        Code::basic(op::ret, 0),
     ];
 
     let maybe_dup = Code::word(&maybe_dup_code);
@@ -134,11 +126,11 @@ fn integer_arithmetics() {
     let mut b = UnsafeCell::new(PalData { int: 67 });
 
     let mut arith_code = UnsafeCell::new([
-        Code::basic(pick, 1),
-        Code::basic(pick, 1),
-        Code::basic(int_add, 0), // placeholder – overwritten each run
-        Code::basic(param_drop, 1),
-        Code::basic(ret, 0),
+        Code::basic(op::pick, 1),
+        Code::basic(op::pick, 1),
+        Code::basic(op::int_add, 0), // placeholder – overwritten each run
+        Code::basic(op::param_drop, 1),
+        Code::basic(op::ret, 0),
     ]);
     let arith_word = Code::word_raw(arith_code.get() as *const _);
 
@@ -146,10 +138,10 @@ fn integer_arithmetics() {
     vm.param_stack.push(b.get()).unwrap();
 
     macro_rules! run {
-        ($builtin:path, $l:expr, $r:expr, $expect:expr) => {{
+        ($builtin:ident, $l:expr, $r:expr, $expect:expr) => {{
             *a.get_mut() = PalData { int: $l };
             *b.get_mut() = PalData { int: $r };
-            arith_code.get_mut()[2] = Code::basic($builtin, 0);
+            arith_code.get_mut()[2] = Code::basic(op::$builtin, 0);
 
             unsafe {
                 vm.execute_code(&arith_word as *const Code);
@@ -185,12 +177,12 @@ fn bool_and_comparisons() {
     let mut y = UnsafeCell::new(PalData { int: 0 });
 
     let mut cmp_code = UnsafeCell::new([
-        Code::basic(pick, 2),
-        Code::basic(pick, 2),
-        Code::basic(pick, 2),
-        Code::basic(int_eq, 0), // placeholder
-        Code::basic(param_drop, 1),
-        Code::basic(ret, 0),
+        Code::basic(op::pick, 2),
+        Code::basic(op::pick, 2),
+        Code::basic(op::pick, 2),
+        Code::basic(op::int_eq, 0), // placeholder
+        Code::basic(op::param_drop, 1),
+        Code::basic(op::ret, 0),
     ]);
     let cmp_word = Code::word_raw(cmp_code.get() as *const _);
 
@@ -199,10 +191,10 @@ fn bool_and_comparisons() {
     vm.param_stack.push(y.get() as *mut _).unwrap();
 
     macro_rules! cmp {
-        ($builtin:path, $l:expr, $r:expr, $ok:expr) => {{
+        ($builtin:ident, $l:expr, $r:expr, $ok:expr) => {{
             *x.get_mut() = PalData { int: $l };
             *y.get_mut() = PalData { int: $r };
-            (cmp_code.get_mut())[3] = Code::basic($builtin, 0);
+            (cmp_code.get_mut())[3] = Code::basic(op::$builtin, 0);
             unsafe {
                 vm.execute_code(&cmp_word as *const Code);
                 assert_eq!(*res.get() == TRUE, $ok);
@@ -226,11 +218,11 @@ fn bool_and_comparisons() {
     let mut rhs = UnsafeCell::new(PalData { bool: FALSE });
 
     let mut bool_code = UnsafeCell::new([
-        Code::basic(pick, 1),
-        Code::basic(pick, 1),
-        Code::basic(bool_and, 0), // placeholder
-        Code::basic(param_drop, 1),
-        Code::basic(ret, 0),
+        Code::basic(op::pick, 1),
+        Code::basic(op::pick, 1),
+        Code::basic(op::bool_and, 0), // placeholder
+        Code::basic(op::param_drop, 1),
+        Code::basic(op::ret, 0),
     ]);
     let bool_word = Code::word_raw(bool_code.get() as *const _);
 
@@ -238,10 +230,10 @@ fn bool_and_comparisons() {
     vm.param_stack.push(rhs.get() as *mut _).unwrap();
 
     macro_rules! logic {
-        ($builtin:path, $l:expr, $r:expr, $expect:expr) => {{
+        ($builtin:ident, $l:expr, $r:expr, $expect:expr) => {{
             *lhs.get_mut() = PalData { bool: $l };
             *rhs.get_mut() = PalData { bool: $r };
-            (bool_code.get_mut())[2] = Code::basic($builtin, 0);
+            (bool_code.get_mut())[2] = Code::basic(op::$builtin, 0);
             unsafe {
                 vm.execute_code(&bool_word as *const Code);
                 assert_eq!((*lhs.get()).bool == TRUE, $expect);
@@ -259,9 +251,9 @@ fn bool_and_comparisons() {
     /* ---- unary NOT ---- */
     let mut v = UnsafeCell::new(TRUE);
     let not_code = [
-        Code::basic(bool_not, 0),
-        Code::basic(param_drop, 1),
-        Code::basic(ret, 0),
+        Code::basic(op::bool_not, 0),
+        Code::basic(op::param_drop, 1),
+        Code::basic(op::ret, 0),
     ];
     let not_word = Code::word(&not_code);
 
@@ -280,13 +272,13 @@ fn nested_word_dup() {
     let mut vm = mem.make_vm();
 
     /* ---- inner word: DUP (pick 0) ---- */
-    let dup_code = [Code::basic(pick, 0), Code::basic(ret, 0)];
+    let dup_code = [Code::basic(op::pick, 0), Code::basic(op::ret, 0)];
     let dup_word = Code::word(&dup_code); // f == None → user-defined
 
     /* ---- outer word: call the inner DUP ---- */
     let outer_code = [
         dup_word, // nested call
-        Code::basic(ret, 0),
+        Code::basic(op::ret, 0),
     ];
     let outer_word = Code::word(&outer_code);
 
@@ -308,13 +300,13 @@ fn if_branch_behavior() {
     let mut vm = mem.make_vm();
 
     let jump_code = [
-        Code::basic(pick, 0), // duplicate top
-        Code::basic(ret, 0),
+        Code::basic(op::pick, 0), // duplicate top
+        Code::basic(op::ret, 0),
     ];
 
     let target = (&jump_code as *const Code).wrapping_sub(1);
 
-    let cond_code = [Code::basic_raw(_if, target), Code::basic(ret, 0)];
+    let cond_code = [Code::basic_raw(op::_if, target), Code::basic(op::ret, 0)];
     let cond_word = Code::word(&cond_code);
 
     let canary = 999usize as *mut _;
@@ -348,15 +340,15 @@ fn call_dyn_executes_target() {
     let mut vm = mem.make_vm();
 
     let dup_code = [
-        Code::basic(pick, 0),
-        Code::basic(no_op, 0),
-        Code::basic(ret, 0),
+        Code::basic(op::pick, 0),
+        Code::basic(op::no_op, 0),
+        Code::basic(op::ret, 0),
     ];
     let dup_word = Code::word(&dup_code);
     let mut dyn_target = PalData { code: &dup_word };
 
     //====== threaded =========
-    let call_code = [Code::basic(call_dyn_threaded, 0), Code::basic(ret, 0)];
+    let call_code = [Code::basic(op::call_dyn_threaded, 0), Code::basic(op::ret, 0)];
     let call_word = Code::word(&call_code);
 
     vm.param_stack.push(canary).unwrap();
@@ -370,10 +362,10 @@ fn call_dyn_executes_target() {
 
     //====== non threaded =========
     let call_code = [
-        Code::basic(call_dyn, 0),
-        Code::basic(no_op, 0),
-        Code::basic(no_op, 0),
-        Code::basic(ret, 0),
+        Code::basic(op::call_dyn, 0),
+        Code::basic(op::no_op, 0),
+        Code::basic(op::no_op, 0),
+        Code::basic(op::ret, 0),
     ];
     let call_word = Code::word(&call_code);
 
@@ -387,8 +379,1743 @@ fn call_dyn_executes_target() {
     }
 }
 
+#[test]
+fn call_dyn_threaded_returns_to_caller() {
+    let canary = 123usize as *mut _;
+
+    let mut mem = VmEasyMemory::<16>::new();
+    let mut vm = mem.make_vm();
+
+    let target_code = [Code::basic(op::no_op, 0), Code::basic(op::ret, 0)];
+    let target_word = Code::word(&target_code);
+    let mut dyn_target = PalData { code: &target_word };
+
+    //the pick after the call only runs if the caller keeps going
+    let call_code = [
+        Code::basic(op::call_dyn_threaded, 0),
+        Code::basic(op::pick, 0),
+        Code::basic(op::ret, 0),
+    ];
+    let call_word = Code::word(&call_code);
+
+    vm.param_stack.push(canary).unwrap();
+    vm.param_stack.push(&mut dyn_target).unwrap();
+
+    unsafe {
+        vm.execute_code(&call_word as *const Code);
+        assert_eq!(vm.param_stack.pop().unwrap(), canary);
+        assert_eq!(vm.param_stack.pop().unwrap(), canary);
+        assert!(vm.param_stack.pop().is_none());
+    }
+}
+
 
 #[test]
 fn defualt_logger(){
     crate::DefualtLogger::new_ref().write_all(b"\n!!!!hey I am logger!!!\n").unwrap()
-}
\ No newline at end of file
+}
+#[test]
+fn tail_call_recursion() {
+    use crate::ir::CompEasyMemory;
+    use crate::lex::LexEasyMemory;
+    use core::mem::ManuallyDrop;
+
+    let mut lex_mem = LexEasyMemory::new();
+    let mut lex = ManuallyDrop::new(lex_mem.make_lex());
+    let mut comp_mem = CompEasyMemory::<16>::new();
+    let mut comp = comp_mem.make_comp(&mut lex);
+
+    let zero = PalData { int: 0 };
+    let one = PalData { int: 1 };
+    let flag = UnsafeCell::new(PalData { bool: FALSE });
+
+    // countdown ( n -- ) decrements n in place untill it hits 0
+    let mem = &mut comp.lex.code_mem;
+    let start = mem
+        .save(Code::basic(op::push_var, flag.get() as isize))
+        .unwrap() as *const Code;
+    for c in [
+        Code::basic(op::pick, 1),
+        Code::basic(op::push_var, &zero as *const _ as isize),
+        Code::basic(op::int_eq, 0),
+        Code::basic(op::branch, 4),
+        Code::basic(op::push_var, &one as *const _ as isize),
+        Code::basic(op::int_sub, 0),
+        Code::word_raw(start), // recurse
+        Code::basic(op::ret, 0),
+        Code::basic(op::param_drop, 1),
+        Code::basic(op::ret, 0),
+    ] {
+        mem.save(c).unwrap();
+    }
+
+    let code = comp.finalize_code().unwrap();
+    assert!(code[7].is(op::tail_call));
+    assert!(code[8].is(op::ret));
+
+    let word = Code::word(code);
+    let mut n = PalData { int: 1_000_000 };
+
+    let mut mem = VmEasyMemory::<64>::new();
+    let mut vm = mem.make_vm();
+    vm.param_stack.push(&mut n).unwrap();
+
+    unsafe {
+        vm.execute_code(&word);
+        assert_eq!(n.int, 0);
+    }
+    assert_eq!(vm.param_stack.len(), 0);
+    assert_eq!(vm.return_stack.len(), 0);
+}
+
+#[test]
+fn fold_constant_literals() {
+    use crate::ir::CompEasyMemory;
+    use crate::lex::LexEasyMemory;
+    use crate::prelude::load_prelude;
+    use core::mem::ManuallyDrop;
+
+    let mut lex_mem = LexEasyMemory::new();
+    let mut lex = ManuallyDrop::new(lex_mem.make_lex());
+    load_prelude(&mut lex);
+    let mut comp_mem = CompEasyMemory::<16>::new();
+    let mut comp = comp_mem.make_comp(&mut lex);
+
+    let int = comp.lex.basic_type("int").unwrap();
+    let bool = comp.lex.basic_type("bool").unwrap();
+
+    // 3 4 int_add -> 7
+    let cp = comp.lex.code_mem.check_point();
+    comp.add_literal(int, PalData { int: 3 });
+    comp.add_literal(int, PalData { int: 4 });
+    let add = comp.lex.words["int_add"].clone();
+    comp.add_word(&add).unwrap();
+
+    let code = comp.lex.code_mem.index_checkpoint(cp);
+    assert_eq!(code.len(), 1);
+    assert!(code[0].is(op::push_var));
+    let seven = code[0].param.load(core::sync::atomic::Ordering::Relaxed) as *const PalData;
+    assert_eq!(unsafe { (*seven).int }, 7);
+    assert_eq!(comp.stack.stack.len(), 1);
+    assert!(comp.stack.stack.peek().unwrap().borrow().constant.is_some());
+
+    //the result is a literal again so it keeps folding
+    // 7 2 int_mul -> 14
+    comp.add_literal(int, PalData { int: 2 });
+    let mul = comp.lex.words["int_mul"].clone();
+    comp.add_word(&mul).unwrap();
+    let code = comp.lex.code_mem.index_checkpoint(cp);
+    assert_eq!(code.len(), 1);
+    let res = code[0].param.load(core::sync::atomic::Ordering::Relaxed) as *const PalData;
+    assert_eq!(unsafe { (*res).int }, 14);
+
+    // false 3 4 int_smaller -> true
+    let cp = comp.lex.code_mem.check_point();
+    comp.add_literal(bool, PalData { bool: FALSE });
+    comp.add_literal(int, PalData { int: 3 });
+    comp.add_literal(int, PalData { int: 4 });
+    let smaller = comp.lex.words["int_smaller"].clone();
+    comp.add_word(&smaller).unwrap();
+
+    let code = comp.lex.code_mem.index_checkpoint(cp);
+    assert_eq!(code.len(), 1);
+    let res = code[0].param.load(core::sync::atomic::Ordering::Relaxed) as *const PalData;
+    assert!(unsafe { (*res).bool });
+    assert_eq!(comp.stack.stack.len(), 2);
+}
+
+#[test]
+fn no_fold_without_constants() {
+    use crate::ir::CompEasyMemory;
+    use crate::lex::LexEasyMemory;
+    use crate::prelude::load_prelude;
+    use crate::types::READ_FLAG;
+    use crate::types::SigError;
+    use crate::types::WRITE_FLAG;
+    use core::mem::ManuallyDrop;
+
+    let mut lex_mem = LexEasyMemory::new();
+    let mut lex = ManuallyDrop::new(lex_mem.make_lex());
+    load_prelude(&mut lex);
+    let mut comp_mem = CompEasyMemory::<16>::new();
+    let mut comp = comp_mem.make_comp(&mut lex);
+
+    let int = comp.lex.basic_type("int").unwrap();
+
+    //a box we know nothing about (say an input)
+    comp.stack.push_box(int, READ_FLAG | WRITE_FLAG, None);
+    let cp = comp.lex.code_mem.check_point();
+    comp.add_literal(int, PalData { int: 4 });
+    let add = comp.lex.words["int_add"].clone();
+    comp.add_word(&add).unwrap();
+
+    let code = comp.lex.code_mem.index_checkpoint(cp);
+    assert_eq!(code.len(), 2);
+    assert!(code[0].is(op::push_var));
+    assert_eq!(buildin_name(&code[1]), Some("int_add"));
+
+    //without folding the literal would be written when the code runs, literals are read only
+    let cp = comp.lex.code_mem.check_point();
+    comp.add_literal(int, PalData { int: 3 });
+    comp.stack.push_box(int, READ_FLAG, None);
+    assert!(matches!(comp.add_word(&add), Err(SigError::BasicSigError { .. })));
+    assert_eq!(comp.lex.code_mem.index_checkpoint(cp).len(), 1);
+    assert_eq!(comp.stack.stack.peek_many(2).unwrap()[1].borrow().permissions, READ_FLAG);
+}
+
+#[test]
+fn trapping_folds_are_refused() {
+    use crate::ir::CompEasyMemory;
+    use crate::ir::parse_literal;
+    use crate::lex::LexEasyMemory;
+    use crate::prelude::load_prelude;
+    use crate::types::SigError;
+    use core::mem::ManuallyDrop;
+
+    let mut lex_mem = LexEasyMemory::new();
+    let mut lex = ManuallyDrop::new(lex_mem.make_lex());
+    load_prelude(&mut lex);
+    assert!(lex.words["int_and"].traps.is_none());
+
+    let mut comp_mem = CompEasyMemory::<16>::new();
+    let mut comp = comp_mem.make_comp(&mut lex);
+
+    //folding would crash the compiler and the literals cant be written at runtime either
+    for (name, lhs, rhs) in [
+        ("int_add", "9223372036854775807", "1"),
+        ("int_div", "3", "0"),
+        ("int_shl", "1", "64"),
+        ("u8_add", "200u8", "100u8"),
+        ("i8_sub", "-100i8", "100i8"),
+        ("u16_mod", "1u16", "0u16"),
+    ] {
+        let cp = comp.lex.code_mem.check_point();
+        for lit in [lhs, rhs] {
+            let (tp, v) = parse_literal(comp.lex, lit).unwrap();
+            comp.add_literal(tp, v);
+        }
+        let word = comp.lex.words[name].clone();
+        assert!(matches!(comp.add_word(&word), Err(SigError::ConstTrap(n)) if n == name));
+        assert_eq!(comp.lex.code_mem.index_checkpoint(cp).len(), 2);
+        comp.stack.stack.free(2);
+        unsafe { comp.lex.code_mem.goto_checkpoint(cp) };
+    }
+
+    //at their own width the ones that fit still fold
+    let cp = comp.lex.code_mem.check_point();
+    for lit in ["200u8", "55u8"] {
+        let (tp, v) = parse_literal(comp.lex, lit).unwrap();
+        comp.add_literal(tp, v);
+    }
+    let word = comp.lex.words["u8_add"].clone();
+    comp.add_word(&word).unwrap();
+    let code = comp.lex.code_mem.index_checkpoint(cp);
+    assert_eq!(code.len(), 1);
+    let res = code[0].param.load(core::sync::atomic::Ordering::Relaxed) as *const u8;
+    assert_eq!(unsafe { *res }, 255);
+}
+
+#[test]
+fn literals_and_definitions_from_text() {
+    use crate::PalError;
+    use crate::input::WordStream;
+    use crate::ir::CompEasyMemory;
+    use crate::lex::LexEasyMemory;
+    use crate::prelude::load_prelude;
+    use crate::vm::CompMode;
+    use core::mem::ManuallyDrop;
+    use no_std_io::io::Cursor;
+
+    let mut lex_mem = LexEasyMemory::new();
+    let mut lex = ManuallyDrop::new(lex_mem.make_lex());
+    load_prelude(&mut lex);
+    let mut comp_mem = CompEasyMemory::<16>::new();
+    let mut comp = comp_mem.make_comp(&mut lex);
+
+    let mut stream: WordStream<_, 64> =
+        WordStream::new(Cursor::new(&b"3 4 int_add : nothing ; : bad 3 ; 1 "[..]));
+    comp.input = Some(&mut stream);
+
+    let mut mem = VmEasyMemory::<16>::new();
+    let mut vm = mem.make_vm();
+    vm.comp = CompMode::Run(Box::new(comp));
+
+    let res = unsafe { vm.respond_to_input() };
+    assert!(matches!(res, Err(PalError::LeftOnStack("bad"))));
+    assert_eq!(vm.param_stack.len(), 1);
+    assert_eq!(unsafe { (**vm.param_stack.peek().unwrap()).int }, 7);
+
+    assert!(matches!(vm.comp, CompMode::Run(_)));
+    let comp = vm.comp.get_comp_crash();
+    assert!(comp.lex.words.contains_key("nothing"));
+    assert!(!comp.lex.words.contains_key("bad"));
+
+    //picks up after the error
+    unsafe { vm.respond_to_input().unwrap() };
+    assert_eq!(vm.param_stack.len(), 2);
+    assert_eq!(unsafe { (**vm.param_stack.peek().unwrap()).int }, 1);
+}
+
+#[test]
+fn fast_buildins_on_both_loops() {
+    use crate::vm::FastCode;
+
+    static PICK_1: FastCode =
+        FastCode::new(pick_fast, core::ptr::without_provenance(1), op::pick);
+    static DROP_1: FastCode =
+        FastCode::new(param_drop_fast, core::ptr::without_provenance(1), op::param_drop);
+
+    let mut flag = PalData { bool: FALSE };
+    let mut three = PalData { int: 3 };
+    let four = PalData { int: 4 };
+    let push_four = &FastCode::new(push_var_fast, &four as *const _ as *const Code, op::push_var);
+
+    // flag three -- flag three
+    // three += 4 ; flag = three > 4
+    let code = [
+        Code::fast(push_four),
+        Code::fast(&INT_ADD_FAST),
+        Code::fast(&PICK_1),
+        Code::fast(&PICK_1),
+        Code::fast(push_four),
+        Code::fast(&INT_BIGGER_FAST),
+        Code::fast(&DROP_1),
+        Code::basic(op::push_var, &four as *const _ as isize),
+        Code::basic(op::int_add, 0),
+        Code::basic(op::ret, 0),
+    ];
+    let word = Code::word(&code);
+
+    let mut mem = VmEasyMemory::<16>::new();
+    let mut vm = mem.make_vm();
+    vm.param_stack.push(&mut flag).unwrap();
+    vm.param_stack.push(&mut three).unwrap();
+
+    unsafe {
+        vm.execute_code_plain(&word);
+        assert_eq!(three.int, 11);
+        assert!(flag.bool);
+
+        flag.bool = FALSE;
+        vm.execute_code_fast(&word);
+        assert_eq!(three.int, 19);
+        assert!(flag.bool);
+    }
+    assert_eq!(vm.param_stack.len(), 2);
+    assert_eq!(vm.return_stack.len(), 0);
+}
+
+#[test]
+fn compiler_emits_fast_buildins() {
+    use crate::ir::CompEasyMemory;
+    use crate::lex::LexEasyMemory;
+    use crate::prelude::load_prelude;
+    use crate::types::READ_FLAG;
+    use crate::types::WRITE_FLAG;
+    use core::mem::ManuallyDrop;
+
+    let mut lex_mem = LexEasyMemory::new();
+    let mut lex = ManuallyDrop::new(lex_mem.make_lex());
+    load_prelude(&mut lex);
+    let mut comp_mem = CompEasyMemory::<16>::new();
+    let mut comp = comp_mem.make_comp(&mut lex);
+
+    let int = comp.lex.basic_type("int").unwrap();
+    comp.stack.push_box(int, READ_FLAG | WRITE_FLAG, None);
+    comp.stack.push_box(int, READ_FLAG | WRITE_FLAG, None);
+    let cp = comp.lex.code_mem.check_point();
+    let add = comp.lex.words["int_add"].clone();
+    comp.add_word(&add).unwrap();
+
+    let code = comp.lex.code_mem.index_checkpoint(cp);
+    assert_eq!(code.len(), 1);
+    assert_eq!(code[0].is(op::fast_call), cfg!(feature = "fast_dispatch"));
+    //tools still see the plain buildin
+    assert_eq!(buildin_name(&code[0]), Some("int_add"));
+}
+
+#[test]
+fn infer_word_signatures() {
+    use crate::PalError;
+    use crate::input::WordStream;
+    use crate::ir::CompEasyMemory;
+    use crate::lex::LexEasyMemory;
+    use crate::prelude::load_prelude;
+    use crate::types::READ_FLAG;
+    use crate::types::UNIQUE_FLAG;
+    use crate::types::WRITE_FLAG;
+    use crate::vm::CompMode;
+    use core::mem::ManuallyDrop;
+    use no_std_io::io::Cursor;
+
+    let mut lex_mem = LexEasyMemory::new();
+    let mut lex = ManuallyDrop::new(lex_mem.make_lex());
+    load_prelude(&mut lex);
+    let mut comp_mem = CompEasyMemory::<16>::new();
+    let mut comp = comp_mem.make_comp(&mut lex);
+
+    let text = b": add int_add ; : add3 3 add ; : add_u ( int -- int:rwu ) int_add ;
+        : bad ( int -- int ) int_add ; 5 2 add 1 add3 ";
+    let mut stream: WordStream<_, 64> = WordStream::new(Cursor::new(&text[..]));
+    comp.input = Some(&mut stream);
+
+    let mut mem = VmEasyMemory::<16>::new();
+    let mut vm = mem.make_vm();
+    vm.comp = CompMode::Run(Box::new(comp));
+
+    let res = unsafe { vm.respond_to_input() };
+    let Err(PalError::SigError(e)) = res else {
+        panic!("expected a mismatch got {res:?}")
+    };
+    let msg = format!("{e}");
+    assert!(msg.contains("  - int [read]\n  + int [read, write]\n"), "{msg}");
+
+    let comp = vm.comp.get_comp_crash();
+    assert!(!comp.lex.words.contains_key("bad"));
+    let sig = |name: &str| {
+        let rt = &comp.lex.words[name].runtime;
+        let perms = |s: &[crate::types::SigItem]| s.iter().map(|i| i.permissions).collect::<Vec<_>>();
+        (perms(rt.input_sig), perms(rt.output_sig))
+    };
+    assert_eq!(sig("add"), (vec![READ_FLAG], vec![READ_FLAG | WRITE_FLAG]));
+    assert_eq!(sig("add3"), (vec![], vec![READ_FLAG | WRITE_FLAG]));
+    assert_eq!(
+        sig("add_u"),
+        (vec![READ_FLAG], vec![READ_FLAG | WRITE_FLAG | UNIQUE_FLAG])
+    );
+
+    unsafe { vm.respond_to_input().unwrap() };
+    assert_eq!(vm.param_stack.len(), 2);
+    unsafe {
+        assert_eq!((*vm.param_stack.pop().unwrap()).int, 4);
+        assert_eq!((*vm.param_stack.pop().unwrap()).int, 7);
+    }
+}
+
+#[test]
+fn cluster_field_borrows() {
+    use crate::input::WordStream;
+    use crate::ir::CompEasyMemory;
+    use crate::lex::LexEasyMemory;
+    use crate::prelude::load_prelude;
+    use crate::types::READ_FLAG;
+    use crate::types::SigError;
+    use crate::types::SigItem;
+    use crate::types::UNIQUE_FLAG;
+    use crate::types::WRITE_FLAG;
+    use crate::vm::CompMode;
+    use core::mem::ManuallyDrop;
+    use no_std_io::io::Cursor;
+
+    let mut lex_mem = LexEasyMemory::new();
+    let mut lex = ManuallyDrop::new(lex_mem.make_lex());
+    load_prelude(&mut lex);
+    let mut comp_mem = CompEasyMemory::<32>::new();
+    let mut comp = comp_mem.make_comp(&mut lex);
+
+    let text = b"cluster point ( x:int y:int ) ";
+    let mut stream: WordStream<_, 64> = WordStream::new(Cursor::new(&text[..]));
+    comp.input = Some(&mut stream);
+    let mut mem = VmEasyMemory::<16>::new();
+    let mut vm = mem.make_vm();
+    vm.comp = CompMode::Run(Box::new(comp));
+    unsafe { vm.respond_to_input().unwrap() };
+
+    let comp = vm.comp.get_comp_crash();
+    let point = comp.lex.named_type("point").unwrap();
+    let int = comp.lex.named_type("int").unwrap();
+    assert_eq!(point.cells, 2);
+    assert_eq!(point.field_offset(1), Some(1));
+    let x = comp.lex.words["point.x"].clone();
+    let y = comp.lex.words["point.y"].clone();
+
+    let rwu = READ_FLAG | WRITE_FLAG | UNIQUE_FLAG;
+    let p = comp.stack.push_box(point, rwu, None);
+    let item = |tp, permissions| SigItem { tp, permissions };
+
+    //x uniquely and y shared touch different fields
+    comp.stack.stack.push(p).unwrap();
+    comp.add_word(&x).unwrap();
+    comp.stack.stack.push(p).unwrap();
+    comp.add_word(&y).unwrap();
+    {
+        let top = comp.stack.stack.peek_many(2).unwrap();
+        assert_eq!(top[0].borrow().permissions, rwu);
+        assert!(core::ptr::eq(top[0].borrow().derived_from.unwrap().0, p));
+    }
+    let inputs = [item(int, rwu), item(int, READ_FLAG)];
+    comp.stack.call_sig(&[], &inputs).unwrap();
+
+    //the same field twice clashes
+    comp.stack.stack.push(p).unwrap();
+    comp.add_word(&x).unwrap();
+    comp.stack.stack.push(p).unwrap();
+    comp.add_word(&x).unwrap();
+    let err = comp.stack.call_sig(&[], &inputs).unwrap_err();
+    assert!(matches!(err, SigError::NeedsUnique(_) | SigError::AlreadyBorrowed(_)));
+    comp.stack.stack.free(2).unwrap();
+
+    //so does the whole cluster with any of its fields
+    comp.stack.stack.push(p).unwrap();
+    comp.add_word(&y).unwrap();
+    let inputs = [item(point, rwu), item(int, READ_FLAG)];
+    let err = comp.stack.call_sig(&[], &inputs).unwrap_err();
+    assert!(matches!(err, SigError::NeedsUnique(_) | SigError::AlreadyBorrowed(_)));
+    let inputs = [item(point, READ_FLAG), item(int, READ_FLAG)];
+    comp.stack.call_sig(&[], &inputs).unwrap();
+    assert_eq!(comp.stack.stack.len(), 0);
+
+    //at runtime the field words just offset the pointer
+    let mut data = [PalData { int: 1 }, PalData { int: 2 }];
+    vm.param_stack.push(data.as_mut_ptr()).unwrap();
+    unsafe {
+        y.runtime.run(&mut vm);
+        assert_eq!((*vm.param_stack.pop().unwrap()).int, 2);
+    }
+}
+
+#[test]
+fn array_indexing() {
+    use crate::ir::CompEasyMemory;
+    use crate::lex::LexEasyMemory;
+    use crate::prelude::load_prelude;
+    use crate::types::READ_FLAG;
+    use crate::types::SigError;
+    use crate::types::SigItem;
+    use crate::types::UNIQUE_FLAG;
+    use crate::types::WRITE_FLAG;
+    use core::mem::ManuallyDrop;
+
+    let mut lex_mem = LexEasyMemory::new();
+    let mut lex = ManuallyDrop::new(lex_mem.make_lex());
+    load_prelude(&mut lex);
+    let int = lex.named_type("int").unwrap();
+    let fixed = lex.add_array(int, Some(3));
+    let slice = lex.add_array(int, None);
+    assert_eq!(fixed.name, "Array<3>(int)");
+    assert_eq!(lex.named_type("Array(int)").map(|t| t.cells), Some(2));
+    let fixed_at = lex.words["Array<3>(int).at"].clone();
+    let slice_at = lex.words["Array(int).at"].clone();
+
+    let mut comp_mem = CompEasyMemory::<32>::new();
+    let mut comp = comp_mem.make_comp(&mut lex);
+    let rw = READ_FLAG | WRITE_FLAG;
+    let rwu = rw | UNIQUE_FLAG;
+    let item = |tp, permissions| SigItem { tp, permissions };
+
+    //the element can not be more unique than the array it came from
+    let shared = comp.stack.push_box(fixed, rw, None);
+    comp.stack.push_box(int, READ_FLAG, None);
+    comp.add_word(&fixed_at).unwrap();
+    let elem = *comp.stack.stack.peek().unwrap();
+    assert_eq!(elem.borrow().permissions, rw);
+    assert!(core::ptr::eq(elem.borrow().derived_from.unwrap().0, shared));
+    let err = comp.stack.call_sig(&[], &[item(int, rwu)]).unwrap_err();
+    assert!(matches!(err, SigError::BasicSigError { .. }), "{err:?}");
+    comp.stack.call_sig(&[], &[item(int, rw)]).unwrap();
+
+    //an element aliases the whole array
+    let owned = comp.stack.push_box(slice, rwu, None);
+    comp.stack.stack.push(owned).unwrap();
+    comp.stack.push_box(int, READ_FLAG, None);
+    comp.add_word(&slice_at).unwrap();
+    let inputs = [item(slice, READ_FLAG), item(int, rwu)];
+    let err = comp.stack.call_sig(&[], &inputs).unwrap_err();
+    assert!(matches!(err, SigError::NeedsUnique(_) | SigError::AlreadyBorrowed(_)));
+    let inputs = [item(slice, READ_FLAG), item(int, READ_FLAG)];
+    comp.stack.call_sig(&[], &inputs).unwrap();
+
+    let mut mem = VmEasyMemory::<16>::new();
+    let mut vm = mem.make_vm();
+    let mut data = [PalData { int: 10 }, PalData { int: 11 }, PalData { int: 12 }];
+    let mut idx = PalData { int: 2 };
+    let mut s = [PalData { ptr: data.as_mut_ptr() }, PalData { int: 3 }];
+    unsafe {
+        vm.param_stack.push(data.as_mut_ptr()).unwrap();
+        vm.param_stack.push(&mut idx).unwrap();
+        fixed_at.runtime.run(&mut vm);
+        assert_eq!((*vm.param_stack.pop().unwrap()).int, 12);
+
+        idx.int = 1;
+        vm.param_stack.push(s.as_mut_ptr()).unwrap();
+        vm.param_stack.push(&mut idx).unwrap();
+        slice_at.runtime.run(&mut vm);
+        assert_eq!((*vm.param_stack.pop().unwrap()).int, 11);
+    }
+}
+
+#[test]
+#[cfg(not(feature = "unchecked_bounds"))]
+#[should_panic(expected = "index out of bounds")]
+fn slice_index_out_of_bounds() {
+    let mut data = [PalData { int: 1 }];
+    let mut s = [PalData { ptr: data.as_mut_ptr() }, PalData { int: 1 }];
+    let mut idx = PalData { int: -1 };
+    let code = [Code::basic(op::slice_at, 8), Code::basic(op::ret, 0)];
+
+    let mut mem = VmEasyMemory::<16>::new();
+    let mut vm = mem.make_vm();
+    vm.param_stack.push(s.as_mut_ptr()).unwrap();
+    vm.param_stack.push(&mut idx).unwrap();
+    unsafe { vm.execute_code(&Code::word(&code)) };
+}
+
+#[test]
+fn float_literals_and_conversions() {
+    use crate::input::WordStream;
+    use crate::ir::CompEasyMemory;
+    use crate::ir::parse_literal;
+    use crate::lex::LexEasyMemory;
+    use crate::prelude::load_prelude;
+    use crate::vm::CompMode;
+    use core::mem::ManuallyDrop;
+    use no_std_io::io::Cursor;
+
+    let mut lex_mem = LexEasyMemory::new();
+    let mut lex = ManuallyDrop::new(lex_mem.make_lex());
+    load_prelude(&mut lex);
+    let float = lex.named_type("float").unwrap();
+    let lit = |s| parse_literal(&lex, s).map(|(tp, v)| (tp.name, unsafe { v.float }));
+    assert_eq!(lit("1.5e3"), Some(("float", 1500.0)));
+    assert_eq!(lit("-.5"), Some(("float", -0.5)));
+    assert_eq!(lit("inf"), None);
+    assert_eq!(lit("nan"), None);
+    assert_eq!(parse_literal(&lex, "15").unwrap().0.name, "int");
+    assert_eq!(float.size, 8);
+
+    let mut comp_mem = CompEasyMemory::<32>::new();
+    let mut comp = comp_mem.make_comp(&mut lex);
+    let text = b": scale 1.5e3 0.5 float_mul float_mul ; 2.0 scale
+        0.0 7 int_to_float
+        0 -2.9 float_to_int
+        0 0.0 0.0 float_div float_to_int
+        false 0.1 0.2 float_add 0.3 float_neq ";
+    let mut stream: WordStream<_, 64> = WordStream::new(Cursor::new(&text[..]));
+    comp.input = Some(&mut stream);
+    let mut mem = VmEasyMemory::<16>::new();
+    let mut vm = mem.make_vm();
+    vm.comp = CompMode::Run(Box::new(comp));
+    unsafe { vm.respond_to_input().unwrap() };
+
+    assert_eq!(vm.param_stack.len(), 5);
+    unsafe {
+        assert!((*vm.param_stack.pop().unwrap()).bool);
+        assert_eq!((*vm.param_stack.pop().unwrap()).int, 0);
+        assert_eq!((*vm.param_stack.pop().unwrap()).int, -2);
+        assert_eq!((*vm.param_stack.pop().unwrap()).float, 7.0);
+        assert_eq!((*vm.param_stack.pop().unwrap()).float, 1500.0);
+    }
+}
+
+#[test]
+#[cfg(feature = "libm")]
+fn float_math_words() {
+    let mut x = PalData { float: 9.0 };
+    let mut y = PalData { float: 0.5 };
+    let code = [
+        Code::basic(op::float_sqrt, 0),
+        Code::basic(op::float_pow, 0),
+        Code::basic(op::ret, 0),
+    ];
+
+    let mut mem = VmEasyMemory::<16>::new();
+    let mut vm = mem.make_vm();
+    vm.param_stack.push(&mut x).unwrap();
+    vm.param_stack.push(&mut y).unwrap();
+    unsafe { vm.execute_code(&Code::word(&code)) };
+    //sqrt only touches the top so this is 9 ^ sqrt(0.5)
+    assert_eq!(unsafe { x.float }, 9f64.powf(0.5f64.sqrt()));
+}
+
+#[test]
+fn sized_ints_and_dense_arrays() {
+    use crate::input::WordStream;
+    use crate::ir::CompEasyMemory;
+    use crate::lex::LexEasyMemory;
+    use crate::prelude::load_prelude;
+    use crate::vm::CompMode;
+    use core::mem::ManuallyDrop;
+    use no_std_io::io::Cursor;
+
+    let mut lex_mem = LexEasyMemory::new();
+    let mut lex = ManuallyDrop::new(lex_mem.make_lex());
+    load_prelude(&mut lex);
+    let u8_t = lex.named_type("u8").unwrap();
+    assert_eq!((u8_t.size, u8_t.cells), (1, 1));
+    assert!(core::ptr::eq(lex.named_type("i64").unwrap(), lex.named_type("int").unwrap()));
+    //sized arithmetic traps on overflow like int does so the folds that would are refused
+    for name in ["u8_add", "u8_sub", "u8_mul", "u8_div", "u8_mod"] {
+        assert!(lex.words[name].pure && lex.words[name].traps.is_some(), "{name}");
+    }
+    for name in ["u8_and", "u8_or", "u8_xor"] {
+        assert!(lex.words[name].pure && lex.words[name].traps.is_none(), "{name}");
+    }
+    let bytes = lex.add_array(u8_t, Some(10));
+    assert_eq!((bytes.size, bytes.cells), (10, 2));
+    let at = lex.words["Array<10>(u8).at"].clone();
+
+    let mut comp_mem = CompEasyMemory::<32>::new();
+    let mut comp = comp_mem.make_comp(&mut lex);
+    let text = b"0u8 300 u8_from_int
+        0i8 false 300 i8_from_int_checked
+        0 -1i8 i8_to_int
+        200u8 50u8 u8_add
+        0 false 18446744073709551615u64 u64_to_int_checked
+        false 255u8 1u8 u8_bigger ";
+    let mut stream: WordStream<_, 64> = WordStream::new(Cursor::new(&text[..]));
+    comp.input = Some(&mut stream);
+    let mut mem = VmEasyMemory::<16>::new();
+    let mut vm = mem.make_vm();
+    vm.comp = CompMode::Run(Box::new(comp));
+    unsafe { vm.respond_to_input().unwrap() };
+
+    assert_eq!(vm.param_stack.len(), 8);
+    unsafe {
+        let mut pop = || vm.param_stack.pop().unwrap();
+        assert!((*pop()).bool);
+        assert!(!(*pop()).bool);
+        assert_eq!((*pop()).int, 0);
+        assert_eq!(*(pop() as *const u8), 250);
+        assert_eq!((*pop()).int, -1);
+        assert!(!(*pop()).bool);
+        assert_eq!(*(pop() as *const i8), 0);
+        assert_eq!(*(pop() as *const u8), 44);
+    }
+
+    //elements of a byte array are packed and only their own byte is written
+    let mut data = [PalData { int: 0 }; 2];
+    let mut idx = PalData { int: 9 };
+    let mut one = PalData { int: 0 };
+    unsafe {
+        *(&mut one as *mut PalData as *mut u8) = 7;
+        vm.param_stack.push(data.as_mut_ptr()).unwrap();
+        vm.param_stack.push(&mut idx).unwrap();
+        at.runtime.run(&mut vm);
+        let elem = *vm.param_stack.peek().unwrap();
+        assert_eq!(elem.byte_offset_from(data.as_ptr()), 9);
+
+        vm.param_stack.push(&mut one).unwrap();
+        let u8_add = SIZED_INTS[3].2[0];
+        assert_eq!(u8_add.0, "u8_add");
+        let code = [Code::basic(u8_add.1, 0), Code::basic(op::ret, 0)];
+        vm.execute_code(&Code::word(&code));
+        let raw = &*(data.as_ptr() as *const [u8; 16]);
+        assert_eq!(raw[9], 7);
+        assert_eq!(raw.iter().map(|b| *b as u32).sum::<u32>(), 7);
+    }
+}
+
+#[test]
+fn overflow_families() {
+    use crate::input::WordStream;
+    use crate::ir::CompEasyMemory;
+    use crate::lex::LexEasyMemory;
+    use crate::prelude::load_prelude;
+    use crate::vm::CompMode;
+    use core::mem::ManuallyDrop;
+    use no_std_io::io::Cursor;
+
+    let max = i64::MAX;
+    let min = i64::MIN;
+    let cases: [(&str, i64, Option<bool>); 17] = [
+        ("9223372036854775807 1 int_add_wrapping", min, None),
+        ("9223372036854775807 1 int_add_saturating", max, None),
+        ("9223372036854775807 false 1 int_add_checked", max, Some(false)),
+        ("-9223372036854775808 3 int_mul_saturating", min, None),
+        ("-9223372036854775808 1 int_sub_wrapping", max, None),
+        ("5 false 3 int_sub_checked", 2, Some(true)),
+        //i64::MIN / -1 overflows
+        ("-9223372036854775808 -1 int_div_wrapping", min, None),
+        ("-9223372036854775808 -1 int_div_saturating", max, None),
+        ("-9223372036854775808 false -1 int_div_checked", min, Some(false)),
+        ("-9223372036854775808 -1 int_mod_wrapping", 0, None),
+        ("-9223372036854775808 false -1 int_mod_checked", min, Some(false)),
+        //division by zero
+        ("7 0 int_div_wrapping", 0, None),
+        ("7 0 int_mod_wrapping", 7, None),
+        ("-7 0 int_div_saturating", min, None),
+        ("7 0 int_mod_saturating", 0, None),
+        ("7 false 0 int_div_checked", 7, Some(false)),
+        ("7 false 0 int_mod_checked", 7, Some(false)),
+    ];
+    let text: String = cases.iter().map(|(src, _, _)| format!("{src}\n")).collect();
+    let mut stream: WordStream<_, 64> = WordStream::new(Cursor::new(text.into_bytes()));
+
+    let mut lex_mem = LexEasyMemory::new();
+    let mut lex = ManuallyDrop::new(lex_mem.make_lex());
+    load_prelude(&mut lex);
+    let mut comp_mem = CompEasyMemory::<64>::new();
+    let mut comp = comp_mem.make_comp(&mut lex);
+
+    comp.input = Some(&mut stream);
+    let mut mem = VmEasyMemory::<64>::new();
+    let mut vm = mem.make_vm();
+    vm.comp = CompMode::Run(Box::new(comp));
+    unsafe { vm.respond_to_input().unwrap() };
+
+    for (src, want, flag) in cases.iter().rev() {
+        unsafe {
+            if let Some(flag) = flag {
+                assert_eq!((*vm.param_stack.pop().unwrap()).bool, *flag, "{src}");
+            }
+            assert_eq!((*vm.param_stack.pop().unwrap()).int, *want, "{src}");
+        }
+    }
+    assert_eq!(vm.param_stack.len(), 0);
+}
+
+#[test]
+fn strings() {
+    use crate::input::WordStream;
+    use crate::ir::CompEasyMemory;
+    use crate::lex::LexEasyMemory;
+    use crate::prelude::load_prelude;
+    use crate::types::READ_FLAG;
+    use crate::types::SigItem;
+    use crate::types::UNIQUE_FLAG;
+    use crate::types::WRITE_FLAG;
+    use crate::vm::CompMode;
+    use core::mem::ManuallyDrop;
+    use no_std_io::io::Cursor;
+
+    unsafe fn view<'a>(p: *const PalData) -> &'a str {
+        unsafe {
+            let bytes = core::slice::from_raw_parts((*p).ptr as *const u8, (*p.add(1)).int as usize);
+            core::str::from_utf8(bytes).unwrap()
+        }
+    }
+
+    let text = "0 s\" héllo wörld\" str_len
+        0 s\" héllo\" 1 str_char_at
+        0u8 s\" héllo\" 1 str_byte_at
+        false s\" abc\" s\" abd\" str_smaller
+        false s\" a b\" s\" a   b\" str_eq
+        0 false s\" -42\" str_to_int
+        0 false s\" 4x\" str_to_int
+        : greet_len s\" hi there\" str_len ; 0 greet_len
+        s\" unclosed ";
+    let mut stream: WordStream<_, 64> = WordStream::new(Cursor::new(text.as_bytes()));
+
+    let mut lex_mem = LexEasyMemory::new();
+    let mut lex = ManuallyDrop::new(lex_mem.make_lex());
+    load_prelude(&mut lex);
+    let bytes = lex.named_type("Array(u8)").unwrap();
+    let str = lex.named_type("str").unwrap();
+    assert_eq!(str.cells, 2);
+    assert!(!core::ptr::eq(str, bytes));
+    let concat = lex.words["str_concat"].clone();
+    let from_int = lex.words["str_from_int"].clone();
+    let from_bytes = lex.words["str_from_bytes"].clone();
+
+    let mut comp_mem = CompEasyMemory::<64>::new();
+    let mut comp = comp_mem.make_comp(&mut lex);
+    let rw = READ_FLAG | WRITE_FLAG;
+    let rwu = rw | UNIQUE_FLAG;
+    let item = |tp, permissions| SigItem { tp, permissions };
+
+    //the result is a read only view of the buffer which stays borrowed while it lives
+    let buf = comp.stack.push_box(bytes, rwu, None);
+    comp.stack.stack.push(buf).unwrap();
+    comp.stack.push_box(str, READ_FLAG, None);
+    comp.stack.push_box(str, READ_FLAG, None);
+    comp.add_word(&concat).unwrap();
+    let res = *comp.stack.stack.peek().unwrap();
+    assert_eq!(res.borrow().permissions, READ_FLAG);
+    assert!(core::ptr::eq(res.borrow().derived_from.unwrap().0, buf));
+    let inputs = [item(bytes, rwu), item(str, READ_FLAG)];
+    assert!(comp.stack.call_sig(&[], &inputs).is_err());
+    let inputs = [item(bytes, READ_FLAG), item(str, READ_FLAG)];
+    comp.stack.call_sig(&[], &inputs).unwrap();
+
+    comp.input = Some(&mut stream);
+    let mut mem = VmEasyMemory::<64>::new();
+    let mut vm = mem.make_vm();
+    vm.comp = CompMode::Run(Box::new(comp));
+    let res = unsafe { vm.respond_to_input() };
+    assert!(matches!(res, Err(crate::PalError::UnclosedStr)), "{res:?}");
+
+    assert_eq!(vm.param_stack.len(), 10);
+    unsafe {
+        let mut pop = || vm.param_stack.pop().unwrap();
+        assert_eq!((*pop()).int, 8);
+        assert!(!(*pop()).bool);
+        assert_eq!((*pop()).int, 0);
+        assert!((*pop()).bool);
+        assert_eq!((*pop()).int, -42);
+        assert!((*pop()).bool);
+        assert!((*pop()).bool);
+        assert_eq!(*(pop() as *const u8), 0xC3);
+        assert_eq!((*pop()).int, 'é' as i64);
+        assert_eq!((*pop()).int, 13);
+    }
+
+    //16 bytes of text and the 2 cells of the str
+    let mut raw = [PalData { int: 0 }; 4];
+    let mut buf = [PalData { ptr: raw.as_mut_ptr() }, PalData { int: 32 }];
+    let mut a = *b"h\xC3\xA9llo ";
+    let mut b = *b"w\xC3\xB6rld";
+    let mut sa = [PalData { ptr: a.as_mut_ptr() as *mut PalData }, PalData { int: 7 }];
+    let mut sb = [PalData { ptr: b.as_mut_ptr() as *mut PalData }, PalData { int: 6 }];
+    let mut n = PalData { int: -1234 };
+    let mut ok = PalData { bool: TRUE };
+    let mut bad = [0xffu8];
+    let mut sbad = [PalData { ptr: bad.as_mut_ptr() as *mut PalData }, PalData { int: 1 }];
+    unsafe {
+        vm.param_stack.push(buf.as_mut_ptr()).unwrap();
+        vm.param_stack.push(sa.as_mut_ptr()).unwrap();
+        vm.param_stack.push(sb.as_mut_ptr()).unwrap();
+        concat.runtime.run(&mut vm);
+        assert_eq!(vm.param_stack.len(), 1);
+        let res = vm.param_stack.pop().unwrap();
+        assert_eq!(view(res), "héllo wörld");
+        //the str lives inside the buffer which keeps its length
+        assert!(core::ptr::eq(res, &raw[2]));
+        assert_eq!(buf[1].int, 32);
+
+        vm.param_stack.push(buf.as_mut_ptr()).unwrap();
+        vm.param_stack.push(&mut n).unwrap();
+        from_int.runtime.run(&mut vm);
+        assert_eq!(view(vm.param_stack.pop().unwrap()), "-1234");
+        assert_eq!(buf[1].int, 32);
+
+        //no room for the text once the str is in
+        buf[1].int = 17;
+        vm.param_stack.push(buf.as_mut_ptr()).unwrap();
+        vm.param_stack.push(&mut n).unwrap();
+        let run = core::panic::AssertUnwindSafe(|| from_int.runtime.run(&mut vm));
+        assert!(std::panic::catch_unwind(run).is_err());
+        assert_eq!(vm.param_stack.len(), 1);
+        vm.param_stack.pop();
+        //the panic left the outer frame on the return stack
+        while vm.return_stack.pop().is_some() {}
+
+        vm.param_stack.push(&mut ok).unwrap();
+        vm.param_stack.push(sbad.as_mut_ptr()).unwrap();
+        from_bytes.runtime.run(&mut vm);
+        assert_eq!(view(vm.param_stack.pop().unwrap()), "");
+        assert!(!(*vm.param_stack.pop().unwrap()).bool);
+    }
+}
+
+#[test]
+fn variants_and_match() {
+    use crate::PalError;
+    use crate::input::WordStream;
+    use crate::ir::CompEasyMemory;
+    use crate::lex::LexEasyMemory;
+    use crate::prelude::load_prelude;
+    use crate::vm::CompMode;
+    use core::mem::ManuallyDrop;
+    use no_std_io::io::Cursor;
+
+    let good = b"variant Opt ( some:int none )
+        : or_zero match case Opt.some int_add case Opt.none endmatch ; ";
+    let partial = b": partial match case Opt.some int_add endmatch ; ";
+    let uneven = b": uneven match case Opt.some case Opt.none endmatch ; ";
+    let mut good: WordStream<_, 64> = WordStream::new(Cursor::new(&good[..]));
+    let mut partial: WordStream<_, 64> = WordStream::new(Cursor::new(&partial[..]));
+    let mut uneven: WordStream<_, 64> = WordStream::new(Cursor::new(&uneven[..]));
+
+    let mut lex_mem = LexEasyMemory::new();
+    let mut lex = ManuallyDrop::new(lex_mem.make_lex());
+    load_prelude(&mut lex);
+    let mut comp_mem = CompEasyMemory::<32>::new();
+    let mut comp = comp_mem.make_comp(&mut lex);
+    comp.input = Some(&mut good);
+    let mut mem = VmEasyMemory::<16>::new();
+    let mut vm = mem.make_vm();
+    vm.comp = CompMode::Run(Box::new(comp));
+    unsafe { vm.respond_to_input().unwrap() };
+
+    let lex = &vm.comp.get_comp_crash().lex;
+    let opt = lex.named_type("Opt").unwrap();
+    assert_eq!((opt.cells, opt.size), (2, 16));
+    assert_eq!(opt.cases().map(|c| c.len()), Some(2));
+    let some = lex.words["Opt.some"].clone();
+    let none = lex.words["Opt.none"].clone();
+    let or_zero = lex.words["or_zero"].clone();
+    assert_eq!(or_zero.runtime.input_sig.len(), 1);
+    assert!(core::ptr::eq(or_zero.runtime.input_sig[0].tp, opt));
+    assert_eq!(or_zero.runtime.output_sig[0].tp.name, "int");
+
+    let mut v = [PalData { int: -1 }; 2];
+    let mut p = PalData { int: 5 };
+    let mut out = PalData { int: 10 };
+    unsafe {
+        vm.param_stack.push(v.as_mut_ptr()).unwrap();
+        vm.param_stack.push(&mut p).unwrap();
+        some.runtime.run(&mut vm);
+        assert_eq!((v[0].int, v[1].int), (0, 5));
+
+        vm.param_stack.pop().unwrap();
+        vm.param_stack.push(&mut out).unwrap();
+        vm.param_stack.push(v.as_mut_ptr()).unwrap();
+        or_zero.runtime.run(&mut vm);
+        assert_eq!(out.int, 15);
+        assert_eq!(vm.param_stack.len(), 1);
+
+        vm.param_stack.push(v.as_mut_ptr()).unwrap();
+        none.runtime.run(&mut vm);
+        assert_eq!(v[0].int, 1);
+        or_zero.runtime.run(&mut vm);
+        assert_eq!(out.int, 15);
+        vm.param_stack.pop().unwrap();
+    }
+
+    vm.comp.get_comp_crash().input = Some(&mut partial);
+    let res = unsafe { vm.respond_to_input() };
+    assert!(matches!(res, Err(PalError::NonExhaustive("none"))), "{res:?}");
+    vm.comp.get_comp_crash().abandon_word();
+    vm.comp.set_compiling(false);
+
+    vm.comp.get_comp_crash().input = Some(&mut uneven);
+    let res = unsafe { vm.respond_to_input() };
+    assert!(matches!(res, Err(PalError::UnevenArm("some"))), "{res:?}");
+}
+
+#[test]
+fn aliases_and_newtypes() {
+    use crate::PalError;
+    use crate::input::WordStream;
+    use crate::ir::CompEasyMemory;
+    use crate::lex::LexEasyMemory;
+    use crate::prelude::load_prelude;
+    use crate::types::SigError;
+    use crate::vm::CompMode;
+    use core::mem::ManuallyDrop;
+    use no_std_io::io::Cursor;
+
+    let good = b"alias count int newtype meters int
+        : add_counts ( count -- count:rw ) int_add ;
+        : add_meters ( meters:rwu -- int:rw ) meters.unwrap int_add ; ";
+    let bad = b": bad ( meters -- meters:rw ) int_add ; ";
+    let mut good: WordStream<_, 64> = WordStream::new(Cursor::new(&good[..]));
+    let mut bad: WordStream<_, 64> = WordStream::new(Cursor::new(&bad[..]));
+
+    let mut lex_mem = LexEasyMemory::new();
+    let mut lex = ManuallyDrop::new(lex_mem.make_lex());
+    load_prelude(&mut lex);
+    let mut comp_mem = CompEasyMemory::<32>::new();
+    let mut comp = comp_mem.make_comp(&mut lex);
+    comp.input = Some(&mut good);
+    let mut mem = VmEasyMemory::<16>::new();
+    let mut vm = mem.make_vm();
+    vm.comp = CompMode::Run(Box::new(comp));
+    unsafe { vm.respond_to_input().unwrap() };
+
+    let lex = &vm.comp.get_comp_crash().lex;
+    let int = lex.named_type("int").unwrap();
+    let count = lex.named_type("count").unwrap();
+    let meters = lex.named_type("meters").unwrap();
+    assert_eq!((count.name, meters.name), ("count", "meters"));
+    assert_eq!((meters.cells, meters.size), (int.cells, int.size));
+    assert!(count.fits(int) && int.fits(count));
+    assert!(!meters.fits(int) && !int.fits(meters));
+    let wrap = &lex.words["meters.wrap"].runtime;
+    assert!(core::ptr::eq(wrap.input_sig[0].tp, int));
+    assert!(core::ptr::eq(wrap.output_sig[0].tp, meters));
+    let add_counts = lex.words["add_counts"].clone();
+    let add_meters = lex.words["add_meters"].clone();
+    assert!(core::ptr::eq(add_meters.runtime.input_sig[0].tp, meters));
+
+    //boxes carry no types at runtime so both run on plain ints
+    let mut acc = PalData { int: 1 };
+    let mut x = PalData { int: 2 };
+    unsafe {
+        vm.param_stack.push(&mut acc).unwrap();
+        vm.param_stack.push(&mut x).unwrap();
+        add_counts.runtime.run(&mut vm);
+        vm.param_stack.push(&mut x).unwrap();
+        add_meters.runtime.run(&mut vm);
+        vm.param_stack.pop().unwrap();
+        assert_eq!(acc.int, 5);
+    }
+
+    vm.comp.get_comp_crash().input = Some(&mut bad);
+    let res = unsafe { vm.respond_to_input() };
+    let Err(PalError::SigError(SigError::Mismatch { declared, inferred })) = res else {
+        panic!("expected a mismatch {res:?}");
+    };
+    assert_eq!((declared[0][0].tp.name, inferred[0][0].tp.name), ("meters", "int"));
+}
+
+#[test]
+fn generic_words() {
+    use crate::PalError;
+    use crate::input::WordStream;
+    use crate::ir::CompEasyMemory;
+    use crate::lex::LexEasyMemory;
+    use crate::prelude::load_prelude;
+    use crate::vm::CompMode;
+    use core::mem::ManuallyDrop;
+    use no_std_io::io::Cursor;
+
+    let good = b"0 5 copy 1 2 swap 3 4 swap<int>
+        : third 2 array_at<u8> copy ; ";
+    let unbound = b": bad 2 array_at ; ";
+    let mut good: WordStream<_, 64> = WordStream::new(Cursor::new(&good[..]));
+    let mut unbound: WordStream<_, 64> = WordStream::new(Cursor::new(&unbound[..]));
+
+    let mut lex_mem = LexEasyMemory::new();
+    let mut lex = ManuallyDrop::new(lex_mem.make_lex());
+    load_prelude(&mut lex);
+    let mut comp_mem = CompEasyMemory::<32>::new();
+    let mut comp = comp_mem.make_comp(&mut lex);
+    comp.input = Some(&mut good);
+    let mut mem = VmEasyMemory::<16>::new();
+    let mut vm = mem.make_vm();
+    vm.comp = CompMode::Run(Box::new(comp));
+    unsafe { vm.respond_to_input().unwrap() };
+
+    let top = vm.param_stack.peek_many(5).unwrap();
+    let vals: Vec<crate::PalInt> = top.iter().map(|p| unsafe { (**p).int }).collect();
+    assert_eq!(vals, [3, 4, 1, 2, 5]);
+
+    //copy and swap were made for int once and swap<int> reused the cached one
+    let lex = &vm.comp.get_comp_crash().lex;
+    let int = lex.named_type("int").unwrap();
+    let u8_t = lex.named_type("u8").unwrap();
+    assert_eq!(lex.specialised.len(), 4);
+    assert!(lex.specialised.contains_key(&("copy", u8_t)));
+    assert_eq!(lex.specialised[&("swap", int)].name, "swap<int>");
+    let third = lex.words["third"].clone();
+    assert_eq!(third.runtime.input_sig[0].tp.name, "Array(u8)");
+    assert!(core::ptr::eq(third.runtime.output_sig[0].tp, u8_t));
+
+    let mut bytes = *b"abcd";
+    let mut out = PalData { int: 0 };
+    let mut slice = [
+        PalData { ptr: bytes.as_mut_ptr() as *mut PalData },
+        PalData { int: 4 },
+    ];
+    unsafe {
+        vm.param_stack.free(5).unwrap();
+        vm.param_stack.push(&mut out).unwrap();
+        vm.param_stack.push(slice.as_mut_ptr()).unwrap();
+        third.runtime.run(&mut vm);
+        vm.param_stack.pop().unwrap();
+        assert_eq!(out.int, b'c' as crate::PalInt);
+    }
+
+    vm.comp.get_comp_crash().input = Some(&mut unbound);
+    let res = unsafe { vm.respond_to_input() };
+    assert!(matches!(res, Err(PalError::UnboundGeneric("array_at"))), "{res:?}");
+}
+
+#[test]
+fn overloaded_words() {
+    use crate::PalError;
+    use crate::input::WordStream;
+    use crate::ir::CompEasyMemory;
+    use crate::lex::LexEasyMemory;
+    use crate::prelude::load_prelude;
+    use crate::types::SigError;
+    use crate::vm::CompMode;
+    use alloc::format;
+    use core::mem::ManuallyDrop;
+    use no_std_io::io::Cursor;
+
+    let good = b"1 2 + 1.5 2.0 + : addf 2.0 + ; ";
+    let nothing = b"true false + ";
+    let ambiguous = b": amb + ; ";
+    let mut good: WordStream<_, 64> = WordStream::new(Cursor::new(&good[..]));
+    let mut nothing: WordStream<_, 64> = WordStream::new(Cursor::new(&nothing[..]));
+    let mut ambiguous: WordStream<_, 64> = WordStream::new(Cursor::new(&ambiguous[..]));
+
+    let mut lex_mem = LexEasyMemory::new();
+    let mut lex = ManuallyDrop::new(lex_mem.make_lex());
+    load_prelude(&mut lex);
+    assert_eq!(lex.words["+"].overloads().count(), 2);
+    let mut comp_mem = CompEasyMemory::<32>::new();
+    let mut comp = comp_mem.make_comp(&mut lex);
+    comp.input = Some(&mut good);
+    let mut mem = VmEasyMemory::<16>::new();
+    let mut vm = mem.make_vm();
+    vm.comp = CompMode::Run(Box::new(comp));
+    unsafe { vm.respond_to_input().unwrap() };
+
+    unsafe {
+        assert_eq!((**vm.param_stack.spot(0).unwrap()).float, 3.5);
+        assert_eq!((**vm.param_stack.spot(1).unwrap()).int, 3);
+    }
+    let lex = &vm.comp.get_comp_crash().lex;
+    let addf = &lex.words["addf"].runtime;
+    assert!(addf.input_sig.is_empty());
+    assert_eq!(addf.output_sig[0].tp.name, "float");
+
+    vm.comp.get_comp_crash().input = Some(&mut nothing);
+    let res = unsafe { vm.respond_to_input() };
+    let Err(PalError::SigError(e @ SigError::NoOverload { found, .. })) = res else {
+        panic!("expected no overload {res:?}");
+    };
+    assert_eq!(found[0].name, "bool");
+    assert_eq!(format!("{e}"), "no overload of + matches (bool); candidates are (float), (int)");
+    vm.comp.get_comp_crash().reclaim_immidate();
+
+    vm.comp.get_comp_crash().input = Some(&mut ambiguous);
+    let res = unsafe { vm.respond_to_input() };
+    assert!(
+        matches!(res, Err(PalError::SigError(SigError::Ambiguous { name: "+", candidates })) if candidates.len() == 2),
+        "{res:?}"
+    );
+}
+
+#[test]
+fn raw_pointers_need_unsafe() {
+    use crate::PalError;
+    use crate::input::WordStream;
+    use crate::ir::CompEasyMemory;
+    use crate::lex::LexEasyMemory;
+    use crate::prelude::load_prelude;
+    use crate::types::SigError;
+    use crate::vm::CompMode;
+    use core::mem::ManuallyDrop;
+    use no_std_io::io::Cursor;
+
+    let good = b": load unsafe{ @<int> } ;
+        : store unsafe{ !<int> } ;
+        : loadf ( ptr(float) -- float:w ) unsafe{ @<float> } ; ";
+    let outside = b": direct @<int> ; ";
+    let caller = b": use_load load ; ";
+    let open = b": leak unsafe{ ; ";
+    let stray = b"} ";
+    let mut good: WordStream<_, 64> = WordStream::new(Cursor::new(&good[..]));
+    let mut outside: WordStream<_, 64> = WordStream::new(Cursor::new(&outside[..]));
+    let mut caller: WordStream<_, 64> = WordStream::new(Cursor::new(&caller[..]));
+    let mut open: WordStream<_, 64> = WordStream::new(Cursor::new(&open[..]));
+    let mut stray: WordStream<_, 64> = WordStream::new(Cursor::new(&stray[..]));
+
+    let mut lex_mem = LexEasyMemory::new();
+    let mut lex = ManuallyDrop::new(lex_mem.make_lex());
+    load_prelude(&mut lex);
+    let mut comp_mem = CompEasyMemory::<32>::new();
+    let mut comp = comp_mem.make_comp(&mut lex);
+    comp.input = Some(&mut good);
+    let mut mem = VmEasyMemory::<16>::new();
+    let mut vm = mem.make_vm();
+    vm.comp = CompMode::Run(Box::new(comp));
+    unsafe { vm.respond_to_input().unwrap() };
+
+    let lex = &vm.comp.get_comp_crash().lex;
+    let load = lex.words["load"].clone();
+    let store = lex.words["store"].clone();
+    assert_eq!(load.runtime.input_sig[0].tp.name, "ptr(int)");
+    assert_eq!(store.runtime.input_sig.len(), 2);
+    assert!(load.runtime.is_raw() && !lex.words["int_add"].runtime.is_raw());
+    assert_eq!(lex.words["loadf"].runtime.input_sig[0].tp.name, "ptr(float)");
+
+    let mut x = PalData { int: 7 };
+    let mut out = PalData { int: 0 };
+    let mut p = PalData { ptr: &mut x };
+    let mut v = PalData { int: 9 };
+    unsafe {
+        vm.param_stack.push(&mut out).unwrap();
+        vm.param_stack.push(&mut p).unwrap();
+        load.runtime.run(&mut vm);
+        assert_eq!(vm.param_stack.pop().unwrap(), &mut out as *mut _);
+        assert_eq!(out.int, 7);
+
+        vm.param_stack.push(&mut v).unwrap();
+        vm.param_stack.push(&mut p).unwrap();
+        store.runtime.run(&mut vm);
+        assert_eq!(vm.param_stack.len(), 0);
+        assert_eq!(x.int, 9);
+    }
+
+    vm.comp.get_comp_crash().input = Some(&mut outside);
+    let res = unsafe { vm.respond_to_input() };
+    assert!(matches!(res, Err(PalError::SigError(SigError::Unsafe("@<int>")))), "{res:?}");
+    vm.comp.get_comp_crash().abandon_word();
+    vm.comp.set_compiling(false);
+
+    //a word is as unsafe as its signature
+    vm.comp.get_comp_crash().input = Some(&mut caller);
+    let res = unsafe { vm.respond_to_input() };
+    assert!(matches!(res, Err(PalError::SigError(SigError::Unsafe("load")))), "{res:?}");
+    vm.comp.get_comp_crash().abandon_word();
+    vm.comp.set_compiling(false);
+
+    vm.comp.get_comp_crash().input = Some(&mut open);
+    let res = unsafe { vm.respond_to_input() };
+    assert!(matches!(res, Err(PalError::BadUnsafe(";"))), "{res:?}");
+    assert_eq!(vm.comp.get_comp_crash().unsafe_depth, 0);
+    vm.comp.set_compiling(false);
+
+    vm.comp.get_comp_crash().input = Some(&mut stray);
+    let res = unsafe { vm.respond_to_input() };
+    assert!(matches!(res, Err(PalError::BadUnsafe("}"))), "{res:?}");
+}
+
+#[test]
+fn typed_xts() {
+    use crate::PalError;
+    use crate::input::WordStream;
+    use crate::ir::CompEasyMemory;
+    use crate::lex::LexEasyMemory;
+    use crate::prelude::load_prelude;
+    use crate::types::SigError;
+    use crate::vm::CompMode;
+    use core::mem::ManuallyDrop;
+    use no_std_io::io::Cursor;
+
+    let good = b"1 2 ' int_add execute
+        10 [: 5 int_add ;] execute
+        : apply ' int_add execute ; 3 4 apply ";
+    let not_xt = b"1 execute ";
+    let overloaded = b"' + ";
+    let generic = b"' copy ";
+    let immidate = b"' ; ";
+    let raw = b"' @<int> ";
+    let nested = b": outer [: 1 ;] ; ";
+    let unclosed = b"[: 1 int_add ; ";
+    let mut good: WordStream<_, 64> = WordStream::new(Cursor::new(&good[..]));
+    let mut not_xt: WordStream<_, 64> = WordStream::new(Cursor::new(&not_xt[..]));
+    let mut overloaded: WordStream<_, 64> = WordStream::new(Cursor::new(&overloaded[..]));
+    let mut generic: WordStream<_, 64> = WordStream::new(Cursor::new(&generic[..]));
+    let mut immidate: WordStream<_, 64> = WordStream::new(Cursor::new(&immidate[..]));
+    let mut raw: WordStream<_, 64> = WordStream::new(Cursor::new(&raw[..]));
+    let mut nested: WordStream<_, 64> = WordStream::new(Cursor::new(&nested[..]));
+    let mut unclosed: WordStream<_, 64> = WordStream::new(Cursor::new(&unclosed[..]));
+
+    let mut lex_mem = LexEasyMemory::new();
+    let mut lex = ManuallyDrop::new(lex_mem.make_lex());
+    load_prelude(&mut lex);
+    let mut comp_mem = CompEasyMemory::<32>::new();
+    let mut comp = comp_mem.make_comp(&mut lex);
+    comp.input = Some(&mut good);
+    let mut mem = VmEasyMemory::<16>::new();
+    let mut vm = mem.make_vm();
+    vm.comp = CompMode::Run(Box::new(comp));
+    unsafe { vm.respond_to_input().unwrap() };
+
+    unsafe {
+        assert_eq!((**vm.param_stack.spot(0).unwrap()).int, 7);
+        assert_eq!((**vm.param_stack.spot(1).unwrap()).int, 15);
+        assert_eq!((**vm.param_stack.spot(2).unwrap()).int, 3);
+    }
+    let comp = vm.comp.get_comp_crash();
+    let apply = &comp.lex.words["apply"].runtime;
+    assert_eq!(apply.input_sig.len(), 1);
+    assert_eq!(apply.output_sig[0].tp.name, "int");
+    let add = comp.lex.words["int_add"].clone();
+    let (tp, _) = comp.lex.xt_of(&add);
+    assert_eq!(tp.name, "xt(int -- int:rw)");
+    assert_eq!(comp.lex.execute_word(tp).unwrap().runtime.input_sig.len(), 2);
+
+    vm.comp.get_comp_crash().input = Some(&mut not_xt);
+    let res = unsafe { vm.respond_to_input() };
+    assert!(
+        matches!(res, Err(PalError::SigError(SigError::NotCallable(Some(tp)))) if tp.name == "int"),
+        "{res:?}"
+    );
+    vm.comp.get_comp_crash().reclaim_immidate();
+
+    vm.comp.get_comp_crash().input = Some(&mut overloaded);
+    let res = unsafe { vm.respond_to_input() };
+    assert!(matches!(res, Err(PalError::SigError(SigError::Ambiguous { name: "+", .. }))), "{res:?}");
+
+    vm.comp.get_comp_crash().input = Some(&mut generic);
+    let res = unsafe { vm.respond_to_input() };
+    assert!(matches!(res, Err(PalError::UnboundGeneric("copy"))), "{res:?}");
+
+    vm.comp.get_comp_crash().input = Some(&mut immidate);
+    let res = unsafe { vm.respond_to_input() };
+    assert!(matches!(res, Err(PalError::NoXt(";"))), "{res:?}");
+
+    vm.comp.get_comp_crash().input = Some(&mut raw);
+    let res = unsafe { vm.respond_to_input() };
+    assert!(matches!(res, Err(PalError::SigError(SigError::Unsafe("@<int>")))), "{res:?}");
+
+    vm.comp.get_comp_crash().input = Some(&mut nested);
+    let res = unsafe { vm.respond_to_input() };
+    assert!(matches!(res, Err(PalError::BadQuotation("[:"))), "{res:?}");
+    vm.comp.get_comp_crash().abandon_word();
+    vm.comp.set_compiling(false);
+
+    vm.comp.get_comp_crash().input = Some(&mut unclosed);
+    let res = unsafe { vm.respond_to_input() };
+    assert!(matches!(res, Err(PalError::BadQuotation(";"))), "{res:?}");
+    assert!(vm.comp.get_comp_crash().def_name.is_none());
+}
+
+#[test]
+fn named_locals() {
+    use crate::PalError;
+    use crate::input::WordStream;
+    use crate::ir::CompEasyMemory;
+    use crate::lex::LexEasyMemory;
+    use crate::prelude::load_prelude;
+    use crate::vm::CompMode;
+    use alloc::format;
+    use core::mem::ManuallyDrop;
+    use no_std_io::io::Cursor;
+
+    let good = b": ten { t:int } t 5 copy t int_add copy ;
+        : seven { a:int } a 3 copy { b:int } b 4 copy int_add copy ;
+        0 ten 0 seven ";
+    let clash = b": bad { a:int }\n a a swap<int> ; ";
+    let unknown = b": huh { a:nope } ; ";
+    let running = b"{ a:int } ";
+    let mut good: WordStream<_, 128> = WordStream::new(Cursor::new(&good[..]));
+    let mut clash: WordStream<_, 64> = WordStream::new(Cursor::new(&clash[..]));
+    let mut unknown: WordStream<_, 64> = WordStream::new(Cursor::new(&unknown[..]));
+    let mut running: WordStream<_, 64> = WordStream::new(Cursor::new(&running[..]));
+
+    let mut lex_mem = LexEasyMemory::new();
+    let mut lex = ManuallyDrop::new(lex_mem.make_lex());
+    load_prelude(&mut lex);
+    let mut comp_mem = CompEasyMemory::<32>::new();
+    let mut comp = comp_mem.make_comp(&mut lex);
+    comp.input = Some(&mut good);
+    let mut mem = VmEasyMemory::<16>::new();
+    let mut vm = mem.make_vm();
+    vm.comp = CompMode::Run(Box::new(comp));
+    unsafe { vm.respond_to_input().unwrap() };
+
+    unsafe {
+        assert_eq!((**vm.param_stack.spot(0).unwrap()).int, 7);
+        assert_eq!((**vm.param_stack.spot(1).unwrap()).int, 10);
+    }
+    assert_eq!(vm.data_stack.len(), 0);
+    let lex = &vm.comp.get_comp_crash().lex;
+    let ten = &lex.words["ten"].runtime;
+    assert!(ten.input_sig.is_empty());
+    assert_eq!(ten.output_sig.len(), 1);
+    let code = ten.exe().inner_slice();
+    assert!(code[0].is(op::frame_alloc));
+    assert!(code[code.len() - 2].is(op::frame_free));
+
+    vm.comp.get_comp_crash().input = Some(&mut clash);
+    let res = unsafe { vm.respond_to_input() };
+    let Err(PalError::SigError(e)) = res else {
+        panic!("expected a borrow error {res:?}");
+    };
+    assert_eq!(format!("{e}"), "`a` is uniquely borrowed by `swap<int>` at line 2");
+    vm.comp.get_comp_crash().abandon_word();
+    vm.comp.set_compiling(false);
+
+    vm.comp.get_comp_crash().input = Some(&mut unknown);
+    let res = unsafe { vm.respond_to_input() };
+    assert!(matches!(res, Err(PalError::UnknownType("nope"))), "{res:?}");
+    assert!(vm.comp.get_comp_crash().def_name.is_none());
+
+    vm.comp.get_comp_crash().input = Some(&mut running);
+    let res = unsafe { vm.respond_to_input() };
+    assert!(matches!(res, Err(PalError::NotCompiling)), "{res:?}");
+}
+
+#[test]
+fn early_exit_with_locals_is_refused() {
+    use crate::PalError;
+    use crate::input::WordStream;
+    use crate::ir::CompEasyMemory;
+    use crate::lex::LexEasyMemory;
+    use crate::prelude::load_prelude;
+    use crate::types::SigError;
+    use crate::vm::CompMode;
+    use core::mem::ManuallyDrop;
+    use no_std_io::io::Cursor;
+
+    //; is the only way out of a word so a word returning from the middle is something the host adds
+    let plain = b": early leave ; early ";
+    let local = b": bad { a:int } leave ; ";
+    let mut plain: WordStream<_, 64> = WordStream::new(Cursor::new(&plain[..]));
+    let mut local: WordStream<_, 64> = WordStream::new(Cursor::new(&local[..]));
+
+    let mut lex_mem = LexEasyMemory::new();
+    let mut lex = ManuallyDrop::new(lex_mem.make_lex());
+    load_prelude(&mut lex);
+    lex.add_buildin("leave", op::ret, &[], &[], false);
+    let mut comp_mem = CompEasyMemory::<32>::new();
+    let mut comp = comp_mem.make_comp(&mut lex);
+    comp.input = Some(&mut plain);
+    let mut mem = VmEasyMemory::<16>::new();
+    let mut vm = mem.make_vm();
+    vm.comp = CompMode::Run(Box::new(comp));
+    unsafe { vm.respond_to_input().unwrap() };
+
+    vm.comp.get_comp_crash().input = Some(&mut local);
+    let res = unsafe { vm.respond_to_input() };
+    assert!(
+        matches!(res, Err(PalError::SigError(SigError::ExitWithLocals))),
+        "{res:?}"
+    );
+    vm.comp.get_comp_crash().abandon_word();
+    vm.comp.set_compiling(false);
+    assert!(!vm.comp.get_comp_crash().lex.words.contains_key("bad"));
+    assert_eq!(vm.data_stack.len(), 0);
+}
+
+#[test]
+fn globals() {
+    use crate::PalError;
+    use crate::input::WordStream;
+    use crate::ir::CompEasyMemory;
+    use crate::lex::LexEasyMemory;
+    use crate::prelude::load_prelude;
+    use crate::types::SigError;
+    use crate::types::UNIQUE_FLAG;
+    use crate::types::WRITE_FLAG;
+    use crate::vm::CompMode;
+    use core::mem::ManuallyDrop;
+    use no_std_io::io::Cursor;
+
+    let good = b"variable count : int constant limit = 5
+        : reset count limit copy drop ;
+        : bump count 1 int_add drop ;
+        reset bump bump count limit ";
+    let write_const = b": bad limit 1 int_add drop ; ";
+    let no_colon = b"variable x = 5 ";
+    let no_literal = b"constant y = nope ";
+    let mut good: WordStream<_, 128> = WordStream::new(Cursor::new(&good[..]));
+    let mut write_const: WordStream<_, 64> = WordStream::new(Cursor::new(&write_const[..]));
+    let mut no_colon: WordStream<_, 64> = WordStream::new(Cursor::new(&no_colon[..]));
+    let mut no_literal: WordStream<_, 64> = WordStream::new(Cursor::new(&no_literal[..]));
+
+    let mut lex_mem = LexEasyMemory::new();
+    let mut lex = ManuallyDrop::new(lex_mem.make_lex());
+    load_prelude(&mut lex);
+    let mut comp_mem = CompEasyMemory::<32>::new();
+    let mut comp = comp_mem.make_comp(&mut lex);
+    comp.input = Some(&mut good);
+    let mut mem = VmEasyMemory::<16>::new();
+    let mut vm = mem.make_vm();
+    vm.comp = CompMode::Run(Box::new(comp));
+    unsafe { vm.respond_to_input().unwrap() };
+
+    unsafe {
+        assert_eq!((**vm.param_stack.spot(0).unwrap()).int, 5);
+        assert_eq!((**vm.param_stack.spot(1).unwrap()).int, 7);
+    }
+    let lex = &vm.comp.get_comp_crash().lex;
+    let count = &lex.words["count"].runtime;
+    assert!(count.input_sig.is_empty());
+    assert_eq!(count.output_sig[0].permissions & UNIQUE_FLAG, 0);
+    assert_eq!(lex.words["limit"].runtime.output_sig[0].permissions & WRITE_FLAG, 0);
+    assert!(lex.words["reset"].runtime.output_sig.is_empty());
+
+    vm.comp.get_comp_crash().input = Some(&mut write_const);
+    let res = unsafe { vm.respond_to_input() };
+    assert!(matches!(res, Err(PalError::SigError(SigError::BasicSigError { .. }))), "{res:?}");
+    vm.comp.get_comp_crash().abandon_word();
+    vm.comp.set_compiling(false);
+
+    vm.comp.get_comp_crash().input = Some(&mut no_colon);
+    let res = unsafe { vm.respond_to_input() };
+    assert!(matches!(res, Err(PalError::BadGlobal("="))), "{res:?}");
+
+    vm.comp.get_comp_crash().input = Some(&mut no_literal);
+    let res = unsafe { vm.respond_to_input() };
+    assert!(matches!(res, Err(PalError::BadGlobal("nope"))), "{res:?}");
+}
+
+#[test]
+fn destructors() {
+    use crate::PalError;
+    use crate::input::WordStream;
+    use crate::ir::CompEasyMemory;
+    use crate::lex::LexEasyMemory;
+    use crate::prelude::load_prelude;
+    use crate::types::READ_FLAG;
+    use crate::types::SigItem;
+    use crate::vm::CompMode;
+    use core::mem::ManuallyDrop;
+    use core::sync::atomic::Ordering;
+    use no_std_io::io::Cursor;
+
+    //note bumps what it is handed and counts how many times it was called
+    let good = b"variable log : int
+        : note 1 int_add drop log 1 int_add drop ;
+        destructor int note
+        : two { a:int } a 1 copy drop { b:int } b 2 copy drop ;
+        two log ";
+    let missing = b"destructor int nope ";
+    let bad = b"destructor int two ";
+    //it is inlined into the epilogue so returning from it would skip freeing the frame
+    let early = b"destructor int leave ";
+    let mut good: WordStream<_, 256> = WordStream::new(Cursor::new(&good[..]));
+    let mut missing: WordStream<_, 64> = WordStream::new(Cursor::new(&missing[..]));
+    let mut bad: WordStream<_, 64> = WordStream::new(Cursor::new(&bad[..]));
+    let mut early: WordStream<_, 64> = WordStream::new(Cursor::new(&early[..]));
+
+    let mut lex_mem = LexEasyMemory::new();
+    let mut lex = ManuallyDrop::new(lex_mem.make_lex());
+    load_prelude(&mut lex);
+    let int = lex.named_type("int").unwrap();
+    let takes_int = [SigItem { tp: int, permissions: READ_FLAG }];
+    lex.add_buildin("leave", op::ret, &[], &takes_int, false);
+    let mut comp_mem = CompEasyMemory::<32>::new();
+    let mut comp = comp_mem.make_comp(&mut lex);
+    comp.input = Some(&mut good);
+    let mut mem = VmEasyMemory::<16>::new();
+    let mut vm = mem.make_vm();
+    vm.comp = CompMode::Run(Box::new(comp));
+    unsafe { vm.respond_to_input().unwrap() };
+
+    unsafe { assert_eq!((**vm.param_stack.spot(0).unwrap()).int, 2) };
+    assert_eq!(vm.data_stack.len(), 0);
+
+    //b was declared last so it goes first, it is on top of the frame
+    let lex = &vm.comp.get_comp_crash().lex;
+    let code = lex.words["two"].runtime.exe().inner_slice();
+    let tail = &code[code.len() - 6..];
+    assert!(tail[0].is(op::push_local) && tail[0].param.load(Ordering::Relaxed) as isize == 0);
+    assert!(tail[2].is(op::push_local) && tail[2].param.load(Ordering::Relaxed) as isize == 1);
+    assert!(tail[4].is(op::frame_free));
+
+    vm.comp.get_comp_crash().input = Some(&mut missing);
+    let res = unsafe { vm.respond_to_input() };
+    assert!(matches!(res, Err(PalError::Missingword("nope"))), "{res:?}");
+
+    vm.comp.get_comp_crash().input = Some(&mut bad);
+    let res = unsafe { vm.respond_to_input() };
+    assert!(matches!(res, Err(PalError::BadDestructor("two"))), "{res:?}");
+
+    vm.comp.get_comp_crash().input = Some(&mut early);
+    let res = unsafe { vm.respond_to_input() };
+    assert!(matches!(res, Err(PalError::BadDestructor("leave"))), "{res:?}");
+}
+
+#[cfg(feature = "heap")]
+#[test]
+fn heap_boxes() {
+    use crate::PalError;
+    use crate::input::WordStream;
+    use crate::ir::CompEasyMemory;
+    use crate::lex::LexEasyMemory;
+    use crate::prelude::load_prelude;
+    use crate::types::SigError;
+    use crate::vm::CompMode;
+    use core::mem::ManuallyDrop;
+    use no_std_io::io::Cursor;
+
+    let good = b": fill { b:box(int) } b alloc b open 5 copy drop ;
+        : peek { b:box(int) } b alloc b open 7 copy drop b open int_add ;
+        : trade { a:box(int) b:box(int) } a alloc a b swap drop drop ; trade
+        fill 0 peek ";
+    let freed_open = b": bad { b:box(int) } b open b free ; ";
+    let twice = b": twice { a:box(int) b:box(int) } a alloc b a copy drop ; ";
+    let payload = b"variant Ob ( some:box(int) none ) ";
+    let field = b"cluster Pair ( a:int b:box(int) ) ";
+    let over = b"fill ";
+    let mut good: WordStream<_, 256> = WordStream::new(Cursor::new(&good[..]));
+    let mut freed_open: WordStream<_, 64> = WordStream::new(Cursor::new(&freed_open[..]));
+    let mut twice: WordStream<_, 128> = WordStream::new(Cursor::new(&twice[..]));
+    let mut payload: WordStream<_, 64> = WordStream::new(Cursor::new(&payload[..]));
+    let mut field: WordStream<_, 64> = WordStream::new(Cursor::new(&field[..]));
+    let mut over: WordStream<_, 64> = WordStream::new(Cursor::new(&over[..]));
+
+    let mut lex_mem = LexEasyMemory::new();
+    let mut lex = ManuallyDrop::new(lex_mem.make_lex());
+    load_prelude(&mut lex);
+    let mut comp_mem = CompEasyMemory::<32>::new();
+    let mut comp = comp_mem.make_comp(&mut lex);
+    comp.input = Some(&mut good);
+    let mut mem = VmEasyMemory::<16>::new();
+    let mut vm = mem.make_vm();
+    vm.comp = CompMode::Run(Box::new(comp));
+    unsafe { vm.respond_to_input().unwrap() };
+
+    //every box was freed by its destructor on the way out, a swapped box just has a new owner
+    unsafe { assert_eq!((**vm.param_stack.spot(0).unwrap()).int, 7) };
+    assert_eq!(vm.heap.used, 0);
+    assert_eq!(vm.heap.peak, 8);
+    let lex = &mut vm.comp.get_comp_crash().lex;
+    let tp = lex.parse_type("box(int)").unwrap();
+    assert_eq!(lex.destructor(tp).unwrap().name, "free<int>");
+
+    vm.comp.get_comp_crash().input = Some(&mut freed_open);
+    let res = unsafe { vm.respond_to_input() };
+    assert!(
+        matches!(res, Err(PalError::SigError(SigError::NeedsUnique(_)))),
+        "{res:?}"
+    );
+    vm.comp.get_comp_crash().abandon_word();
+    vm.comp.set_compiling(false);
+
+    //a copy of an owned box would be freed twice
+    vm.comp.get_comp_crash().input = Some(&mut twice);
+    let res = unsafe { vm.respond_to_input() };
+    assert!(matches!(res, Err(PalError::MoveOnly("box(int)"))), "{res:?}");
+    vm.comp.get_comp_crash().abandon_word();
+    vm.comp.set_compiling(false);
+
+    //payloads and fields are copied in and never freed so they may not own a box either
+    for input in [&mut payload, &mut field] {
+        vm.comp.get_comp_crash().input = Some(input);
+        let res = unsafe { vm.respond_to_input() };
+        assert!(matches!(res, Err(PalError::MoveOnly("box(int)"))), "{res:?}");
+    }
+
+    extern crate std;
+    vm.heap.limit = 4;
+    vm.comp.get_comp_crash().input = Some(&mut over);
+    let res = std::panic::catch_unwind(core::panic::AssertUnwindSafe(|| unsafe {
+        vm.respond_to_input().is_ok()
+    }));
+    assert!(res.is_err(), "going over the limit must panic");
+
+    //a limit lowered under what is in use does not wrap around
+    vm.heap.used = 16;
+    vm.heap.limit = 8;
+    let mut b = PalData { ptr: core::ptr::null_mut() };
+    let code = [Code::basic(op::heap_alloc, 8), Code::basic(op::ret, 0)];
+    let res = std::panic::catch_unwind(core::panic::AssertUnwindSafe(|| unsafe {
+        vm.param_stack.push(&mut b).unwrap();
+        vm.execute_code(&Code::word(&code));
+    }));
+    let err = res.expect_err("the alloc must be refused");
+    assert_eq!(err.downcast_ref::<&str>(), Some(&"heap limit reached"));
+}
+
+#[cfg(all(unix, feature = "protected_consts"))]
+#[test]
+fn constants_are_read_only() {
+    use crate::PalData;
+    use crate::lex::LexEasyMemory;
+    use crate::prelude::load_prelude;
+    use core::mem::ManuallyDrop;
+
+    let mut lex_mem = LexEasyMemory::new();
+    let mut lex = ManuallyDrop::new(lex_mem.make_lex());
+    load_prelude(&mut lex);
+    let int = lex.basic_type("int").unwrap();
+    let a = lex.add_constant("a", int, PalData { int: 3 });
+    let b = lex.add_constant("b", int, PalData { int: 4 });
+    unsafe {
+        assert_eq!((*a).int, 3);
+        assert_eq!((*b).int, 4);
+    }
+    assert!(lex.const_mem.offset_of(b).is_some());
+    assert!(lex.data_mem.offset_of(b).is_none());
+
+    //the write has to happen in a child so the fault does not take the tests down with it
+    unsafe {
+        let pid = libc::fork();
+        assert!(pid >= 0);
+        if pid == 0 {
+            core::ptr::write_volatile(a as *mut PalData, PalData { int: 9 });
+            libc::_exit(0);
+        }
+        let mut status = 0;
+        assert_eq!(libc::waitpid(pid, &mut status, 0), pid);
+        assert!(libc::WIFSIGNALED(status));
+        assert!(matches!(libc::WTERMSIG(status), libc::SIGSEGV | libc::SIGBUS));
+        assert_eq!((*a).int, 3);
+    }
+}
+
+#[cfg(all(unix, feature = "protected_consts"))]
+#[test]
+fn constant_pages_are_unmapped_on_drop() {
+    use crate::lex::ProtectedAlloc;
+
+    let mut mem = ProtectedAlloc::new(1).unwrap();
+    let page = mem.save(7i64).unwrap() as *const i64 as *mut libc::c_void;
+    unsafe {
+        assert_eq!(libc::msync(page, 1, libc::MS_ASYNC), 0);
+        drop(mem);
+        //msync refuses addresses that are not mapped
+        assert_eq!(libc::msync(page, 1, libc::MS_ASYNC), -1);
+    }
+}
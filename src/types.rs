@@ -1,6 +1,8 @@
 use crate::lex::DelayedSlice;
 use crate::lex::Lex;
+use crate::PalData;
 use crate::lex::StackAllocator;
+use crate::lex::StackAllocatorCheckPoint;
 use crate::lex::StackWriter;
 use crate::stack::StackRef;
 use crate::stack::make_storage;
@@ -53,13 +55,111 @@ pub enum SigError<'lex> {
         found: &'lex Type<'lex>,
         wanted: &'lex Type<'lex>,
     },
-    NeedsUnique,
-    AlreadyBorrowed,
+    ///a unique borrow of a box that is already borrowed
+    NeedsUnique(BorrowConflict<'lex>),
+    ///a borrow of a box that is already uniquely borrowed
+    AlreadyBorrowed(BorrowConflict<'lex>),
     BasicSigError {
         clash: RwT,
         have: RwT,
     }, // cleaner and clearer
     MissingArgument(SigItem<'lex>),
+    ///a pure word over literals that would trap, it can not run at compile time and its literals can not be written later
+    ConstTrap(&'lex str),
+    ///a box was dropped while a box derived from it still points into it
+    DroppedWhileDerived(SigItem<'lex>),
+    ///there was nothing to drop
+    EmptyStack,
+    ///a declared signature does not fit the body of its word
+    Mismatch {
+        declared: [&'lex [SigItem<'lex>]; 2],
+        inferred: [&'lex [SigItem<'lex>]; 2],
+    },
+    ///no overload of an overloaded word takes the types on the stack (found is bottom first)
+    NoOverload {
+        name: &'lex str,
+        found: &'lex [TypeP<'lex>],
+        candidates: &'lex [&'lex [SigItem<'lex>]],
+    },
+    ///more than one overload takes the types on the stack, candidates are the ones that do
+    Ambiguous {
+        name: &'lex str,
+        candidates: &'lex [&'lex [SigItem<'lex>]],
+    },
+    ///a word whose signature has raw pointers in it was used outside of unsafe{ }
+    Unsafe(&'lex str),
+    ///execute found something other than an xt on top (None is an empty stack)
+    NotCallable(Option<TypeP<'lex>>),
+    ///code returning from the middle of a word that has locals, their frame would never be freed
+    ExitWithLocals,
+}
+
+///where a borrow was taken, the word taking it and the input line it was on (0 when unknown)
+#[derive(Debug, Clone, Copy)]
+pub struct BorrowSite<'lex> {
+    pub word: &'lex str,
+    pub line: u32,
+}
+
+///the box a borrow clashed with and who borrowed it last
+#[derive(Debug, Clone, Copy)]
+pub struct BorrowConflict<'lex> {
+    pub name: Option<&'lex str>,
+    pub tp: TypeP<'lex>,
+    pub by: Option<BorrowSite<'lex>>,
+}
+
+impl fmt::Display for BorrowSite<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}`", self.word)?;
+        if self.line > 0 {
+            write!(f, " at line {}", self.line)?;
+        }
+        Ok(())
+    }
+}
+
+///writes the box as `name` or as a box of its type when it has no name
+fn write_box(f: &mut fmt::Formatter<'_>, conflict: &BorrowConflict) -> fmt::Result {
+    match conflict.name {
+        Some(name) => write!(f, "`{name}`"),
+        None => write!(f, "a box of {}", conflict.tp.name),
+    }
+}
+
+///writes the types of a signature as `(int float)`
+fn write_inputs(f: &mut fmt::Formatter<'_>, sig: &[SigItem]) -> fmt::Result {
+    write!(f, "(")?;
+    for (i, item) in sig.iter().enumerate() {
+        if i > 0 {
+            write!(f, " ")?;
+        }
+        write!(f, "{}", item.tp.name)?;
+    }
+    write!(f, ")")
+}
+
+///permissions a box must at least be given for the item to fit
+fn item_fits(have: &SigItem, needed: &SigItem) -> bool {
+    have.tp.fits(needed.tp) && check_subset(have.permissions, needed.permissions).is_ok()
+}
+
+///writes the declared and inferred items line by line marking the ones that differ
+fn diff_items(f: &mut fmt::Formatter<'_>, declared: &[SigItem], inferred: &[SigItem]) -> fmt::Result {
+    for i in 0..declared.len().max(inferred.len()) {
+        match (declared.get(i), inferred.get(i)) {
+            (Some(d), Some(n)) if item_fits(d, n) => writeln!(f, "    {d}")?,
+            (d, n) => {
+                if let Some(d) = d {
+                    writeln!(f, "  - {d}")?;
+                }
+                if let Some(n) = n {
+                    writeln!(f, "  + {n}")?;
+                }
+            }
+        }
+    }
+    Ok(())
 }
 
 impl fmt::Display for SigError<'_> {
@@ -68,12 +168,21 @@ impl fmt::Display for SigError<'_> {
             SigError::WrongType { found, wanted } => {
                 writeln!(f, "  - expected {}, but got {}", wanted.name, found.name)
             }
-            SigError::NeedsUnique => write!(
-                f,
-                "Cannot borrow: value is already borrowed (requires unique access)"
-            ),
-            SigError::AlreadyBorrowed => {
-                write!(f, "Cannot borrow: value is currently borrowed as unique")
+            SigError::NeedsUnique(conflict) => {
+                write_box(f, conflict)?;
+                write!(f, " needs unique access but is borrowed")?;
+                match conflict.by {
+                    Some(site) => write!(f, " by {site}"),
+                    None => Ok(()),
+                }
+            }
+            SigError::AlreadyBorrowed(conflict) => {
+                write_box(f, conflict)?;
+                write!(f, " is uniquely borrowed")?;
+                match conflict.by {
+                    Some(site) => write!(f, " by {site}"),
+                    None => Ok(()),
+                }
             }
             SigError::BasicSigError { clash, have } => {
                 writeln!(f, "Signature mismatch:")?;
@@ -119,6 +228,51 @@ impl fmt::Display for SigError<'_> {
                 Ok(())
             }
             SigError::MissingArgument(a) => write!(f, "Missing an argument of type {a}"),
+            SigError::ConstTrap(word) => write!(f, "{word} traps on these literals"),
+            SigError::DroppedWhileDerived(a) => {
+                write!(f, "Cannot drop {a}: a box derived from it is still live")
+            }
+            SigError::EmptyStack => write!(f, "Cannot drop: the stack is empty"),
+            SigError::Mismatch { declared, inferred } => {
+                writeln!(f, "declared signature (-) does not match the body (+)")?;
+                writeln!(f, "  inputs:")?;
+                diff_items(f, declared[0], inferred[0])?;
+                writeln!(f, "  outputs:")?;
+                diff_items(f, declared[1], inferred[1])
+            }
+            SigError::NoOverload { name, found, candidates } => {
+                write!(f, "no overload of {name} matches (")?;
+                for (i, tp) in found.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "{}", tp.name)?;
+                }
+                write!(f, "); candidates are")?;
+                for (i, sig) in candidates.iter().enumerate() {
+                    write!(f, "{}", if i > 0 { ", " } else { " " })?;
+                    write_inputs(f, sig)?;
+                }
+                Ok(())
+            }
+            SigError::NotCallable(found) => {
+                let found = found.map_or("nothing", |tp| tp.name);
+                write!(f, "execute needs an xt on top, but got {found}")
+            }
+            SigError::ExitWithLocals => {
+                write!(f, "leaving a word early would skip freeing its locals, only ; may end it")
+            }
+            SigError::Unsafe(name) => {
+                write!(f, "{name} works on raw pointers so it can only be used inside unsafe{{ }}")
+            }
+            SigError::Ambiguous { name, candidates } => {
+                write!(f, "call to {name} is ambiguous between")?;
+                for (i, sig) in candidates.iter().enumerate() {
+                    write!(f, "{}", if i > 0 { ", " } else { " " })?;
+                    write_inputs(f, sig)?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -141,10 +295,135 @@ pub struct Type<'lex> {
 
 #[derive(Debug, Clone, Copy, Eq, Hash, PartialEq)]
 pub enum TypeInner<'lex> {
-    Basic,
+    Basic(&'lex str),
+    ///another name for the parent, boxes of either fit where the other is wanted
     Alias(TypeP<'lex>, &'lex str),
+    ///same layout as the parent but only fits where it is named, wrap and unwrap convert
+    Newtype(TypeP<'lex>, &'lex str),
     Array(TypeP<'lex>, Option<i32>),
     Cluster(DelayedSlice<'lex, TypeP<'lex>>),
+    ///a tag cell followed by the payload of one of the cases
+    Variant(DelayedSlice<'lex, VariantCase<'lex>>),
+    ///a raw pointer to a T, nothing checks what it points at so words using it need unsafe
+    Ptr(TypeP<'lex>),
+    ///an execution token for a word with these [inputs, outputs], called with execute
+    Xt([DelayedSlice<'lex, SigItem<'lex>>; 2]),
+    ///an owned pointer to a T on the heap, freed by its destructor
+    #[cfg(feature = "heap")]
+    Box(TypeP<'lex>),
+}
+
+///one alternative of a variant, cases without a payload only carry their tag
+#[derive(Debug, Clone, Copy, Eq, Hash, PartialEq)]
+pub struct VariantCase<'lex> {
+    pub name: &'lex str,
+    pub payload: Option<TypeP<'lex>>,
+}
+
+impl<'lex> Type<'lex> {
+    ///the type under all the aliases, newtypes stop the search
+    pub fn resolved(&self) -> &Type<'lex> {
+        match self.inner {
+            TypeInner::Alias(parent, _) => parent.resolved(),
+            _ => self,
+        }
+    }
+
+    ///whether a box of this type can be used where other is wanted
+    ///aliases are looked through so only newtypes and real differences in layout keep types apart
+    pub fn fits(&self, other: &Type<'lex>) -> bool {
+        core::ptr::eq(self.resolved(), other.resolved())
+    }
+
+    ///the fields of a cluster looking through aliases
+    pub fn fields(&self) -> Option<&'lex [TypeP<'lex>]> {
+        match self.inner {
+            TypeInner::Cluster(fields) => Some(fields.as_slice()),
+            TypeInner::Alias(parent, _) | TypeInner::Newtype(parent, _) => parent.fields(),
+            _ => None,
+        }
+    }
+
+    ///the cases of a variant looking through aliases
+    pub fn cases(&self) -> Option<&'lex [VariantCase<'lex>]> {
+        match self.inner {
+            TypeInner::Variant(cases) => Some(cases.as_slice()),
+            TypeInner::Alias(parent, _) | TypeInner::Newtype(parent, _) => parent.cases(),
+            _ => None,
+        }
+    }
+
+    ///whether a box of this type holds a raw pointer somewhere in it
+    pub fn is_raw(&self) -> bool {
+        match self.inner {
+            TypeInner::Ptr(_) => true,
+            //holding code is fine, execute checks the signature of the call on its own
+            TypeInner::Basic(_) | TypeInner::Xt(_) => false,
+            //the box owns what it points at so nothing else can see it
+            #[cfg(feature = "heap")]
+            TypeInner::Box(_) => false,
+            TypeInner::Alias(parent, _) | TypeInner::Newtype(parent, _) => parent.is_raw(),
+            TypeInner::Array(elem, _) => elem.is_raw(),
+            TypeInner::Cluster(fields) => fields.iter().any(|f| f.is_raw()),
+            TypeInner::Variant(cases) => cases.iter().any(|c| c.payload.is_some_and(|p| p.is_raw())),
+        }
+    }
+
+    ///whether a box of this type stores an address anywhere in it
+    pub fn holds_address(&self) -> bool {
+        match self.inner {
+            TypeInner::Basic(_) => false,
+            TypeInner::Ptr(_) | TypeInner::Xt(_) => true,
+            #[cfg(feature = "heap")]
+            TypeInner::Box(_) => true,
+            TypeInner::Alias(parent, _) | TypeInner::Newtype(parent, _) => parent.holds_address(),
+            //slices are a (ptr,len) pair
+            TypeInner::Array(_, None) => true,
+            TypeInner::Array(elem, Some(_)) => elem.holds_address(),
+            TypeInner::Cluster(fields) => fields.iter().any(|f| f.holds_address()),
+            TypeInner::Variant(cases) => cases.iter().any(|c| c.payload.is_some_and(|p| p.holds_address())),
+        }
+    }
+
+    ///whether a box of this type owns memory somewhere in it, copying one would free that memory twice
+    pub fn owns_memory(&self) -> bool {
+        match self.inner {
+            #[cfg(feature = "heap")]
+            TypeInner::Box(_) => true,
+            TypeInner::Basic(_) | TypeInner::Ptr(_) | TypeInner::Xt(_) => false,
+            TypeInner::Alias(parent, _) | TypeInner::Newtype(parent, _) => parent.owns_memory(),
+            //a slice only views its elements
+            TypeInner::Array(_, None) => false,
+            TypeInner::Array(elem, Some(_)) => elem.owns_memory(),
+            TypeInner::Cluster(fields) => fields.iter().any(|f| f.owns_memory()),
+            TypeInner::Variant(cases) => cases.iter().any(|c| c.payload.is_some_and(|p| p.owns_memory())),
+        }
+    }
+
+    ///basic types narrower than a cell are packed back to back in arrays
+    pub fn is_dense(&self) -> bool {
+        match self.inner {
+            TypeInner::Basic(_) => (self.size as usize) < size_of::<PalData>(),
+            TypeInner::Alias(parent, _) | TypeInner::Newtype(parent, _) => parent.is_dense(),
+            _ => false,
+        }
+    }
+
+    ///bytes between elements of an array of this type
+    pub fn stride(&self) -> i32 {
+        if self.is_dense() {
+            self.size
+        } else {
+            self.cells * size_of::<PalData>() as i32
+        }
+    }
+
+    ///how many cells into a cluster field i starts
+    pub fn field_offset(&self, i: usize) -> Option<i32> {
+        let fields = self.fields()?;
+        fields.get(i)?;
+        Some(fields[..i].iter().map(|f| f.cells).sum())
+    }
 }
 
 impl<'lex> TypeInner<'lex> {
@@ -154,8 +433,10 @@ impl<'lex> TypeInner<'lex> {
         }
 
         let (name, cells, size) = match self {
-            TypeInner::Basic => unreachable!("missing basic type in the table"),
-            TypeInner::Alias(parent, name) => (*name, parent.cells, parent.size),
+            TypeInner::Basic(_) => unreachable!("missing basic type in the table"),
+            TypeInner::Alias(parent, name) | TypeInner::Newtype(parent, name) => {
+                (*name, parent.cells, parent.size)
+            }
             TypeInner::Array(elem, num) => {
                 let mut writer = StackWriter::new(&mut lex.comp_data_mem);
                 match num {
@@ -167,7 +448,9 @@ impl<'lex> TypeInner<'lex> {
                     Some(len) => {
                         write!(writer, "Array<{}>({})", len, elem.name)
                             .expect("Out of memory in comp data");
-                        (&*writer.finish(), len * elem.cells, len * elem.size)
+                        let cell = size_of::<PalData>() as i32;
+                        let cells = (len * elem.stride() + cell - 1) / cell;
+                        (&*writer.finish(), cells, len * elem.size)
                     }
                 }
             }
@@ -187,6 +470,62 @@ impl<'lex> TypeInner<'lex> {
                 write!(writer, ")").expect("Out of memory in comp data");
                 (writer.finish() as &_, cells, size)
             }
+            TypeInner::Ptr(elem) => {
+                let mut writer = StackWriter::new(&mut lex.comp_data_mem);
+                write!(writer, "ptr({})", elem.name).expect("Out of memory in comp data");
+                (writer.finish() as &_, 1, size_of::<*const ()>() as i32)
+            }
+            #[cfg(feature = "heap")]
+            TypeInner::Box(elem) => {
+                let mut writer = StackWriter::new(&mut lex.comp_data_mem);
+                write!(writer, "box({})", elem.name).expect("Out of memory in comp data");
+                (writer.finish() as &_, 1, size_of::<*const ()>() as i32)
+            }
+            TypeInner::Xt(sig) => {
+                let mut writer = StackWriter::new(&mut lex.comp_data_mem);
+                write!(writer, "xt(").expect("Out of memory in comp data");
+                let [inputs, outputs] = sig;
+                //None stands for the -- between the two
+                let items = inputs.iter().map(Some).chain([None]).chain(outputs.iter().map(Some));
+                for (i, item) in items.enumerate() {
+                    let sep = if i > 0 { " " } else { "" };
+                    let Some(item) = item else {
+                        write!(writer, "{sep}--").expect("Out of memory in comp data");
+                        continue;
+                    };
+                    write!(writer, "{sep}{}", item.tp.name).expect("Out of memory in comp data");
+                    if item.permissions != READ_FLAG {
+                        write!(writer, ":").expect("Out of memory in comp data");
+                        for (flag, c) in [(READ_FLAG, 'r'), (WRITE_FLAG, 'w'), (UNIQUE_FLAG, 'u')] {
+                            if item.permissions & flag != 0 {
+                                writer.write_char(c).expect("Out of memory in comp data");
+                            }
+                        }
+                    }
+                }
+                write!(writer, ")").expect("Out of memory in comp data");
+                (writer.finish() as &_, 1, size_of::<*const ()>() as i32)
+            }
+            TypeInner::Variant(cases) => {
+                let mut writer = StackWriter::new(&mut lex.comp_data_mem);
+                let mut cells = 0;
+                let mut size = 0;
+                write!(writer, "Variant(").expect("Out of memory in comp data");
+                for (i, case) in cases.iter().enumerate() {
+                    if i > 0 {
+                        write!(writer, " | ").expect("Out of memory in comp data");
+                    }
+                    write!(writer, "{}", case.name).expect("Out of memory in comp data");
+                    if let Some(tp) = case.payload {
+                        cells = cells.max(tp.cells);
+                        size = size.max(tp.size);
+                        write!(writer, ":{}", tp.name).expect("Out of memory in comp data");
+                    }
+                }
+                write!(writer, ")").expect("Out of memory in comp data");
+                let tag = size_of::<PalData>() as i32;
+                (writer.finish() as &_, cells + 1, size + tag)
+            }
         };
         let me = lex
             .types_mem
@@ -214,7 +553,7 @@ impl<'lex> TypeInner<'lex> {
 //in PALFORTH virtually all outputs are done by injection
 //meaning a pointer to the output spot is passed to the function the output is written to it and then it remains on the stack
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct SigItem<'lex> {
     pub tp: &'lex Type<'lex>,
     pub permissions: RwT,
@@ -248,12 +587,48 @@ impl fmt::Display for SigItem<'_> {
     }
 }
 
+///a box whose value is known at compile time
+///code_at is where the code pushing it starts so it can be removed again
+#[derive(Debug, Clone, Copy)]
+pub struct ConstBox {
+    pub data: *const PalData,
+    pub code_at: StackAllocatorCheckPoint,
+}
+
 #[derive(Debug)]
 pub struct CompVar<'ctx, 'lex> {
     pub tp: &'lex Type<'lex>,
     pub offset_from_start: i32,        // first local is 0
     pub num_borrowed: &'ctx Cell<i32>, // unique borrow is -1
     pub permissions: RwT,
+    pub constant: Option<ConstBox>,
+    ///the caller provides this box, its permissions are whatever the body ends up needing
+    pub inferred: bool,
+    ///for boxes pointing into another box, that box and the field (None is anywhere in it)
+    pub derived_from: Option<(&'ctx RefCell<CompVar<'ctx, 'lex>>, Option<u32>)>,
+    ///the borrows of each field of a cluster (empty for anything else)
+    pub field_borrows: &'ctx [Cell<i32>],
+    ///no longer on the stack, a derived box stays alive untill nothing points into it
+    pub consumed: bool,
+    ///the name the source gave the box if it has one
+    pub name: Option<&'lex str>,
+    ///where the latest borrow of the box (or a part of it) was taken
+    pub borrowed_by: Cell<Option<BorrowSite<'lex>>>,
+}
+
+///checks a declared signature is at least what the inferred one needs
+pub fn sig_fits<'lex>(
+    declared: [&'lex [SigItem<'lex>]; 2],
+    inferred: [&'lex [SigItem<'lex>]; 2],
+) -> Result<(), SigError<'lex>> {
+    let fits = |d: &[SigItem], n: &[SigItem]| {
+        d.len() == n.len() && d.iter().zip(n).all(|(d, n)| item_fits(d, n))
+    };
+    if fits(declared[0], inferred[0]) && fits(declared[1], inferred[1]) {
+        Ok(())
+    } else {
+        Err(SigError::Mismatch { declared, inferred })
+    }
 }
 
 fn check_subset(have: RwT, sig: RwT) -> Result<(), SigError<'static>> {
@@ -290,44 +665,189 @@ fn check_subset(have: RwT, sig: RwT) -> Result<(), SigError<'static>> {
 pub fn use_box_as<'lex>(
     box_var: &mut CompVar<'_, 'lex>,
     sig: &SigItem<'lex>,
+    site: Option<BorrowSite<'lex>>,
 ) -> Result<(), SigError<'lex>> {
-    if box_var.tp as *const _ != sig.tp as *const _ {
+    if !box_var.tp.fits(sig.tp) {
         return Err(SigError::WrongType {
             found: box_var.tp,
             wanted: sig.tp,
         });
     }
+    //only access is inferred, how a box is passed is never something a caller can supply
+    if box_var.inferred {
+        box_var.permissions |= sig.permissions & (READ_FLAG | WRITE_FLAG | UNIQUE_FLAG);
+    }
     check_subset(box_var.permissions, sig.permissions)?;
 
-    if box_var.num_borrowed.get() == -1 {
-        return Err(SigError::AlreadyBorrowed);
+    let unique = sig.permissions & UNIQUE_FLAG != 0;
+    can_borrow(box_var, None, unique, 0)?;
+    claim(box_var, site);
+    borrow_cells(box_var, None, unique, 0, take_cell);
+    Ok(())
+}
+
+pub fn free_box_use(box_var: &mut CompVar, sig: RwT) {
+    borrow_cells(box_var, None, sig & UNIQUE_FLAG != 0, 0, free_cell);
+    release(box_var);
+}
+
+/*──────────────────  BORROWS ────────────────── */
+//every box counts its borrows (-1 is unique) and clusters count them per field as well
+//using a derived box also borrows the part of its parent it points into
+//so a unique borrow of field 0 only conflicts with borrows of field 0 or the whole cluster
+//
+//on top of that a live derived box holds a shared borrow of its parent for as long as it lives
+//so the parent can not be uniquely borrowed or dropped under it
+//held is how many of the borrows on a count belong to the box asking (its own hold)
+
+fn check_cell<'lex>(
+    var: &CompVar<'_, 'lex>,
+    c: &Cell<i32>,
+    unique: bool,
+    held: i32,
+) -> Result<(), SigError<'lex>> {
+    let conflict = || BorrowConflict {
+        name: var.name,
+        tp: var.tp,
+        by: var.borrowed_by.get(),
+    };
+    match c.get() {
+        -1 => Err(SigError::AlreadyBorrowed(conflict())),
+        n if unique && n != held => Err(SigError::NeedsUnique(conflict())),
+        _ => Ok(()),
     }
-    if (sig.permissions & UNIQUE_FLAG != 0) && box_var.num_borrowed.get() != 0 {
-        return Err(SigError::NeedsUnique);
+}
+
+///records site as the borrower of the box and what it is derived from, call before borrowing
+///a box that is already borrowed keeps the site of the borrow that is still out
+fn claim<'lex>(var: &CompVar<'_, 'lex>, site: Option<BorrowSite<'lex>>) {
+    if is_idle(var) {
+        var.borrowed_by.set(site);
     }
+    if let Some((parent, _)) = var.derived_from {
+        claim(&parent.borrow(), site);
+    }
+}
 
-    if sig.permissions & UNIQUE_FLAG != 0 {
-        box_var.num_borrowed.set(-1);
-    } else {
-        //num_borrowed++
-        box_var.num_borrowed.set(box_var.num_borrowed.get() + 1);
+///forgets the borrower of every box in the chain that is no longer borrowed
+fn release(var: &CompVar) {
+    if is_idle(var) {
+        var.borrowed_by.set(None);
+    }
+    if let Some((parent, _)) = var.derived_from {
+        release(&parent.borrow());
     }
+}
 
-    Ok(())
+fn take_cell(c: &Cell<i32>, unique: bool, _held: i32) {
+    //num_borrowed++
+    c.set(if unique { -1 } else { c.get() + 1 })
 }
 
-pub fn free_box_use(box_var: &mut CompVar, sig: RwT) {
-    if sig & UNIQUE_FLAG != 0 {
-        box_var.num_borrowed.set(0);
-    } else {
-        //num_borrowed--
-        box_var.num_borrowed.set(box_var.num_borrowed.get() - 1);
+fn free_cell(c: &Cell<i32>, unique: bool, held: i32) {
+    //num_borrowed--
+    c.set(if unique { held } else { c.get() - 1 })
+}
+
+///checks the box (or one of its fields) and everything it is derived from can be borrowed
+fn can_borrow<'lex>(
+    var: &CompVar<'_, 'lex>,
+    field: Option<u32>,
+    unique: bool,
+    held: i32,
+) -> Result<(), SigError<'lex>> {
+    match field {
+        None => {
+            check_cell(var, var.num_borrowed, unique, held)?;
+            for c in var.field_borrows {
+                check_cell(var, c, unique, 0)?;
+            }
+        }
+        Some(i) => {
+            //a field is only blocked by whole borrows that clash
+            check_cell(var, var.num_borrowed, unique, 0)?;
+            check_cell(var, &var.field_borrows[i as usize], unique, held)?;
+        }
+    }
+    match var.derived_from {
+        Some((parent, field)) => can_borrow(&parent.borrow(), field, unique, 1),
+        None => Ok(()),
+    }
+}
+
+///applies f to every count a borrow touches, only call after [`can_borrow`]
+fn borrow_cells(
+    var: &CompVar,
+    field: Option<u32>,
+    unique: bool,
+    held: i32,
+    f: fn(&Cell<i32>, bool, i32),
+) {
+    match field {
+        None => f(var.num_borrowed, unique, held),
+        Some(i) => f(&var.field_borrows[i as usize], unique, held),
+    }
+    if let Some((parent, field)) = var.derived_from {
+        borrow_cells(&parent.borrow(), field, unique, 1, f)
+    }
+}
+
+///adds to the count a derived box holds on its parent (the field or the whole box)
+fn hold(parent: &CompVar, field: Option<u32>, delta: i32) {
+    let c = match field {
+        None => parent.num_borrowed,
+        Some(i) => &parent.field_borrows[i as usize],
+    };
+    c.set(c.get() + delta)
+}
+
+///a hold taken at site
+fn claim_hold<'lex>(parent: &CompVar<'_, 'lex>, field: Option<u32>, site: Option<BorrowSite<'lex>>) {
+    if is_idle(parent) {
+        parent.borrowed_by.set(site);
+    }
+    hold(parent, field, 1);
+}
+
+///nothing is borrowing any part of the box
+fn is_idle(var: &CompVar) -> bool {
+    var.num_borrowed.get() == 0 && var.field_borrows.iter().all(|c| c.get() == 0)
+}
+
+///a consumed derived box dies once nothing points into it, releasing its parent
+///which may in turn be a consumed derived box that can now die
+fn settle(var: &RefCell<CompVar>) {
+    let mut v = var.borrow_mut();
+    if !v.consumed || !is_idle(&v) {
+        return;
     }
+    let Some((parent, field)) = v.derived_from.take() else {
+        return;
+    };
+    drop(v);
+    hold(&parent.borrow(), field, -1);
+    release(&parent.borrow());
+    settle(parent);
+}
+
+///a derived box can not have more access than its parent
+///unless the parent is inferred in which case it is asked for whatever the child needs
+fn derived_permissions(parent: &mut CompVar, permissions: RwT) -> RwT {
+    const RWU: RwT = READ_FLAG | WRITE_FLAG | UNIQUE_FLAG;
+    let permissions = permissions & !INDEX_FLAG;
+    if parent.inferred {
+        parent.permissions |= permissions & RWU;
+    }
+    permissions & (parent.permissions | !RWU)
 }
 
 /// # Safety
 /// changing any of the underlying stacks is considered unsound
 pub struct SigStack<'me, 'lex> {
+    ///when set missing arguments are not an error, they become boxes the caller provides
+    pub infer: bool,
+    ///the word being checked, borrows it takes are recorded as taken here
+    pub site: Option<BorrowSite<'lex>>,
     cells_locals: i32,
     var_arena: StackAllocator<'me, RefCell<CompVar<'me, 'lex>>>,
     borrows_arena: StackAllocator<'me, Cell<i32>>,
@@ -346,42 +866,273 @@ impl<'me, 'lex> SigStack<'me, 'lex> {
             permissions: READ_FLAG | WRITE_FLAG | UNIQUE_FLAG,
             num_borrowed,
             offset_from_start: self.cells_locals,
+            constant: None,
+            inferred: false,
+            derived_from: None,
+            field_borrows: self.add_field_borrows(tp),
+            consumed: false,
+            name: None,
+            borrowed_by: Cell::new(None),
         };
         let ans = self.var_arena.save(var.into()).expect("overflow var arena");
         self.cells_locals += tp.cells;
         ans
     }
 
+    ///the cells taken by the locals made so far
+    #[inline]
+    pub fn local_cells(&self) -> i32 {
+        self.cells_locals
+    }
+
+    ///every local made so far in the order they were declared
+    pub fn locals(&self) -> impl DoubleEndedIterator<Item = &'me RefCell<CompVar<'me, 'lex>>> {
+        self.var_arena
+            .index_checkpoint(StackAllocatorCheckPoint(0))
+            .iter()
+            .filter(|v| v.borrow().offset_from_start >= 0)
+    }
+
+    ///the newest local with that name
+    pub fn find_local(&self, name: &str) -> Option<&'me RefCell<CompVar<'me, 'lex>>> {
+        self.var_arena
+            .index_checkpoint(StackAllocatorCheckPoint(0))
+            .iter()
+            .rev()
+            .find(|v| {
+                let v = v.borrow();
+                v.offset_from_start >= 0 && v.name == Some(name)
+            })
+    }
+
     pub fn add_borrows(&mut self, num: i32) -> &'me Cell<i32> {
         self.borrows_arena
             .save(Cell::new(num))
             .expect("overflow borrow arena")
     }
 
+    ///one count per field if tp is a cluster
+    pub fn add_field_borrows(&mut self, tp: &'lex Type<'lex>) -> &'me [Cell<i32>] {
+        let start = self.borrows_arena.check_point();
+        for _ in 0..tp.fields().map_or(0, |f| f.len()) {
+            self.add_borrows(0);
+        }
+        self.borrows_arena.index_checkpoint(start)
+    }
+
+    ///pushes a new box which is not a local (a literal or the result of a call)
+    pub fn push_box(
+        &mut self,
+        tp: &'lex Type<'lex>,
+        permissions: RwT,
+        constant: Option<ConstBox>,
+    ) -> &'me RefCell<CompVar<'me, 'lex>> {
+        let num_borrowed = self.add_borrows(0);
+        let var = CompVar {
+            tp,
+            permissions,
+            num_borrowed,
+            offset_from_start: -1,
+            constant,
+            inferred: false,
+            derived_from: None,
+            field_borrows: self.add_field_borrows(tp),
+            consumed: false,
+            name: None,
+            borrowed_by: Cell::new(None),
+        };
+        let ans = self.var_arena.save(var.into()).expect("overflow var arena");
+        self.stack.push(ans).expect("overflow sig stack");
+        ans
+    }
+
+    ///pushes a box pointing into parent (at field, None is anywhere in it) without consuming it
+    ///the new box holds its parent for as long as it lives like the INDEX outputs of a call
+    pub fn push_derived(
+        &mut self,
+        parent: &'me RefCell<CompVar<'me, 'lex>>,
+        tp: &'lex Type<'lex>,
+        permissions: RwT,
+        field: Option<u32>,
+    ) -> &'me RefCell<CompVar<'me, 'lex>> {
+        claim_hold(&parent.borrow(), field, self.site);
+        let permissions = derived_permissions(&mut parent.borrow_mut(), permissions);
+        let child = self.push_box(tp, permissions, None);
+        child.borrow_mut().derived_from = Some((parent, field));
+        child
+    }
+
+    ///consumes a box that was taken off the stack by hand, it dies once nothing points into it
+    pub fn retire(&mut self, var: &'me RefCell<CompVar<'me, 'lex>>) {
+        var.borrow_mut().consumed = true;
+        settle(var);
+    }
+
+    ///whether the boxes on top have the types sig takes (permissions are left to the call)
+    ///while inferring missing boxes fit since the caller will provide them
+    pub fn tops_fit(&self, sig: &[SigItem<'lex>]) -> bool {
+        let n = self.stack.len().min(sig.len());
+        if n < sig.len() && !self.infer {
+            return false;
+        }
+        let tops = self.stack.peek_many(n).expect("len was checked");
+        sig.iter().rev().zip(tops).all(|(item, b)| b.borrow().tp.fits(item.tp))
+    }
+
+    ///puts a caller provided box under everything else on the stack
+    pub fn push_inferred_below(&mut self, tp: &'lex Type<'lex>) {
+        let num_borrowed = self.add_borrows(0);
+        let var = CompVar {
+            tp,
+            permissions: 0,
+            num_borrowed,
+            offset_from_start: -1,
+            constant: None,
+            inferred: true,
+            derived_from: None,
+            field_borrows: self.add_field_borrows(tp),
+            consumed: false,
+            name: None,
+            borrowed_by: Cell::new(None),
+        };
+        let ans = self.var_arena.save(var.into()).expect("overflow var arena");
+
+        //slide the live part down one spot and write at the bottom
+        let len = self.stack.len();
+        unsafe {
+            self.stack.alloc(1).expect("overflow sig stack");
+            let head = self.stack.get_head();
+            core::ptr::copy(head.add(1), head, len);
+            head.add(len).write(ans);
+        }
+    }
+
+    ///every caller provided box made so far, the first one made is the top most
+    pub fn inferred(&self) -> impl DoubleEndedIterator<Item = &'me RefCell<CompVar<'me, 'lex>>> {
+        self.var_arena
+            .index_checkpoint(StackAllocatorCheckPoint(0))
+            .iter()
+            .filter(|v| v.borrow().inferred)
+    }
+
+    ///drops every box
+    pub fn clear(&mut self) {
+        self.stack.free(self.stack.len());
+        self.reclaim();
+    }
+
+    ///once nothing is on the stack all the bookkeeping can be thrown away
+    pub fn reclaim(&mut self) {
+        if !self.stack.is_empty() {
+            return;
+        }
+        self.cells_locals = 0;
+        unsafe {
+            self.var_arena.goto_checkpoint(StackAllocatorCheckPoint(0));
+            self.borrows_arena
+                .goto_checkpoint(StackAllocatorCheckPoint(0));
+        }
+    }
+
     ///checks a signature and pops out the inputs from the argument stack
-    ///on faliure the stack is left in a weird but safe state
+    ///borrows only last for the duration of the call so they are released right after
+    ///on faliure the stack is left untouched
     pub fn call_sig(
         &mut self,
         outputs: &[SigItem<'lex>],
         inputs: &[SigItem<'lex>],
     ) -> Result<(), SigError<'lex>> {
-        for t in inputs.iter().rev() {
-            match self.stack.pop() {
-                None => return Err(SigError::MissingArgument(*t)),
-                Some(b) => use_box_as(&mut b.borrow_mut(), t)?,
+        self.call_sig_derived(outputs, inputs, None)
+    }
+
+    ///same as [`SigStack::call_sig`] but INDEX outputs point at field of the first input
+    ///INDEX outputs are not passed in, they are pushed after the inputs are consumed
+    ///their permissions are limited by the box they are derived from (if the word takes any inputs)
+    pub fn call_sig_derived(
+        &mut self,
+        outputs: &[SigItem<'lex>],
+        inputs: &[SigItem<'lex>],
+        field: Option<u32>,
+    ) -> Result<(), SigError<'lex>> {
+        let passed = |o: &&SigItem| o.permissions & INDEX_FLAG == 0;
+        let total = inputs.len() + outputs.iter().filter(passed).count();
+
+        //inputs are on top and outputs are right below them
+        let sigs = || inputs.iter().rev().chain(outputs.iter().rev().filter(passed));
+
+        if self.infer {
+            for t in sigs().skip(self.stack.len()) {
+                self.push_inferred_below(t.tp);
+            }
+        }
+
+        let boxes = self
+            .stack
+            .peek_many(total.min(self.stack.len()))
+            .expect("len was checked");
+
+        let mut used = 0;
+        let mut res = Ok(());
+        for t in sigs() {
+            res = match boxes.get(used) {
+                None => Err(SigError::MissingArgument(*t)),
+                Some(b) => use_box_as(&mut b.borrow_mut(), t, self.site),
             };
+            if res.is_err() {
+                break;
+            }
+            used += 1;
         }
 
-        //checkpoint here so outputs arent poped
-        let mut stack = StackRef::new_full(self.stack.split().0);
+        for (b, t) in boxes.iter().zip(sigs()).take(used) {
+            free_box_use(&mut b.borrow_mut(), t.permissions);
+        }
+        res?;
 
-        for t in outputs.iter().rev() {
-            match stack.pop() {
-                None => return Err(SigError::MissingArgument(*t)),
-                Some(b) => use_box_as(&mut b.borrow_mut(), t)?,
+        let parent = inputs.len().checked_sub(1).map(|i| boxes[i]);
+        let derived = outputs.iter().filter(|o| !passed(o));
+
+        //the children hold their parent for as long as they live
+        //so this has to happen before the inputs die
+        if let Some(parent) = parent {
+            for _ in derived.clone() {
+                claim_hold(&parent.borrow(), field, self.site);
+            }
+        }
+        for b in &boxes[..inputs.len()] {
+            b.borrow_mut().consumed = true;
+            settle(b);
+        }
+        self.stack.free(inputs.len());
+
+        for o in derived {
+            //with no inputs the output points at memory that outlives the word such as a global
+            let Some(parent) = parent else {
+                self.push_box(o.tp, o.permissions & !INDEX_FLAG, None);
+                continue;
             };
+            let permissions = derived_permissions(&mut parent.borrow_mut(), o.permissions);
+            let child = self.push_box(o.tp, permissions, None);
+            child.borrow_mut().derived_from = Some((parent, field));
         }
+        Ok(())
+    }
 
+    ///pops the top box for good, a box something still points into can not be dropped
+    pub fn drop_top(&mut self) -> Result<(), SigError<'lex>> {
+        let b = *self.stack.peek().ok_or(SigError::EmptyStack)?;
+        {
+            let v = b.borrow();
+            if !is_idle(&v) {
+                return Err(SigError::DroppedWhileDerived(SigItem {
+                    tp: v.tp,
+                    permissions: v.permissions,
+                }));
+            }
+        }
+        self.stack.free(1);
+        b.borrow_mut().consumed = true;
+        settle(b);
         Ok(())
     }
 }
@@ -410,6 +1161,8 @@ impl<'me, 'lex, const STACK_SIZE: usize> SigStackEasyMemory<'me, 'lex, STACK_SIZ
 
     pub fn make_sig_stack(&'me mut self) -> SigStack<'me, 'lex> {
         SigStack {
+            infer: false,
+            site: None,
             cells_locals: 0,
             var_arena: StackAllocator::new(&mut self.var_arena_mem),
             borrows_arena: StackAllocator::new(&mut self.borrows_arena_mem),
@@ -424,13 +1177,13 @@ impl<'me, 'lex, const STACK_SIZE: usize> SigStackEasyMemory<'me, 'lex, STACK_SIZ
 fn make_types() -> (Type<'static>, Type<'static>) {
     (
         Type {
-            inner: TypeInner::Basic,
+            inner: TypeInner::Basic("int"),
             size: 4,
             cells: 1,
             name: "int",
         },
         Type {
-            inner: TypeInner::Basic,
+            inner: TypeInner::Basic("float"),
             size: 4,
             cells: 1,
             name: "float",
@@ -454,6 +1207,13 @@ fn sig_stack_success_case() {
             offset_from_start: 1,
             num_borrowed,
             permissions: READ_FLAG,
+            constant: None,
+            inferred: false,
+            derived_from: None,
+            field_borrows: &[],
+            consumed: false,
+            name: None,
+            borrowed_by: Cell::new(None),
         }))
         .unwrap();
     sig_stack.stack.push(var1).unwrap();
@@ -487,6 +1247,13 @@ fn sig_stack_wrong_type_error() {
             offset_from_start: 0,
             num_borrowed,
             permissions: READ_FLAG | WRITE_FLAG | UNIQUE_FLAG,
+            constant: None,
+            inferred: false,
+            derived_from: None,
+            field_borrows: &[],
+            consumed: false,
+            name: None,
+            borrowed_by: Cell::new(None),
         }))
         .unwrap();
     sig_stack.stack.push(var1).unwrap();
@@ -538,6 +1305,13 @@ fn sig_stack_permission_error() {
             offset_from_start: 0,
             num_borrowed,
             permissions: READ_FLAG,
+            constant: None,
+            inferred: false,
+            derived_from: None,
+            field_borrows: &[],
+            consumed: false,
+            name: None,
+            borrowed_by: Cell::new(None),
         }))
         .unwrap();
     sig_stack.stack.push(var1).unwrap();
@@ -570,6 +1344,13 @@ fn sig_stack_needs_unique_error() {
             offset_from_start: 0,
             num_borrowed,
             permissions: READ_FLAG | UNIQUE_FLAG,
+            constant: None,
+            inferred: false,
+            derived_from: None,
+            field_borrows: &[],
+            consumed: false,
+            name: None,
+            borrowed_by: Cell::new(None),
         }))
         .unwrap();
     sig_stack.stack.push(var1).unwrap();
@@ -586,7 +1367,8 @@ fn sig_stack_needs_unique_error() {
         },
     ];
     let err = sig_stack.call_sig(&[], &inputs).unwrap_err();
-    assert!(matches!(err, SigError::NeedsUnique));
+    assert!(matches!(err, SigError::NeedsUnique(_)));
+    assert_eq!(alloc::format!("{err}"), "a box of int needs unique access but is borrowed");
 }
 
 #[test]
@@ -603,6 +1385,13 @@ fn sig_stack_already_borrowed_error() {
             offset_from_start: 0,
             num_borrowed,
             permissions: READ_FLAG | UNIQUE_FLAG,
+            constant: None,
+            inferred: false,
+            derived_from: None,
+            field_borrows: &[],
+            consumed: false,
+            name: None,
+            borrowed_by: Cell::new(None),
         }))
         .unwrap();
     sig_stack.stack.push(var1).unwrap();
@@ -612,5 +1401,151 @@ fn sig_stack_already_borrowed_error() {
         permissions: READ_FLAG,
     }];
     let err = sig_stack.call_sig(&[], &inputs).unwrap_err();
-    assert!(matches!(err, SigError::AlreadyBorrowed));
+    assert!(matches!(err, SigError::AlreadyBorrowed(_)));
+}
+
+#[test]
+fn sig_stack_infers_missing_arguments() {
+    let (type_int, type_float) = make_types();
+    let mut sig_mem = SigStackEasyMemory::<'_, '_, 1024>::new();
+    let mut sig_stack = sig_mem.make_sig_stack();
+    sig_stack.infer = true;
+
+    //one float is already there so only the int is made for this call
+    let float = sig_stack.push_box(&type_float, READ_FLAG, None);
+    let outputs = [SigItem {
+        tp: &type_int,
+        permissions: READ_FLAG | WRITE_FLAG,
+    }];
+    let inputs = [SigItem {
+        tp: &type_float,
+        permissions: READ_FLAG,
+    }];
+    sig_stack.call_sig(&outputs, &inputs).unwrap();
+    assert_eq!(sig_stack.stack.len(), 1);
+
+    //now the float below the int is missing too
+    let inputs = [
+        SigItem {
+            tp: &type_float,
+            permissions: READ_FLAG,
+        },
+        SigItem {
+            tp: &type_int,
+            permissions: READ_FLAG | UNIQUE_FLAG,
+        },
+    ];
+    sig_stack.call_sig(&[], &inputs).unwrap();
+    assert!(sig_stack.stack.is_empty());
+
+    let made: Vec<_> = sig_stack
+        .inferred()
+        .map(|v| (v.borrow().tp, v.borrow().permissions))
+        .collect();
+    assert_eq!(made.len(), 2);
+    assert!(core::ptr::eq(made[0].0, &type_int));
+    assert_eq!(made[0].1, READ_FLAG | WRITE_FLAG | UNIQUE_FLAG);
+    assert!(core::ptr::eq(made[1].0, &type_float));
+    assert_eq!(made[1].1, READ_FLAG);
+    assert!(!float.borrow().inferred);
+}
+
+#[test]
+fn sig_stack_infers_only_access() {
+    let (type_int, _) = make_types();
+    let mut sig_mem = SigStackEasyMemory::<'_, '_, 1024>::new();
+    let mut sig_stack = sig_mem.make_sig_stack();
+    sig_stack.infer = true;
+
+    //a raw input is passed on the data stack so no box on the param stack can stand in for it
+    let raw = [SigItem {
+        tp: &type_int,
+        permissions: READ_FLAG | RAW_FLAG,
+    }];
+    let err = sig_stack.call_sig(&[], &raw).unwrap_err();
+    assert!(matches!(err, SigError::BasicSigError { clash: RAW_FLAG, .. }));
+
+    let made: Vec<_> = sig_stack.inferred().map(|v| v.borrow().permissions).collect();
+    assert_eq!(made, [READ_FLAG]);
+}
+
+#[test]
+fn sig_stack_derived_boxes_hold_their_parent() {
+    let (type_int, type_float) = make_types();
+    let mut sig_mem = SigStackEasyMemory::<'_, '_, 1024>::new();
+    let mut sig_stack = sig_mem.make_sig_stack();
+    let rwu = READ_FLAG | WRITE_FLAG | UNIQUE_FLAG;
+    let item = |tp, permissions| SigItem { tp, permissions };
+    let index = [item(&type_int, rwu | INDEX_FLAG)];
+    let take_parent = [item(&type_float, 0)];
+    let take_child = [item(&type_int, 0)];
+
+    let parent = sig_stack.push_box(&type_float, rwu, None);
+    sig_stack.stack.push(parent).unwrap();
+    sig_stack.call_sig_derived(&index, &take_parent, None).unwrap();
+    let child = *sig_stack.stack.peek().unwrap();
+    assert_eq!(parent.borrow().num_borrowed.get(), 1);
+
+    //the parent can be shared but not uniquely borrowed or dropped under the child
+    sig_stack.stack.push(parent).unwrap();
+    let err = sig_stack.call_sig(&[], &[item(&type_float, rwu)]).unwrap_err();
+    assert!(matches!(err, SigError::NeedsUnique(_)));
+    let err = sig_stack.drop_top().unwrap_err();
+    assert!(matches!(err, SigError::DroppedWhileDerived(_)));
+    sig_stack.call_sig(&[], &[item(&type_float, READ_FLAG)]).unwrap();
+
+    //the child itself can still be used uniquely, a derived box of it keeps the parent held
+    sig_stack.call_sig(&[item(&type_int, rwu)], &[]).unwrap();
+    sig_stack.call_sig_derived(&index, &take_child, None).unwrap();
+    assert!(child.borrow().consumed);
+    assert_eq!(parent.borrow().num_borrowed.get(), 1);
+
+    //once the last box pointing into the parent dies everything is released
+    sig_stack.drop_top().unwrap();
+    assert_eq!(child.borrow().num_borrowed.get(), 0);
+    assert_eq!(parent.borrow().num_borrowed.get(), 0);
+    sig_stack.stack.push(parent).unwrap();
+    sig_stack.call_sig(&[], &[item(&type_float, rwu)]).unwrap();
+    sig_stack.drop_top().unwrap();
+    assert!(matches!(sig_stack.drop_top(), Err(SigError::EmptyStack)));
+}
+
+#[test]
+fn sig_stack_borrow_errors_name_the_borrower() {
+    use alloc::format;
+
+    let (type_int, type_float) = make_types();
+    let mut sig_mem = SigStackEasyMemory::<'_, '_, 1024>::new();
+    let mut sig_stack = sig_mem.make_sig_stack();
+    let rwu = READ_FLAG | WRITE_FLAG | UNIQUE_FLAG;
+    let item = |tp, permissions| SigItem { tp, permissions };
+    let at = |word, line| Some(BorrowSite { word, line });
+
+    let buf = sig_stack.add_local(&type_float);
+    buf.borrow_mut().name = Some("buf");
+
+    //the borrow still out is the one reported, later shared uses do not replace it
+    sig_stack.site = at("fill", 7);
+    sig_stack.push_derived(buf, &type_int, rwu, None);
+    sig_stack.site = at("print", 8);
+    sig_stack.stack.push(buf).unwrap();
+    sig_stack.call_sig(&[], &[item(&type_float, READ_FLAG)]).unwrap();
+    sig_stack.site = at("clear", 9);
+    sig_stack.stack.push(buf).unwrap();
+    let err = sig_stack.call_sig(&[], &[item(&type_float, rwu)]).unwrap_err();
+    assert_eq!(format!("{err}"), "`buf` needs unique access but is borrowed by `fill` at line 7");
+    sig_stack.stack.free(1).unwrap();
+
+    //once the child is gone so is the borrow
+    sig_stack.drop_top().unwrap();
+    assert!(buf.borrow().borrowed_by.get().is_none());
+
+    //borrowing twice in one call blames the call itself
+    sig_stack.site = at("swap", 10);
+    sig_stack.stack.push(buf).unwrap();
+    sig_stack.stack.push(buf).unwrap();
+    let inputs = [item(&type_float, READ_FLAG), item(&type_float, rwu)];
+    let err = sig_stack.call_sig(&[], &inputs).unwrap_err();
+    assert!(matches!(err, SigError::AlreadyBorrowed(_)));
+    assert_eq!(format!("{err}"), "`buf` is uniquely borrowed by `swap` at line 10");
 }
@@ -3,9 +3,11 @@ use crate::Box;
 use no_std_io::io::Write;
 use crate::DefualtLogger;
 use crate::PalData;
+use crate::buildins::op;
 use crate::buildins::unwrap_over;
 use crate::buildins::unwrap_under;
 use crate::ir::CompContext;
+use crate::ir::parse_literal;
 use crate::stack::StackRef;
 use crate::stack::make_storage;
 use core::mem::MaybeUninit;
@@ -17,6 +19,64 @@ use core::sync::atomic::Ordering;
 pub type BuildinFunc =
     for<'vm> unsafe extern "C-unwind" fn(*const Code, &mut Vm<'vm, '_, '_>) -> *const Code;
 
+///stable id of a buildin so code can be recognized after it is built
+///function addresses cant do this since rust may give a function many addresses (or many functions one)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Op(pub u16);
+
+impl Op {
+    ///derived words and buildins the host defined
+    pub const NONE: Op = Op(0);
+}
+
+///a buildin function with its [`Op`]
+///the ones this crate defines are in [`crate::buildins::op`]
+#[derive(Debug, Clone, Copy)]
+pub struct Buildin {
+    pub f: BuildinFunc,
+    pub op: Op,
+}
+
+impl Buildin {
+    #[inline]
+    pub const fn new(f: BuildinFunc, op: Op) -> Self {
+        Self { f, op }
+    }
+
+    ///a buildin the host defined, code using it is never recognized as anything
+    #[inline]
+    pub const fn host(f: BuildinFunc) -> Self {
+        Self::new(f, Op::NONE)
+    }
+}
+
+///head of the param stack as fast buildins see it (the lowest live element)
+pub type ParamHead = *mut *mut PalData;
+
+///a buildin that only touches the param stack
+///it gets the param, the head and the bounds (above, end) of the stack and returns the new head
+///fast buildins cant do control flow, the next cell allways runs
+pub type FastFunc =
+    unsafe extern "C-unwind" fn(*const Code, ParamHead, ParamHead, ParamHead) -> ParamHead;
+
+///what a [`fast_call`](crate::buildins::fast_call) cell points to
+///[`FastCode::slow`] is the plain buildin it does the work of, tools that read code see that one
+#[derive(Debug)]
+pub struct FastCode {
+    pub f: FastFunc,
+    pub param: *const Code,
+    pub slow: Buildin,
+}
+
+//the param is as constant as the one in Code
+unsafe impl Sync for FastCode {}
+
+impl FastCode {
+    pub const fn new(f: FastFunc, param: *const Code, slow: Buildin) -> Self {
+        Self { f, param, slow }
+    }
+}
+
 #[derive(Debug)]
 pub struct BuildinPtr {
     inner: AtomicPtr<()>,
@@ -66,6 +126,7 @@ pub struct Code {
     //in general only one of this is acessed atomic at any moment
     pub f: BuildinPtr,
     pub param: AtomicPtr<Code>, //this is const
+    pub op: Op,
 }
 
 // #[derive(Debug,Clone,Copy,PartialEq)]
@@ -76,18 +137,20 @@ pub struct Code {
 
 impl Code {
     #[inline]
-    pub fn basic(f: BuildinFunc, v: isize) -> Self {
+    pub fn basic(b: Buildin, v: isize) -> Self {
         Code {
-            f: BuildinPtr::new(f),
+            f: BuildinPtr::new(b.f),
             param: AtomicPtr::new(v as *mut Code),
+            op: b.op,
         }
     }
 
     #[inline]
-    pub fn basic_raw(f: BuildinFunc, p: *const Code) -> Self {
+    pub fn basic_raw(b: Buildin, p: *const Code) -> Self {
         Code {
-            f: BuildinPtr::new(f),
+            f: BuildinPtr::new(b.f),
             param: AtomicPtr::new(p as *mut _),
+            op: b.op,
         }
     }
 
@@ -96,6 +159,7 @@ impl Code {
         Code {
             f: BuildinPtr::empty(),
             param: AtomicPtr::new(c as *const [_] as *const _ as *mut _),
+            op: Op::NONE,
         }
     }
 
@@ -104,9 +168,24 @@ impl Code {
         Code {
             f: BuildinPtr::empty(),
             param: AtomicPtr::new(param as *mut _),
+            op: Op::NONE,
         }
     }
 
+    ///checks if this runs the specific buildin b
+    ///host buildins and derived words are never anything
+    #[inline]
+    pub fn is(&self, b: Buildin) -> bool {
+        self.op != Op::NONE && self.op == b.op
+    }
+
+    ///a cell running a fast buildin, works with both dispatch loops
+    ///like [`Code::word`] f must outlive the code
+    #[inline]
+    pub fn fast(f: &FastCode) -> Self {
+        Code::basic_raw(op::fast_call, f as *const FastCode as *const Code)
+    }
+
     #[inline]
     pub fn is_null(&self) -> bool {
         self.f.load(Ordering::Relaxed).is_none() && self.param.load(Ordering::Relaxed).is_null()
@@ -115,7 +194,7 @@ impl Code {
     #[inline(always)]
     pub fn shallow_clone(&self) -> Self {
         match self.f.load(Ordering::Relaxed) {
-            Some(f) => Code::basic_raw(f, self.param.load(Ordering::Relaxed)),
+            Some(f) => Code::basic_raw(Buildin::new(f, self.op), self.param.load(Ordering::Relaxed)),
             None => Code::word_raw(self.param.load(Ordering::Relaxed)),
         }
     }
@@ -137,7 +216,7 @@ impl<const STACK_SIZE: usize> Default for VmEasyMemory<STACK_SIZE> {
     }
 }
 
-impl<'lex, const STACK_SIZE: usize> VmEasyMemory<STACK_SIZE> {
+impl<const STACK_SIZE: usize> VmEasyMemory<STACK_SIZE> {
     pub fn new() -> Self {
         Self::default()
     }
@@ -149,6 +228,8 @@ impl<'lex, const STACK_SIZE: usize> VmEasyMemory<STACK_SIZE> {
             return_stack: StackRef::from_slice(&mut self.rs),
             comp: CompMode::Task,
             output:DefualtLogger::new_ref(),
+            #[cfg(feature = "heap")]
+            heap: HeapUsage::default(),
         }
     }
 }
@@ -166,6 +247,20 @@ impl<'lex,'comp> CompMode<'comp,'lex> {
             CompMode::Comp(comp) | CompMode::Run(comp) => comp,
         }
     }
+
+    ///moves the context into compile mode (or out of it) keeping everything else
+    pub fn set_compiling(&mut self, compiling: bool) {
+        *self = match core::mem::replace(self, CompMode::Task) {
+            CompMode::Task => panic!("need compile time context to switch modes"),
+            CompMode::Comp(comp) | CompMode::Run(comp) => {
+                if compiling {
+                    CompMode::Comp(comp)
+                } else {
+                    CompMode::Run(comp)
+                }
+            }
+        }
+    }
 }
 
 pub struct Vm<'me, 'lex,'comp> {
@@ -174,6 +269,31 @@ pub struct Vm<'me, 'lex,'comp> {
     pub return_stack: StackRef<'me, *const Code>,
     pub comp: CompMode<'comp,'lex>,
     pub output: &'me mut dyn Write,
+    #[cfg(feature = "heap")]
+    pub heap: HeapUsage,
+}
+
+///what a vm has taken from the heap, hosts set the limit to cap a script
+#[cfg(feature = "heap")]
+#[derive(Debug, Clone, Copy)]
+pub struct HeapUsage {
+    ///bytes allocated right now
+    pub used: usize,
+    ///the most bytes that were ever allocated at once
+    pub peak: usize,
+    ///an alloc that would take used over this panics instead
+    pub limit: usize,
+}
+
+#[cfg(feature = "heap")]
+impl Default for HeapUsage {
+    fn default() -> Self {
+        Self {
+            used: 0,
+            peak: 0,
+            limit: usize::MAX,
+        }
+    }
 }
 
 impl Vm<'_, '_, '_> {
@@ -201,14 +321,25 @@ impl Vm<'_, '_, '_> {
                     // again we run into a core lifetime issue in rust where None and Some both borrow s
                     // even if s is only used in None
                     let sp = s as *const str;
-                    match comp.lex.words.get(s){
+                    match comp.find_word(unsafe{&*sp},true)? {
                     	Some(word)=> unsafe {
-                        	word.runtime.clone().comp_run_checked(self)?;
+                        	word.runtime.comp_run_checked(self)?;
                     	},
-                    	//reborrow s since we did not call self
-                    	None => return Err(PalError::Missingword(unsafe{&*sp}))
+                    	None => match parse_literal(comp.lex,unsafe{&*sp}) {
+                    		Some((tp,value)) => {
+                    			let data = comp.run_literal(tp,value);
+                    			unsafe{unwrap_over(self.param_stack.push(data).ok())};
+                    		}
+                    		//reborrow s since we did not call self
+                    		None => return Err(PalError::Missingword(unsafe{&*sp}))
+                    	}
                     }
 
+                    let comp = self.comp.get_comp_crash();
+                    if let Some(e) = comp.error.take() {
+                    	return Err(e)
+                    }
+                    comp.reclaim_immidate();
 
                 }
                 CompMode::Comp(comp) => {
@@ -226,19 +357,28 @@ impl Vm<'_, '_, '_> {
                     // again we run into a core lifetime issue in rust where None and Some both borrow s
                     // even if s is only used in None
                     let sp = s as *const str;
-                	match comp.lex.words.get(s){
+                    if comp.push_named_local(unsafe{&*sp}) {
+                    	continue;
+                    }
+                	match comp.find_word(unsafe{&*sp},false)? {
                     	Some(word)=> {
                     		if let Some(im) = word.immidate {
 		                        unsafe {
 		                            //no typecheck needed
 		                            self.execute_code(im)
 		                        }
+		                        if let Some(e) = self.comp.get_comp_crash().error.take() {
+		                        	return Err(e)
+		                        }
 		                    } else {
-		                        comp.add_runtime_code(&word.runtime.clone())?;
+		                        comp.add_word(&word)?;
 		                    }
                     	}
-                    	//reborrow s since we did not call self
-                    	None => return Err(PalError::Missingword(unsafe{&*sp}))
+                    	None => match parse_literal(comp.lex,unsafe{&*sp}) {
+                    		Some((tp,value)) => comp.add_literal(tp,value),
+                    		//reborrow s since we did not call self
+                    		None => return Err(PalError::Missingword(unsafe{&*sp}))
+                    	}
                     }
                 }
             }
@@ -272,7 +412,81 @@ impl Vm<'_, '_, '_> {
     /// # Safety
     /// the pointer past must point to valid code
     /// the stacks must contain the correct inputs
-    pub unsafe fn execute_code(&mut self, mut code: *const Code) {
+    #[inline(always)]
+    pub unsafe fn execute_code(&mut self, code: *const Code) {
+        #[cfg(feature = "fast_dispatch")]
+        unsafe {
+            self.execute_code_fast(code)
+        }
+        #[cfg(not(feature = "fast_dispatch"))]
+        unsafe {
+            self.execute_code_plain(code)
+        }
+    }
+
+    /// the dispatch loop that keeps the stack heads in locals
+    /// fast buildins and ret never see the Vm so the heads stay in registers
+    /// other buildins get the heads written back before they run and reloaded after
+    /// # Safety
+    /// same as [`Vm::execute_code`]
+    pub unsafe fn execute_code_fast(&mut self, mut code: *const Code) {
+        unsafe {
+            let mut sp = self.param_stack.get_head();
+            let mut rp = self.return_stack.get_head();
+            //a slow buildin may switch stacks so these are reloaded with the heads
+            let mut p_bounds = (self.param_stack.get_above(), self.param_stack.get_end());
+            let mut r_bounds = (self.return_stack.get_above(), self.return_stack.get_end());
+            loop {
+                let mut primitive = (*code).f.load(Ordering::Relaxed);
+                while primitive.is_none() {
+                    #[cfg(not(feature = "unchecked_overflow"))]
+                    if rp == r_bounds.1 {
+                        panic!("stack overflow")
+                    }
+                    rp = rp.sub(1);
+                    *rp = code;
+                    code = (*code).param.load(Ordering::Relaxed) as *const _;
+                    primitive = (*code).f.load(Ordering::Relaxed);
+                }
+                let f = primitive.unwrap_unchecked();
+
+                if (*code).is(op::fast_call) {
+                    let fast = &*((*code).param.load(Ordering::Relaxed) as *const FastCode);
+                    sp = (fast.f)(fast.param, sp, p_bounds.0, p_bounds.1);
+                } else {
+                    if (*code).is(op::ret) {
+                        code = ptr::null();
+                    } else {
+                        self.param_stack.set_head(sp);
+                        self.return_stack.set_head(rp);
+                        code = f(code, self);
+                        sp = self.param_stack.get_head();
+                        rp = self.return_stack.get_head();
+                        p_bounds = (self.param_stack.get_above(), self.param_stack.get_end());
+                        r_bounds = (self.return_stack.get_above(), self.return_stack.get_end());
+                    }
+
+                    if code.is_null() {
+                        //same as the plain loop the outer frame has nowhere to go
+                        if r_bounds.0.offset_from(rp) <= 1 {
+                            self.param_stack.set_head(sp);
+                            self.return_stack.set_head(rp);
+                            unwrap_under(self.return_stack.pop());
+                            return;
+                        }
+                        code = *rp;
+                        rp = rp.add(1);
+                    }
+                }
+                code = code.wrapping_add(1);
+            }
+        }
+    }
+
+    /// the original dispatch loop, every buildin gets the full Vm
+    /// # Safety
+    /// same as [`Vm::execute_code`]
+    pub unsafe fn execute_code_plain(&mut self, mut code: *const Code) {
         unsafe {
             //compiler can load the return stack
             loop {